@@ -1,82 +1,330 @@
+use crate::config::{BaseUrl, Policies};
+use crate::logging;
 use crate::make;
-use crate::notif::end_of_task;
-use crossbeam_channel::{unbounded, Receiver, Sender};
-use matrix_bot_api::handlers::{HandleResult, Message, MessageHandler};
-use matrix_bot_api::{ActiveBot, MatrixBot, MessageType, Room};
+use crate::registry::{self, registry, Policy, Spec};
+use crate::transport::{Event, Incoming, Relation, Transport};
+use crossbeam_channel::Sender;
 use shell::expr::{parse_command, Command};
-use shell::store::{ConnectedStore, Store};
-use std::path::Path;
+use shell::metrics;
+use shell::parser_ext::CommandError;
+use shell::store::{ConnectedStore, Role, Store};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::thread;
+use tracing::{debug, error, info_span, warn};
 
 pub struct CommandHandler {
     chan: Sender<String>,
     arc_store: Arc<Mutex<Store>>,
-    last_message_id: String,
-    base_url: String,
+    base_url: BaseUrl,
+    policies: Policies,
 }
 
 pub struct Context<'a> {
     pub store: &'a mut ConnectedStore,
     pub room_id: String,
     pub base_url: String,
+    /// Confirmation policies set in the config, by command name.
+    pub policies: HashMap<String, Policy>,
 }
 
-fn exec_command(context: &mut Context, user: String, body: String) -> Option<(String, String)> {
-    match parse_command(&body) {
-        Ok(com) => {
-            let u = user;
-            match com {
-                Command::Ping => Some(("pong".into(), String::new())),
-                Command::List => make::list(context),
-                Command::Add(project) => make::new(context, u, project),
-                Command::Do(project, task, duration) => {
-                    make::start(context, u, duration, project, task)
-                }
-                Command::Done(project, task, duration) => {
-                    make::done(context, u, duration, project, task)
-                }
-                Command::Stop => make::stop(context, u),
-                Command::More(d) => make::more(context, u, d),
-                Command::Digest(project) => make::digest(context, project),
-                Command::Since(since) => make::since(context, u, since),
-                Command::Switch(project, task) => make::switch(context, u, project, task),
-                Command::Deadline(project, end) => make::deadline(context, project, end),
-                Command::Provision(project, d) => make::provision(context, project, d),
-                Command::Complete(project, end) => make::complete(context, project, end),
-                Command::Note(project, content) => make::note(context, u, project, content),
-                Command::Meta(project) => make::meta(context, u, project),
-                Command::Parent(child, parent) => make::parent(context, u, child, parent),
-                Command::Avail(start, end, weekly) => make::avail(context, u, start, end, weekly),
-                Command::Intent(project_name, amount) => {
-                    make::intent(context, u, project_name, amount)
-                }
-                Command::Help => make::help(context),
+/// The deny reply when `role` is not enough to run the command of `spec`.
+/// Commands that change records need the admin role when they target
+/// another user than the sender, or share a task with someone else.
+fn check_role(
+    spec: &Spec,
+    command: &Command,
+    user: &str,
+    role: Role,
+) -> Option<Option<(String, String)>> {
+    let for_other = command
+        .target()
+        .into_iter()
+        .chain(command.with().iter().map(String::as_str))
+        .any(|other| other != user);
+    let (name, required) = match for_other && spec.role >= Role::Member {
+        true => (format!("{} for someone else", spec.name), Role::Admin),
+        false => (String::from(spec.name), spec.role),
+    };
+    match role < required {
+        true => Some(make::denied(&name, required, role)),
+        false => None,
+    }
+}
+
+fn count(parsed: &Result<(usize, Command), CommandError>, specs: &[Spec]) {
+    match parsed {
+        Ok((index, _)) => metrics::incr(metrics::COMMANDS, &[("command", specs[*index].name)]),
+        Err(err) => metrics::incr(
+            metrics::PARSE_FAILURES,
+            &[("command", err.command.as_deref().unwrap_or("unknown"))],
+        ),
+    }
+}
+
+/// The question to ask before running `command`, if its policy says so.
+fn confirmation(
+    context: &mut Context,
+    spec: &Spec,
+    command: &Command,
+    user: &str,
+) -> Option<String> {
+    let policy = context
+        .policies
+        .get(spec.name)
+        .copied()
+        .unwrap_or(spec.confirm);
+    make::confirm::question(context, spec, policy, command, user)
+}
+
+/// Each line starting with `!` is a command, the other lines belong to the
+/// command above them, as the body of a `!note` would.
+fn command_lines(body: &str) -> Vec<String> {
+    body.lines().fold(Vec::<String>::new(), |mut lines, line| {
+        match (line.trim_start().starts_with('!'), lines.last_mut()) {
+            (false, Some(last)) => {
+                last.push('\n');
+                last.push_str(line);
             }
+            (false, None) => {}
+            (true, _) => lines.push(String::from(line.trim())),
         }
-        Err(err) => make::parse_error(&err),
+        lines
+    })
+}
+
+/// Commands whose whole effect is the records they add, which editing
+/// their message can take back and write anew.
+fn replaceable(command: &Command) -> bool {
+    matches!(command, Command::Do(..) | Command::Done(..))
+}
+
+/// Whether editing a message into `body` can be applied: each of its
+/// commands, if any, is one of those an edit replaces.
+fn replaces(body: &str) -> bool {
+    let grammars: Vec<_> = registry().iter().map(|spec| spec.grammar).collect();
+    command_lines(body).iter().all(
+        |line| matches!(parse_command(line, &grammars), Ok((_, command)) if replaceable(&command)),
+    )
+}
+
+/// The command a reply to the bot stands for, when it starts with the name
+/// of one without its `!`, as in "since 1day"; other replies are just chat.
+fn reply_command(body: &str) -> Option<String> {
+    let name = body.split_whitespace().next()?;
+    registry::find(&registry(), name).map(|_| format!("!{}", body.trim()))
+}
+
+/// Runs the commands of a message, asking first for those that need a
+/// confirmation unless the sender `confirmed` it already.
+pub fn exec_command(
+    context: &mut Context,
+    user: String,
+    body: String,
+    confirmed: bool,
+) -> Option<(String, String)> {
+    let specs = registry();
+    let role = context
+        .store
+        .select_role_for_user(&user)
+        .unwrap_or(Role::Viewer);
+    let grammars: Vec<_> = specs.iter().map(|spec| spec.grammar).collect();
+    let lines = command_lines(&body);
+    if lines.len() < 2 {
+        let parsed = parse_command(body.trim(), &grammars);
+        count(&parsed, &specs);
+        if !matches!(&parsed, Ok((_, command)) if replaceable(command)) {
+            context.store.detach_event();
+        }
+        return match parsed {
+            Ok((index, command)) => match check_role(&specs[index], &command, &user, role) {
+                Some(denied) => denied,
+                None => match confirmed {
+                    true => (specs[index].handler)(context, user, command),
+                    false => match confirmation(context, &specs[index], &command, &user) {
+                        Some(question) => make::confirm::ask(context, user, body, &[question]),
+                        None => (specs[index].handler)(context, user, command),
+                    },
+                },
+            },
+            Err(err) => make::parse_error(body.trim(), &err, &specs),
+        };
+    }
+
+    // All lines must parse and be allowed before any runs, a batch is
+    // all-or-nothing.
+    let parsed: Vec<_> = lines
+        .iter()
+        .map(|line| parse_command(line, &grammars))
+        .collect();
+    parsed.iter().for_each(|result| count(result, &specs));
+    let denials: Vec<_> = parsed
+        .iter()
+        .map(|result| match result {
+            Ok((index, command)) => check_role(&specs[*index], command, &user, role),
+            Err(_) => None,
+        })
+        .collect();
+    let runs = parsed.iter().all(Result::is_ok) && denials.iter().all(Option::is_none);
+    if !parsed
+        .iter()
+        .all(|result| matches!(result, Ok((_, command)) if replaceable(command)))
+    {
+        context.store.detach_event();
+    }
+    if runs && !confirmed {
+        let questions: Vec<String> = parsed
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .filter_map(|(index, command)| confirmation(context, &specs[*index], command, &user))
+            .collect();
+        if !questions.is_empty() {
+            return make::confirm::ask(context, user, body, &questions);
+        }
+    }
+    let outcomes: Vec<make::Outcome> = if runs {
+        parsed
+            .into_iter()
+            .zip(lines.iter())
+            .filter_map(|(result, line)| result.ok().map(|parsed| (parsed, line)))
+            .map(|((index, command), line)| {
+                make::Outcome::Ran(line, (specs[index].handler)(context, user.clone(), command))
+            })
+            .collect()
+    } else {
+        parsed
+            .iter()
+            .zip(denials)
+            .zip(lines.iter())
+            .map(|((result, denial), line)| match (result, denial) {
+                (Err(err), _) => make::Outcome::Failed(line, make::parse_error(line, err, &specs)),
+                (Ok(_), Some(denied)) => make::Outcome::Denied(line, denied),
+                (Ok(_), None) => make::Outcome::Skipped(line),
+            })
+            .collect()
+    };
+    make::batch(&outcomes)
+}
+
+/// Tells that the records of an edited message were replaced, along with
+/// the reply to its new text.
+fn corrected(replaced: usize, reply: Option<(String, String)>) -> Option<(String, String)> {
+    let note = format!("Replaced the {} record(s) of the edited message.", replaced);
+    match (replaced, reply) {
+        (0, reply) => reply,
+        (_, None) => Some((note, String::new())),
+        (_, Some((text, html))) if html.is_empty() => Some((format!("{}\n{}", note, text), html)),
+        (_, Some((text, html))) => Some((
+            format!("{}\n{}", note, text),
+            format!("<p>{}</p>{}", note, html),
+        )),
+    }
+}
+
+/// Tells that an edit recorded nothing, so the edited message still stands,
+/// along with the reply to its new text.
+fn kept(reply: Option<(String, String)>) -> Option<(String, String)> {
+    let note = "The edit recorded nothing, the edited message still stands.";
+    match reply {
+        None => Some((String::from(note), String::new())),
+        Some((text, html)) if html.is_empty() => Some((format!("{}\n{}", note, text), html)),
+        Some((text, html)) => Some((
+            format!("{}\n{}", note, text),
+            format!("<p>{}</p>{}", note, html),
+        )),
+    }
+}
+
+/// Runs `body` in place of the message `original` it edits. The records of
+/// `original` are replaced only when the edit records something itself, or
+/// removed when it holds no command anymore; they are kept otherwise.
+fn edit_command(
+    context: &mut Context,
+    original: &str,
+    user: String,
+    body: String,
+) -> Option<(String, String)> {
+    let room_id = context.room_id.clone();
+    let base_url = context.base_url.clone();
+    let policies = context.policies.clone();
+    let outcome = context.store.tentative(|store| {
+        let replaced = match store.replace_event(original, &user)? {
+            Some(replaced) => replaced,
+            None => return Ok((None, false)),
+        };
+        let mut context = Context {
+            store,
+            room_id,
+            base_url,
+            policies,
+        };
+        let removed = !body.starts_with('!');
+        let reply = match removed {
+            true => None,
+            false => exec_command(&mut context, user.clone(), body.clone(), false),
+        };
+        let recorded = removed || !context.store.select_do_for_event(original)?.is_empty();
+        let asked = matches!(
+            context.store.select_pending_for_user(&user)?,
+            Some(pending) if pending.body == body
+        );
+        let reply = match (recorded, asked) {
+            (true, _) => corrected(replaced, reply),
+            (false, true) => Some((
+                String::from(
+                    "The edited message still stands, edits can't wait for a !yes: send a new message instead.",
+                ),
+                String::new(),
+            )),
+            (false, false) => kept(reply),
+        };
+        Ok((Some(reply), recorded))
+    });
+    match outcome {
+        Ok(Some(reply)) => reply,
+        Ok(None) => Some((
+            String::from("Only who sent a message can correct it by editing it."),
+            String::new(),
+        )),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
     }
 }
 
-impl MessageHandler for CommandHandler {
-    fn init_handler(&mut self, _bot: &ActiveBot) {
-        // println!("CommandHandler::init_handler");
-        // bot.join_room(&self.room_id);
-        // let path = self.arc_store.get_path();
-        // Store::new(path)
-        //     .map(|store| {
-        //         start_notifications(
-        //             path,
-        //             Notifier {
-        //                 store: store,
-        //                 bot: bot.clone(),
-        //             },
-        //         )
-        //     })
-        //     .expect("Failed at creating a store for notification handler");
-    }
-
-    fn handle_join(&mut self, bot: &ActiveBot, room: &Room) -> HandleResult {
+impl CommandHandler {
+    pub fn new<B: Into<BaseUrl>, P: Into<Policies>>(
+        arc_store: Arc<Mutex<Store>>,
+        base_url: B,
+        policies: P,
+        chan: Sender<String>,
+    ) -> Self {
+        CommandHandler {
+            base_url: base_url.into(),
+            policies: policies.into(),
+            chan,
+            arc_store,
+        }
+    }
+
+    pub fn handle_event<T: Transport>(&mut self, transport: &T, event: Event) {
+        match event {
+            Event::Join(room) => self.handle_join(transport, &room),
+            Event::Leave(room) => self.handle_leave(&room),
+            Event::Message(message) => self.handle_message(transport, &message),
+            Event::Sync(since) => self.handle_sync(&since),
+        }
+    }
+
+    fn handle_sync(&mut self, since: &str) {
+        match self.arc_store.lock() {
+            Ok(mut store) => {
+                if let Err(err) = store.update_sync_token(since) {
+                    error!("Could not save the sync token: {}", err);
+                }
+            }
+            Err(_) => error!("Could not lock the store"),
+        }
+    }
+
+    fn handle_join<T: Transport>(&mut self, transport: &T, room: &str) {
         let success = "
         Hello there! I'm ready to take commands, 
         type `!help` for help
@@ -87,93 +335,1124 @@ impl MessageHandler for CommandHandler {
         to kick me out and invite me again 
         in order to fix this.
         ";
+        let span = info_span!("room", room = %room);
+        let _entered = span.enter();
         if let Ok(mut store) = self.arc_store.lock() {
-            if store.connect_or_create(&room.id).is_ok() {
-                bot.send_message(success, &room.id, MessageType::RoomNotice)
+            if store.connect_or_create(room).is_ok() {
+                transport.send_notice(room, success)
             } else {
-                bot.send_message(error, &room.id, MessageType::RoomNotice)
+                transport.send_notice(room, error)
             }
         } else {
-            bot.send_message(error, &room.id, MessageType::RoomNotice)
+            transport.send_notice(room, error)
         }
+    }
 
-        HandleResult::StopHandling
+    fn handle_leave(&mut self, room: &str) {
+        self.chan.try_send(format!("[{}] left", room)).unwrap_or(());
     }
 
-    fn handle_message(&mut self, bot: &ActiveBot, message: &Message) -> HandleResult {
-        println!(">>>>>> \nhandle_message {}\n", &message.body);
+    fn handle_message<T: Transport>(&mut self, transport: &T, message: &Incoming) {
+        let span = info_span!("room", room = %message.room);
+        let _entered = span.enter();
+        debug!(
+            id = %message.id,
+            sender = %message.sender,
+            body = %logging::body(&message.body),
+            "message"
+        );
         let user = message.sender.clone();
-        let body = message.body.clone();
+        let body = match (
+            &message.relation,
+            message.body.trim_start().starts_with('!'),
+        ) {
+            (Some(Relation::ReplyToBot), false) => {
+                reply_command(&message.body).unwrap_or_else(|| message.body.clone())
+            }
+            _ => message.body.clone(),
+        };
+        let original = match &message.relation {
+            Some(Relation::Edit(original)) => Some(original.clone()),
+            _ => None,
+        };
         let room = message.room.clone();
-        let base_url = self.base_url.clone();
-        if message.id == self.last_message_id {
-            return HandleResult::StopHandling;
-        }
-        self.last_message_id = message.id.clone();
-        if body.chars().next().unwrap_or('_') != '!' {
+        let base_url = self.base_url.get();
+        let policies = self.policies.get();
+        let is_command = body.starts_with('!');
+        if !is_command && original.is_none() {
             self.chan
-                .try_send(format!("[{}] {}> {}", room, user, body))
+                .try_send(format!("[{}] {}> {}", room, user, logging::body(&body)))
                 .unwrap_or(());
-            return HandleResult::ContinueHandling;
+            return;
         }
         if let Ok(mut store) = self.arc_store.lock() {
             if let Ok(connected) = store.connected(&room) {
-                let mut context = Context {
-                    store: connected,
-                    room_id: room.clone(),
-                    base_url,
-                };
-
-                match exec_command(&mut context, user, body) {
-                    Some((ref msg, ref html)) if html.is_empty() => {
-                        bot.send_message(msg, &room, MessageType::RoomNotice)
+                let reply = connected.once(&message.id, |store| {
+                    let mut context = Context {
+                        store,
+                        room_id: room.clone(),
+                        base_url,
+                        policies,
+                    };
+                    match &original {
+                        None => exec_command(&mut context, user, body, false),
+                        Some(_) if !replaces(&body) => Some((
+                            String::from(
+                                "Only edits of !do and !done are applied, send a new message for anything else.",
+                            ),
+                            String::new(),
+                        )),
+                        Some(original) => edit_command(&mut context, original, user, body),
                     }
-                    Some((ref msg, ref html)) => {
-                        bot.send_html_message(msg, html, &room, MessageType::RoomNotice)
+                });
+
+                match reply {
+                    Ok(Some(Some((ref msg, ref html)))) if html.is_empty() => {
+                        transport.send_notice(&room, msg)
                     }
-                    None => {}
+                    Ok(Some(Some((ref msg, ref html)))) => transport.send_html(&room, msg, html),
+                    Ok(Some(None)) => {}
+                    Ok(None) => debug!("Already handled {}, skipping", &message.id),
+                    Err(err) => error!("Could not handle {}: {}", &message.id, err),
                 };
             } else {
-                println!("Ouch, could not get a connection for: {}", &room);
+                warn!("Could not get a connection");
             }
         } else {
-            println!("Ouch, could not lock the store");
+            error!("Could not lock the store");
         }
-
-        HandleResult::ContinueHandling
     }
 }
 
-pub fn start_bot(
-    path: &Path,
-    homeserver: &str,
-    user: &str,
-    password: &str,
-    base_url: &str,
-) -> Receiver<String> {
-    let (s, r) = unbounded::<String>();
-    let h = String::from(homeserver);
-    let u = String::from(user);
-    let p = String::from(password);
-    let base_url = String::from(base_url);
-    let store = Store::new(String::from(path.to_string_lossy()));
-    let arc_store = Arc::new(Mutex::new(store));
-
-    thread::spawn(move || {
-        let mut bot = MatrixBot::new(CommandHandler {
-            base_url,
-            chan: s,
-            last_message_id: String::new(),
-            arc_store: arc_store.clone(),
-        });
-
-        end_of_task(bot.get_activebot_clone(), arc_store.clone());
-        bot.set_verbose(false);
-        bot.run(&u, &p, &h);
-    });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
 
-    r
-}
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
 
-#[cfg(test)]
-mod tests {}
+    impl Recorder {
+        fn last(&self) -> Option<String> {
+            self.0.lock().ok().and_then(|sent| sent.last().cloned())
+        }
+    }
+
+    impl Transport for Recorder {
+        fn send_text(&self, _room: &str, text: &str) {
+            self.0.lock().unwrap().push(String::from(text));
+        }
+        fn send_notice(&self, _room: &str, text: &str) {
+            self.0.lock().unwrap().push(String::from(text));
+        }
+        fn send_html(&self, _room: &str, text: &str, _html: &str) {
+            self.0.lock().unwrap().push(String::from(text));
+        }
+    }
+
+    fn say(handler: &mut CommandHandler, transport: &Recorder, room: &str, id: &str, body: &str) {
+        say_as(handler, transport, room, id, "@alice:localhost", body);
+    }
+
+    fn say_as(
+        handler: &mut CommandHandler,
+        transport: &Recorder,
+        room: &str,
+        id: &str,
+        sender: &str,
+        body: &str,
+    ) {
+        handler.handle_event(
+            transport,
+            Event::Message(Incoming {
+                id: String::from(id),
+                room: String::from(room),
+                sender: String::from(sender),
+                body: String::from(body),
+                relation: None,
+            }),
+        );
+    }
+
+    /// A room in a directory of its own, removed when dropped, so that tests
+    /// running side by side share no database, not even the sync one.
+    struct TempRoom {
+        dir: std::path::PathBuf,
+        name: String,
+        store: Arc<Mutex<Store>>,
+    }
+
+    impl Drop for TempRoom {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.dir).unwrap_or(());
+        }
+    }
+
+    /// A room the bot has joined, with the handler and what it sent.
+    fn room(name: &str) -> (TempRoom, CommandHandler, Recorder) {
+        let dir = std::env::temp_dir().join(format!("pearls-bot-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = Arc::new(Mutex::new(Store::new(String::from(dir.to_string_lossy()))));
+        let (s, _r) = unbounded::<String>();
+        let mut handler = CommandHandler::new(
+            store.clone(),
+            String::from("localhost"),
+            Policies::default(),
+            s,
+        );
+        let transport = Recorder::default();
+        let name = format!("{}.db", name);
+        handler.handle_event(&transport, Event::Join(name.clone()));
+        (TempRoom { dir, name, store }, handler, transport)
+    }
+
+    #[test]
+    fn reports_hours_per_tag() {
+        let (room, mut handler, transport) = room("tags");
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot dev 2h #billable",
+        );
+        say(&mut handler, &transport, &room.name, "3", "!tags 1day");
+        let tags = transport.last();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "!since 1day #support",
+        );
+        let since = transport.last();
+
+        assert!(tags.unwrap_or_default().starts_with("#billable\t"));
+        assert_eq!(since, Some(String::new()));
+    }
+
+    #[test]
+    fn runs_a_batch_all_or_nothing() {
+        let (room, mut handler, transport) = room("batch");
+
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "1",
+            "!new ac/bot\n!dne ac/bot dev 1h",
+        );
+        let refused = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!ls\n!new ac/bot\n!ping",
+        );
+        let accepted = transport.last().unwrap_or_default();
+
+        assert!(refused.starts_with("Nothing was done, I could not understand line 2."));
+        assert!(refused.contains("> !new ac/bot\nnot run"));
+        assert!(accepted.contains("> !new ac/bot\n"));
+        assert!(!accepted.contains("> !new ac/bot\nnot run"));
+        assert!(accepted.ends_with("> !ping\npong"));
+    }
+
+    #[test]
+    fn checks_roles_before_running() {
+        let (room, mut handler, transport) = room("roles");
+        let bob = "@bob:localhost";
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!grant @alice:localhost admin",
+        );
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            "!grant @alice:localhost member",
+        );
+        let last_admin = transport.last().unwrap_or_default();
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            bob,
+            "!deadline ac/bot 2030-01-01",
+        );
+        let denied = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "5",
+            "!grant @bob:localhost viewer",
+        );
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "6",
+            bob,
+            "!ls\n!done ac/bot dev 1h",
+        );
+        let batch = transport.last().unwrap_or_default();
+        say_as(&mut handler, &transport, &room.name, "7", bob, "!ping");
+        let allowed = transport.last();
+
+        assert!(last_admin.contains("last admin"));
+        assert!(denied.starts_with("!deadline requires the admin role, you are a member"));
+        assert!(batch.starts_with("Nothing was done, you are not allowed to run line 2."));
+        assert_eq!(allowed, Some(String::from("pong")));
+    }
+
+    #[test]
+    fn logs_time_for_someone_else() {
+        let (room, mut handler, transport) = room("behalf");
+        let bob = "@bob:localhost";
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!grant @alice:localhost admin",
+        );
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            bob,
+            "!done @alice:localhost ac/bot dev 1h",
+        );
+        let denied = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "!done @bob:localhost ac/bot dev 1h",
+        );
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "5",
+            bob,
+            "!since 1day",
+        );
+        let own = transport.last().unwrap_or_default();
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "6",
+            bob,
+            "!since @alice:localhost 1day",
+        );
+        let other = transport.last();
+
+        assert!(denied.starts_with("!done for someone else requires the admin role"));
+        assert!(own.ends_with("(entered by @alice)"));
+        assert_eq!(other, Some(String::new()));
+    }
+
+    #[test]
+    fn shares_a_task_only_when_allowed_and_free() {
+        let (room, mut handler, transport) = room("sharing");
+        let bob = "@bob:localhost";
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!grant @alice:localhost admin",
+        );
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            bob,
+            "!done ac/bot dev 1h with @alice:localhost",
+        );
+        let denied = transport.last().unwrap_or_default();
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            bob,
+            "!do ac/bot dev 1h",
+        );
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "5",
+            "!done ac/bot meeting 1h with @bob:localhost",
+        );
+        let busy = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "6", "!since 1day");
+        let left = transport.last();
+
+        assert!(denied.starts_with("!done for someone else requires the admin role"));
+        assert_eq!(
+            busy,
+            "@bob is already doing dev, you're covered, or tricky :)"
+        );
+        assert_eq!(left, Some(String::new()));
+    }
+
+    #[test]
+    fn shares_a_task_with_others() {
+        let (room, mut handler, transport) = room("group");
+        let bob = "@bob:localhost";
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot meeting 1h with @bob:localhost @carol:localhost",
+        );
+        let recorded = transport.last().unwrap_or_default();
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            bob,
+            "!since 1day",
+        );
+        let shared = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "4", "!amend 30m");
+        say(&mut handler, &transport, &room.name, "5", "!yes");
+        let amended = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "6", "!undo");
+        say(&mut handler, &transport, &room.name, "7", "!yes");
+        let undone = transport.last().unwrap_or_default();
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "8",
+            bob,
+            "!since 1day",
+        );
+        let left = transport.last();
+
+        assert_eq!(recorded, "Well recorded, with @bob and @carol.");
+        assert!(shared.contains("\twith @alice, @carol\t(entered by @alice)"));
+        assert!(amended.ends_with("for you, @bob and @carol."));
+        assert_eq!(undone, "Removed ac/bot meeting for you, @bob and @carol.");
+        assert_eq!(left, Some(String::new()));
+    }
+
+    #[test]
+    fn switches_with_the_tags_of_the_task() {
+        let (room, mut handler, transport) = room("switch");
+        let current_tags = || {
+            let mut store = room.store.lock().unwrap();
+            let connected = store.connected(&room.name).unwrap();
+            let current = connected
+                .select_current_task_for(String::from("@alice:localhost"))
+                .unwrap_or_default();
+            let rec = current.first().unwrap();
+            (
+                rec.task.clone(),
+                connected.select_tags_for_do(rec.id).unwrap_or_default(),
+            )
+        };
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!do ac/bot dev 2h #billable",
+        );
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            "!switch ac/bot review",
+        );
+        let kept = current_tags();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "!switch ac/bot support #internal",
+        );
+        let given = current_tags();
+
+        assert_eq!(
+            kept,
+            (String::from("review"), vec![String::from("billable")])
+        );
+        assert_eq!(
+            given,
+            (String::from("support"), vec![String::from("internal")])
+        );
+    }
+
+    #[test]
+    fn corrects_an_edited_command() {
+        let (room, mut handler, transport) = room("edit");
+        let edit = |id: &str, original: &str, body: &str| {
+            Event::Message(Incoming {
+                id: String::from(id),
+                room: room.name.clone(),
+                sender: String::from("@alice:localhost"),
+                body: String::from(body),
+                relation: Some(Relation::Edit(String::from(original))),
+            })
+        };
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot dev 2h",
+        );
+        handler.handle_event(&transport, edit("3", "2", "!done ac/bot dev 1h"));
+        let replaced = transport.last().unwrap_or_default();
+        handler.handle_event(&transport, edit("4", "2", "!done ac/bot review 30m"));
+        handler.handle_event(
+            &transport,
+            Event::Message(Incoming {
+                id: String::from("5"),
+                room: room.name.clone(),
+                sender: String::from("@alice:localhost"),
+                body: String::from("since 1day"),
+                relation: Some(Relation::ReplyToBot),
+            }),
+        );
+        let since = transport.last().unwrap_or_default();
+        let records = room
+            .store
+            .lock()
+            .unwrap()
+            .connected(&room.name)
+            .and_then(|connected| {
+                connected.select_user(
+                    String::from("@alice:localhost"),
+                    std::time::UNIX_EPOCH,
+                    None,
+                )
+            })
+            .unwrap_or_default();
+
+        assert!(replaced.starts_with("Replaced the 1 record(s) of the edited message.\n"));
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].task, "review");
+        assert_eq!(records[0].duration, std::time::Duration::from_secs(30 * 60));
+        assert!(since.starts_with("ac/bot\treview\t"));
+    }
+
+    #[test]
+    fn runs_only_replies_that_name_a_command() {
+        let (room, mut handler, transport) = room("replies");
+        let reply = |id: &str, body: &str| {
+            Event::Message(Incoming {
+                id: String::from(id),
+                room: room.name.clone(),
+                sender: String::from("@alice:localhost"),
+                body: String::from(body),
+                relation: Some(Relation::ReplyToBot),
+            })
+        };
+
+        handler.handle_event(&transport, reply("1", "ping"));
+        let pong = transport.last();
+        handler.handle_event(&transport, reply("2", "thanks!"));
+        handler.handle_event(&transport, reply("3", "ok"));
+        let after_chat = transport.last();
+
+        assert_eq!(pong, Some(String::from("pong")));
+        assert_eq!(after_chat, pong);
+    }
+
+    #[test]
+    fn keeps_the_records_of_an_edit_that_records_nothing() {
+        let (room, mut handler, transport) = room("kept");
+        let edit = |id: &str, body: &str| {
+            Event::Message(Incoming {
+                id: String::from(id),
+                room: room.name.clone(),
+                sender: String::from("@alice:localhost"),
+                body: String::from(body),
+                relation: Some(Relation::Edit(String::from("2"))),
+            })
+        };
+        let tasks = || {
+            room.store
+                .lock()
+                .unwrap()
+                .connected(&room.name)
+                .and_then(|connected| connected.select_do_for_event("2"))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|rec| (rec.project, rec.task))
+                .collect::<Vec<_>>()
+        };
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot dev 2h",
+        );
+        handler.handle_event(&transport, edit("3", "!done ac/bto dev 1h"));
+        let unknown = transport.last().unwrap_or_default();
+        let after_unknown = tasks();
+        handler.handle_event(&transport, edit("4", "!done ac/bot review 1h"));
+        let replaced = transport.last().unwrap_or_default();
+
+        assert!(
+            unknown.starts_with("The edit recorded nothing, the edited message still stands.\n"),
+            "{}",
+            unknown
+        );
+        assert_eq!(
+            after_unknown,
+            vec![(String::from("ac/bot"), String::from("dev"))]
+        );
+        assert!(
+            replaced.starts_with("Replaced the 1 record(s)"),
+            "{}",
+            replaced
+        );
+        assert_eq!(
+            tasks(),
+            vec![(String::from("ac/bot"), String::from("review"))]
+        );
+    }
+
+    #[test]
+    fn refuses_edits_it_cannot_replace() {
+        let (room, mut handler, transport) = room("refused");
+        let edit = |id: &str, sender: &str, original: &str, body: &str| {
+            Event::Message(Incoming {
+                id: String::from(id),
+                room: room.name.clone(),
+                sender: String::from(sender),
+                body: String::from(body),
+                relation: Some(Relation::Edit(String::from(original))),
+            })
+        };
+        let tasks = || {
+            room.store
+                .lock()
+                .unwrap()
+                .connected(&room.name)
+                .and_then(|connected| {
+                    connected.select_user(
+                        String::from("@alice:localhost"),
+                        std::time::UNIX_EPOCH,
+                        None,
+                    )
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .map(|rec| (rec.task, rec.duration.as_secs()))
+                .collect::<Vec<_>>()
+        };
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot review 1h",
+        );
+        handler.handle_event(
+            &transport,
+            edit("3", "@bob:localhost", "2", "!done ac/bot review 5h"),
+        );
+        let other = transport.last().unwrap_or_default();
+        let after_other = tasks();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "!do ac/bot dev 2h",
+        );
+        say(&mut handler, &transport, &room.name, "5", "!more 30m");
+        let before = tasks();
+        handler.handle_event(&transport, edit("6", "@alice:localhost", "5", "!more 1h"));
+        let more = transport.last().unwrap_or_default();
+        let after_more = tasks();
+
+        assert_eq!(
+            more,
+            "Only edits of !do and !done are applied, send a new message for anything else."
+        );
+        assert_eq!(before, after_more);
+        assert_eq!(
+            other,
+            "Only who sent a message can correct it by editing it."
+        );
+        assert!(after_other.contains(&(String::from("review"), 3600)));
+        assert!(!after_other.iter().any(|(_, secs)| *secs == 5 * 3600));
+    }
+
+    #[test]
+    fn takes_days_off_and_imports_holidays() {
+        let (room, mut handler, transport) = room("off");
+        let file = format!("pearls-bot-{}.ics", std::process::id());
+        std::fs::create_dir_all(room.dir.join("holidays")).unwrap();
+        std::fs::write(
+            room.dir.join("holidays").join(&file),
+            "BEGIN:VCALENDAR\nBEGIN:VEVENT\nDTSTART;VALUE=DATE:20261225\nSUMMARY:Christmas\nEND:VEVENT\nEND:VCALENDAR\n",
+        )
+        .unwrap();
+
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "1",
+            "!off 2026-12-21 2027-01-03",
+        );
+        let off = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!holidays ../pearls.toml",
+        );
+        let outside = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            &format!("!holidays {}", file),
+        );
+        let imported = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            &format!("!holidays {}", file),
+        );
+        let absences = room
+            .store
+            .lock()
+            .unwrap()
+            .connected(&room.name)
+            .and_then(|connected| connected.select_absence_all())
+            .unwrap_or_default();
+
+        assert_eq!(off, "Off from 2026-12-21 to 2027-01-03, enjoy.");
+        assert_eq!(outside, "../pearls.toml is not a plain file name.");
+        assert!(imported.starts_with("Imported 1 holidays from "));
+        assert_eq!(absences.len(), 2);
+        assert_eq!(absences[1].username, None);
+        assert_eq!(absences[1].label, "Christmas");
+    }
+
+    #[test]
+    fn reports_the_plan() {
+        let (room, mut handler, transport) = room("plan");
+
+        say(&mut handler, &transport, &room.name, "1", "!plan");
+        let empty = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "2", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            "!deadline ac/bot 2099-12-31",
+        );
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "!intent ac/bot 10h",
+        );
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "5",
+            "!avail today 2099-12-31 40h",
+        );
+        say(&mut handler, &transport, &room.name, "6", "!plan");
+        let plan = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "7",
+            "!whatif @alice:localhost 2000h on ac/new by 2099-01-01",
+        );
+        let whatif = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "8", "!plan");
+        let unchanged = transport.last().unwrap_or_default();
+
+        assert!(empty.starts_with("Nothing planned"));
+        assert!(plan.starts_with("Planned with the deadline strategy:\nac/bot\t"));
+        assert!(plan.contains("\t2099-12-31\t"));
+        assert!(plan.ends_with("days of slack"));
+        assert!(whatif.starts_with("What moves:\nac/bot\t"), "{}", whatif);
+        assert!(whatif.contains("slips"));
+        assert!(whatif.contains("\nac/new\t"));
+        assert_eq!(plan, unchanged);
+    }
+
+    #[test]
+    fn plans_with_the_room_strategy() {
+        let (room, mut handler, transport) = room("strategy");
+        let finish = |plan: &str, project: &str| {
+            plan.lines()
+                .find(|line| line.starts_with(project))
+                .and_then(|line| line.split('\t').nth(2))
+                .map(String::from)
+                .unwrap_or_default()
+        };
+
+        for (id, body) in [
+            "!new ac/bot",
+            "!new ac/web",
+            "!deadline ac/bot 2099-06-30",
+            "!deadline ac/web 2099-12-31",
+            "!intent ac/bot 20h",
+            "!intent ac/web 20h",
+            "!avail today 2099-12-31 40h",
+        ]
+        .iter()
+        .enumerate()
+        {
+            say(&mut handler, &transport, &room.name, &id.to_string(), body);
+        }
+        say(&mut handler, &transport, &room.name, "10", "!plan");
+        let by_deadline = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "11",
+            "!priority ac/web 5",
+        );
+        let priority = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "12",
+            "!strategy priority",
+        );
+        say(&mut handler, &transport, &room.name, "13", "!plan");
+        let by_priority = transport.last().unwrap_or_default();
+
+        assert!(finish(&by_deadline, "ac/bot") < finish(&by_deadline, "ac/web"));
+        assert_eq!(priority, "Priority of ac/web set to 5");
+        assert!(by_priority.starts_with("Planned with the priority strategy:"));
+        assert!(finish(&by_priority, "ac/web") < finish(&by_priority, "ac/bot"));
+    }
+
+    #[test]
+    fn counts_done_hours_from_the_intent_start() {
+        let (room, mut handler, transport) = room("intent");
+        let done = || {
+            room.store
+                .lock()
+                .unwrap()
+                .connected(&room.name)
+                .and_then(|connected| connected.select_done_for_intents())
+                .unwrap_or_default()
+        };
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!intent ac/bot 10h from 2020-01-01",
+        );
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            "!done ac/bot dev 2h",
+        );
+        let counted = done();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "!intent ac/bot 10h from tomorrow",
+        );
+        let rebased = done();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "5",
+            "!intent ac/bot 12h",
+        );
+        let kept = done();
+
+        assert_eq!(counted.len(), 1);
+        assert_eq!(counted[0].duration.as_secs() / 60, 120);
+        assert!(rebased.is_empty());
+        assert!(kept.is_empty());
+    }
+
+    #[test]
+    fn manages_intents_and_availability() {
+        let (room, mut handler, transport) = room("manage");
+        let mut said = 0;
+        let mut ask = |handler: &mut CommandHandler, body: &str| {
+            said += 1;
+            say(handler, &transport, &room.name, &said.to_string(), body);
+            transport.last().unwrap_or_default()
+        };
+
+        ask(&mut handler, "!new ac/bot");
+        ask(&mut handler, "!intent ac/bot 10h");
+        ask(&mut handler, "!avail 2020-01-01 2099-12-31 40h");
+        let intents = ask(&mut handler, "!intents ac/bot");
+        let avails = ask(&mut handler, "!avails @alice:localhost");
+        let id = avails.split('\t').next().unwrap_or_default().to_string();
+        let ended = ask(&mut handler, "!endintent ac/bot 2099-06-30");
+        let later = ask(&mut handler, "!endintent ac/bot 2099-07-31");
+        let missing = ask(&mut handler, "!endintent ac/other");
+        let shortened = ask(&mut handler, &format!("!endavail {} 2099-06-30", id));
+        let question = ask(&mut handler, &format!("!rmavail {}", id));
+        let removed = ask(&mut handler, "!yes");
+        let none = ask(&mut handler, "!avails");
+        ask(&mut handler, "!rmintent ac/bot");
+        ask(&mut handler, "!yes");
+        let log = ask(&mut handler, "!intentlog ac/bot");
+        let gone = ask(&mut handler, "!intents");
+
+        assert!(
+            intents.starts_with("@alice\tac/bot\t10 hours"),
+            "{}",
+            intents
+        );
+        assert!(avails.starts_with('#'), "{}", avails);
+        assert!(avails.contains("\t2020-01-01\t2099-12-31\t"), "{}", avails);
+        assert_eq!(ended, "Intent on ac/bot ends on 2099-06-30");
+        assert_eq!(
+            later,
+            "There's no intent on ac/bot ending after 2099-07-31 for you, sorry."
+        );
+        assert!(
+            missing.starts_with("There's no intent on ac/other ending after "),
+            "{}",
+            missing
+        );
+        assert_eq!(shortened, format!("Availability {} ends on 2099-06-30", id));
+        assert!(
+            question.starts_with(&format!("This will remove availability {} of @alice", id)),
+            "{}",
+            question
+        );
+        assert_eq!(removed, format!("Removed availability {}", id));
+        assert_eq!(none, "No availability yet.");
+        let changes: Vec<&str> = log
+            .lines()
+            .map(|line| line.split('\t').nth(1).unwrap_or_default())
+            .collect();
+        assert_eq!(changes, ["set", "end", "remove"]);
+        assert_eq!(gone, "No intents yet.");
+    }
+
+    #[test]
+    fn hands_out_feed_tokens() {
+        let (room, mut handler, transport) = room("feed");
+        let token = |reply: &str| {
+            reply
+                .split("/feed/")
+                .nth(1)
+                .and_then(|rest| rest.split('/').next())
+                .map(String::from)
+                .unwrap_or_default()
+        };
+        let owner = |token: &str| {
+            room.store
+                .lock()
+                .unwrap()
+                .connected(&room.name)
+                .and_then(|connected| connected.select_feed(token))
+                .ok()
+                .flatten()
+                .map(|feed| feed.username)
+        };
+
+        say(&mut handler, &transport, &room.name, "1", "!feed");
+        let first = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "2", "!feed");
+        let again = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "3", "!feed renew");
+        let renewed = transport.last().unwrap_or_default();
+        let (old, new) = (owner(&token(&first)), owner(&token(&renewed)));
+
+        let found = room
+            .store
+            .lock()
+            .unwrap()
+            .find_feed(&token(&renewed))
+            .ok()
+            .flatten()
+            .map(|(found, _)| found);
+
+        assert!(
+            first.starts_with("your logged tasks: http://localhost/feed/"),
+            "{}",
+            first
+        );
+        assert!(!first.contains(&room.name), "{}", first);
+        assert!(first.contains("/deadlines.ics\n"));
+        assert_eq!(token(&first), token(&again));
+        assert_ne!(token(&first), token(&renewed));
+        assert_eq!(old, None);
+        assert_eq!(new.as_deref(), Some("@alice:localhost"));
+        assert_eq!(found, Some(room.name.clone()));
+    }
+
+    #[test]
+    fn asks_before_overwriting() {
+        let (room, mut handler, transport) = room("confirm");
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!provision ac/bot 40h",
+        );
+        let first = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "3",
+            "!provision ac/bot 20h",
+        );
+        let question = transport.last().unwrap_or_default();
+        say_as(
+            &mut handler,
+            &transport,
+            &room.name,
+            "4",
+            "@bob:localhost",
+            "!yes",
+        );
+        let other = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "5", "!yes");
+        let confirmed = transport.last().unwrap_or_default();
+        say(&mut handler, &transport, &room.name, "6", "!yes");
+        let again = transport.last().unwrap_or_default();
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "7",
+            "!provision ac/bot 20h",
+        );
+        let same = transport.last().unwrap_or_default();
+
+        assert_eq!(first, "Updated provision");
+        assert!(question.starts_with("This will overwrite the "));
+        assert!(question.contains(" provision of ac/bot with "));
+        assert!(question.ends_with(", reply !yes within 2 minutes to go ahead."));
+        assert_eq!(other, "There's nothing waiting for your !yes.");
+        assert_eq!(confirmed, "Updated provision");
+        assert_eq!(again, "There's nothing waiting for your !yes.");
+        assert_eq!(same, "Updated provision");
+    }
+
+    #[test]
+    fn runs_a_replayed_event_once() {
+        let (room, mut handler, transport) = room("replay");
+
+        say(&mut handler, &transport, &room.name, "1", "!new ac/bot");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot dev 1h",
+        );
+        say(&mut handler, &transport, &room.name, "3", "!ping");
+        say(
+            &mut handler,
+            &transport,
+            &room.name,
+            "2",
+            "!done ac/bot dev 1h",
+        );
+        let replayed = transport.last();
+        handler.handle_event(&transport, Event::Sync(String::from("s42_1")));
+        let token = room.store.lock().unwrap().select_sync_token();
+        let hours = room
+            .store
+            .lock()
+            .unwrap()
+            .connected(&room.name)
+            .and_then(|connected| {
+                connected.select_user("@alice:localhost".into(), std::time::UNIX_EPOCH, None)
+            })
+            .map(|records| records.iter().map(|r| r.duration.as_secs()).sum::<u64>());
+
+        assert_eq!(replayed, Some(String::from("pong")));
+        assert_eq!(hours.ok(), Some(3600));
+        assert_eq!(token.ok(), Some(Some(String::from("s42_1"))));
+    }
+
+    #[test]
+    fn handles_commands_without_a_homeserver() {
+        let (room, mut handler, transport) = room("test");
+
+        handler.handle_event(
+            &transport,
+            Event::Message(Incoming {
+                id: String::from("1"),
+                room: room.name.clone(),
+                sender: String::from("@alice:localhost"),
+                body: String::from("!ping"),
+                relation: None,
+            }),
+        );
+
+        assert_eq!(transport.last(), Some(String::from("pong")));
+    }
+}
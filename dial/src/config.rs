@@ -0,0 +1,515 @@
+//! Settings of the bot and of its HTTP server.
+//!
+//! They are read from a TOML file, then from `PEARLS_*` environment
+//! variables, then from command line flags, each one overriding the one
+//! before. See `pearls.example.toml` for the keys.
+
+use crate::logging;
+use crate::registry::{self, Policy};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use tracing::{error, info, warn};
+
+/// Which parts of the program to run.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    All,
+    Bot,
+    Http,
+}
+
+impl Mode {
+    fn from_name(name: &str) -> Option<Mode> {
+        match name {
+            "all" => Some(Mode::All),
+            "bot" => Some(Mode::Bot),
+            "http" => Some(Mode::Http),
+            _ => None,
+        }
+    }
+
+    pub fn runs_bot(&self) -> bool {
+        matches!(self, Mode::All | Mode::Bot)
+    }
+
+    pub fn runs_http(&self) -> bool {
+        matches!(self, Mode::All | Mode::Http)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatrixConfig {
+    pub homeserver: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    /// A file holding the password, so that it stays out of the config.
+    pub password_file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    pub address: Option<String>,
+    pub static_dir: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// error, warn, info, debug or trace.
+    pub level: String,
+    /// Log the length of message bodies instead of what they say.
+    pub redact_bodies: bool,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: String::from("info"),
+            redact_bodies: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub mode: Mode,
+    pub log_dir: String,
+    pub base_url: String,
+    /// Ignore what was said while the bot was away instead of catching up.
+    pub skip_backlog: bool,
+    pub matrix: MatrixConfig,
+    pub http: HttpConfig,
+    pub log: LogConfig,
+    /// Confirmation policies by command name, overriding those of commands.
+    pub confirm: HashMap<String, Policy>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            mode: Mode::default(),
+            log_dir: String::from("."),
+            base_url: String::from("localhost"),
+            skip_backlog: false,
+            matrix: MatrixConfig::default(),
+            http: HttpConfig::default(),
+            log: LogConfig::default(),
+            confirm: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Read(PathBuf, String),
+    Parse(String),
+    Missing(&'static str),
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => {
+                write!(f, "Failed to read {}: {}", path.to_string_lossy(), err)
+            }
+            ConfigError::Parse(err) => write!(f, "Failed to parse the config file: {}", err),
+            ConfigError::Missing(key) => write!(f, "Missing setting: {}", key),
+            ConfigError::Invalid(key, reason) => write!(f, "Invalid {}: {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Keys that can be overridden, as named in the environment (uppercased,
+/// with a `PEARLS_` prefix) and on the command line.
+pub const KEYS: &[&str] = &[
+    "mode",
+    "log_dir",
+    "base_url",
+    "skip_backlog",
+    "homeserver",
+    "user",
+    "password",
+    "password_file",
+    "http_address",
+    "static_dir",
+    "log_level",
+    "redact_bodies",
+];
+
+fn boolean(key: &'static str, value: &str) -> Result<bool, ConfigError> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(ConfigError::Invalid(
+            key,
+            format!("{} is not true or false", value),
+        )),
+    }
+}
+
+impl Config {
+    pub fn from_toml(source: &str) -> Result<Config, ConfigError> {
+        toml::from_str(source).map_err(|err| ConfigError::Parse(err.to_string()))
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        fs::read_to_string(path)
+            .map_err(|err| ConfigError::Read(path.to_path_buf(), err.to_string()))
+            .and_then(|source| Config::from_toml(&source))
+    }
+
+    /// Overrides settings with the values `get` finds for `KEYS`.
+    pub fn apply<F>(mut self, get: F) -> Result<Config, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        for key in KEYS {
+            if let Some(value) = get(key) {
+                match *key {
+                    "mode" => {
+                        self.mode = Mode::from_name(&value).ok_or_else(|| {
+                            ConfigError::Invalid(
+                                "mode",
+                                format!("{} is not all, bot or http", value),
+                            )
+                        })?
+                    }
+                    "log_dir" => self.log_dir = value,
+                    "base_url" => self.base_url = value,
+                    "skip_backlog" => self.skip_backlog = boolean("skip_backlog", &value)?,
+                    "homeserver" => self.matrix.homeserver = Some(value),
+                    "user" => self.matrix.user = Some(value),
+                    "password" => self.matrix.password = Some(value),
+                    "password_file" => self.matrix.password_file = Some(PathBuf::from(value)),
+                    "http_address" => self.http.address = Some(value),
+                    "static_dir" => self.http.static_dir = Some(value),
+                    "log_level" => self.log.level = value,
+                    "redact_bodies" => self.log.redact_bodies = boolean("redact_bodies", &value)?,
+                    _ => {}
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// The password, read from `password_file` when there is one.
+    pub fn password(&self) -> Result<String, ConfigError> {
+        match (&self.matrix.password_file, &self.matrix.password) {
+            (Some(path), _) => fs::read_to_string(path)
+                .map(|secret| String::from(secret.trim_end_matches(&['\r', '\n'][..])))
+                .map_err(|err| ConfigError::Read(path.clone(), err.to_string())),
+            (None, Some(password)) => Ok(password.clone()),
+            (None, None) => Err(ConfigError::Missing("password")),
+        }
+    }
+
+    fn check(&self, bot: bool, http: bool) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        if !Path::new(&self.log_dir).is_dir() {
+            errors.push(ConfigError::Invalid(
+                "log_dir",
+                format!("{} is not a directory", self.log_dir),
+            ));
+        }
+        if self.log.level.parse::<tracing::Level>().is_err() {
+            errors.push(ConfigError::Invalid(
+                "log_level",
+                format!(
+                    "{} is not error, warn, info, debug or trace",
+                    self.log.level
+                ),
+            ));
+        }
+        let specs = registry::registry();
+        for name in self.confirm.keys() {
+            if registry::find(&specs, name).is_none() {
+                errors.push(ConfigError::Invalid(
+                    "confirm",
+                    format!("{} is not a command", name),
+                ));
+            }
+        }
+        if bot {
+            if self.matrix.homeserver.is_none() {
+                errors.push(ConfigError::Missing("homeserver"));
+            }
+            if self.matrix.user.is_none() {
+                errors.push(ConfigError::Missing("user"));
+            }
+            if let Err(err) = self.password() {
+                errors.push(err);
+            }
+        }
+        if http {
+            match &self.http.address {
+                None => errors.push(ConfigError::Missing("http_address")),
+                Some(address) if address.parse::<SocketAddr>().is_err() => errors.push(
+                    ConfigError::Invalid("http_address", format!("{} is not host:port", address)),
+                ),
+                Some(_) => {}
+            }
+            match &self.http.static_dir {
+                None => errors.push(ConfigError::Missing("static_dir")),
+                Some(dir) if !Path::new(dir).is_dir() => errors.push(ConfigError::Invalid(
+                    "static_dir",
+                    format!("{} is not a directory", dir),
+                )),
+                Some(_) => {}
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    /// Checks that everything the mode needs is there and makes sense.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        self.check(self.mode.runs_bot(), self.mode.runs_http())
+    }
+
+    /// Checks a config for the terminal, which needs no homeserver and runs
+    /// the HTTP server only when given an address.
+    pub fn validate_repl(&self) -> Result<(), Vec<ConfigError>> {
+        self.check(false, self.http.address.is_some())
+    }
+
+    /// Settings that differ from `other` but only apply after a restart.
+    pub fn needs_restart(&self, other: &Config) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        if self.mode != other.mode {
+            keys.push("mode");
+        }
+        if self.log_dir != other.log_dir {
+            keys.push("log_dir");
+        }
+        if self.matrix != other.matrix {
+            keys.push("matrix");
+        }
+        if self.http != other.http {
+            keys.push("http");
+        }
+        if self.log.level != other.log.level {
+            keys.push("log_level");
+        }
+        keys
+    }
+}
+
+/// Reads the file at `path` if any, then the environment, then `flags`.
+pub fn load(
+    path: Option<&Path>,
+    flags: &HashMap<String, String>,
+) -> Result<Config, Vec<ConfigError>> {
+    let config = match path {
+        Some(path) => Config::from_file(path),
+        None => Ok(Config::default()),
+    };
+    config
+        .and_then(|config| {
+            config.apply(|key| std::env::var(format!("PEARLS_{}", key.to_uppercase())).ok())
+        })
+        .and_then(|config| config.apply(|key| flags.get(key).cloned()))
+        .map_err(|err| vec![err])
+}
+
+/// The base URL handed to commands, which can change on reload.
+#[derive(Clone, Debug)]
+pub struct BaseUrl(Arc<RwLock<String>>);
+
+impl BaseUrl {
+    pub fn get(&self) -> String {
+        self.0
+            .read()
+            .map(|url| url.clone())
+            .unwrap_or_else(|err| err.into_inner().clone())
+    }
+
+    pub fn set(&self, url: String) {
+        if let Ok(mut current) = self.0.write() {
+            *current = url;
+        }
+    }
+}
+
+impl From<String> for BaseUrl {
+    fn from(url: String) -> Self {
+        BaseUrl(Arc::new(RwLock::new(url)))
+    }
+}
+
+impl From<&str> for BaseUrl {
+    fn from(url: &str) -> Self {
+        BaseUrl::from(String::from(url))
+    }
+}
+
+/// Confirmation policies handed to commands, which can change on reload.
+#[derive(Clone, Debug, Default)]
+pub struct Policies(Arc<RwLock<HashMap<String, Policy>>>);
+
+impl Policies {
+    pub fn get(&self) -> HashMap<String, Policy> {
+        self.0
+            .read()
+            .map(|policies| policies.clone())
+            .unwrap_or_else(|err| err.into_inner().clone())
+    }
+
+    pub fn set(&self, policies: HashMap<String, Policy>) {
+        if let Ok(mut current) = self.0.write() {
+            *current = policies;
+        }
+    }
+}
+
+impl From<HashMap<String, Policy>> for Policies {
+    fn from(policies: HashMap<String, Policy>) -> Self {
+        Policies(Arc::new(RwLock::new(policies)))
+    }
+}
+
+/// Loads the config again on SIGHUP. A config that does not validate is
+/// reported and ignored, and settings that need a restart are only logged.
+pub fn reload_on_hangup<F>(load: F, current: Config, base_url: BaseUrl, policies: Policies)
+where
+    F: Fn() -> Result<Config, Vec<ConfigError>> + Send + 'static,
+{
+    let mut signals = match signal_hook::iterator::Signals::new([signal_hook::consts::SIGHUP]) {
+        Ok(signals) => signals,
+        Err(err) => {
+            warn!("Could not listen to SIGHUP, no config reload: {}", err);
+            return;
+        }
+    };
+    thread::spawn(move || {
+        let mut current = current;
+        for _ in signals.forever() {
+            match load().and_then(|next| next.validate().map(|_| next)) {
+                Ok(next) => {
+                    for key in next.needs_restart(&current) {
+                        warn!("Config: {} changed, restart to apply it", key);
+                    }
+                    base_url.set(next.base_url.clone());
+                    logging::redact_bodies(next.log.redact_bodies);
+                    policies.set(next.confirm.clone());
+                    current = next;
+                    info!("Config reloaded");
+                }
+                Err(errors) => {
+                    for err in errors {
+                        error!("Config not reloaded: {}", err);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_the_file() {
+        let config = Config::from_toml(
+            r#"
+            mode = "bot"
+            [matrix]
+            homeserver = "https://matrix.org"
+            user = "@pearls:matrix.org"
+            "#,
+        )
+        .and_then(|config| {
+            config.apply(|key| match key {
+                "user" => Some(String::from("@other:matrix.org")),
+                "static_dir" => Some(String::from("static")),
+                _ => None,
+            })
+        })
+        .unwrap();
+
+        assert_eq!(config.mode, Mode::Bot);
+        assert_eq!(config.log_dir, ".");
+        assert_eq!(
+            config.matrix.homeserver.as_deref(),
+            Some("https://matrix.org")
+        );
+        assert_eq!(config.matrix.user.as_deref(), Some("@other:matrix.org"));
+        assert_eq!(config.http.static_dir.as_deref(), Some("static"));
+    }
+
+    #[test]
+    fn reads_log_settings() {
+        let config = Config::from_toml("[log]\nlevel = \"verbose\"\n")
+            .and_then(|config| config.apply(|key| (key == "redact_bodies").then(|| "0".into())))
+            .unwrap();
+
+        assert!(!config.log.redact_bodies);
+        assert!(config
+            .validate_repl()
+            .unwrap_err()
+            .contains(&ConfigError::Invalid(
+                "log_level",
+                String::from("verbose is not error, warn, info, debug or trace")
+            )));
+    }
+
+    #[test]
+    fn reads_the_password_from_a_file() {
+        let path = std::env::temp_dir().join(format!("pearls-secret-{}", std::process::id()));
+        fs::write(&path, "s3cret\n").unwrap();
+        let config = Config::default()
+            .apply(|key| match key {
+                "password" => Some(String::from("ignored")),
+                "password_file" => Some(String::from(path.to_string_lossy())),
+                _ => None,
+            })
+            .unwrap();
+        let password = config.password();
+        fs::remove_file(&path).unwrap_or(());
+
+        assert_eq!(password, Ok(String::from("s3cret")));
+    }
+
+    #[test]
+    fn validates_what_the_mode_needs() {
+        let http_only = Config {
+            mode: Mode::Http,
+            http: HttpConfig {
+                address: Some(String::from("localhost")),
+                static_dir: Some(String::from(".")),
+            },
+            ..Config::default()
+        };
+
+        assert_eq!(
+            http_only.validate(),
+            Err(vec![ConfigError::Invalid(
+                "http_address",
+                String::from("localhost is not host:port")
+            )])
+        );
+        assert_eq!(
+            Config::default().validate().map_err(|errors| errors.len()),
+            Err(5)
+        );
+        assert_eq!(Config::default().validate_repl(), Ok(()));
+        assert!(Config::from_toml("mode = \"both\"").is_err());
+        assert!(Config::from_toml("homeserver = \"x\"").is_err());
+    }
+}
@@ -0,0 +1,26 @@
+//! Leveled logs through `tracing`, keeping what people write in rooms out of
+//! them unless told otherwise.
+
+use crate::config::LogConfig;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Level;
+
+static REDACT_BODIES: AtomicBool = AtomicBool::new(true);
+
+pub fn init(config: &LogConfig) {
+    let level = config.level.parse::<Level>().unwrap_or(Level::INFO);
+    tracing_subscriber::fmt().with_max_level(level).init();
+    redact_bodies(config.redact_bodies);
+}
+
+pub fn redact_bodies(redact: bool) {
+    REDACT_BODIES.store(redact, Ordering::Relaxed);
+}
+
+/// A message body as it may appear in logs.
+pub fn body(text: &str) -> String {
+    match REDACT_BODIES.load(Ordering::Relaxed) {
+        true => format!("[{} chars]", text.chars().count()),
+        false => String::from(text),
+    }
+}
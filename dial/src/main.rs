@@ -1,34 +1,102 @@
 // #[macro_use]
 // extern crate tower_web;
 
-use clap::{App, Arg};
-use std::path::Path;
+use clap::{App, Arg, ArgMatches};
+use config::{BaseUrl, Config, ConfigError, Policies};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use tracing::info;
 
 mod bot;
+mod config;
+mod logging;
 // mod http;
 mod make;
 mod notif;
+mod registry;
+mod transport;
 
-fn run_bot(
-    homeserver: &str,
-    username: &str,
-    password: &str,
-    log: &str,
-    http_address: &str,
-    base_url: &str,
-    statict_dir: &str,
-) {
-    let log_path = Path::new(log);
-    let rx = bot::start_bot(log_path, homeserver, username, password, base_url);
+fn start_http(config: &Config) {
+    if let (Some(http_address), Some(static_dir)) = (&config.http.address, &config.http.static_dir)
+    {
+        http::start_http(Path::new(&config.log_dir), http_address, static_dir);
+    }
+}
 
-    http::start_http(log_path, http_address, statict_dir);
+fn run(config: &Config, base_url: BaseUrl, policies: Policies) {
+    let log_path = Path::new(&config.log_dir);
+    if config.mode.runs_http() {
+        start_http(config);
+    }
+    if !config.mode.runs_bot() {
+        loop {
+            thread::park();
+        }
+    }
+
+    let rx = transport::start_matrix(
+        log_path,
+        config.matrix.homeserver.as_deref().unwrap_or_default(),
+        config.matrix.user.as_deref().unwrap_or_default(),
+        &config.password().unwrap_or_default(),
+        base_url,
+        policies,
+        config.skip_backlog,
+    );
+    for message in rx.iter() {
+        info!("{}", message);
+    }
+}
+
+fn run_terminal(room: &str, config: &Config, base_url: BaseUrl, policies: Policies) {
+    let log_path = Path::new(&config.log_dir);
+    let username = config.matrix.user.as_deref().unwrap_or("@me:localhost");
+    let rx = transport::start_terminal(log_path, room, username, base_url, policies);
+
+    start_http(config);
 
     for message in rx.iter() {
         println!("{}", message);
     }
 }
 
+/// Flags given on the command line, by config key.
+fn flags(matches: &ArgMatches) -> HashMap<String, String> {
+    config::KEYS
+        .iter()
+        .filter_map(|key| match (matches.value_of(key), matches.is_present(key)) {
+            (Some(value), _) => Some((String::from(*key), String::from(value))),
+            (None, true) => Some((String::from(*key), String::from("true"))),
+            (None, false) => None,
+        })
+        .collect()
+}
+
+/// Prints what is wrong with the config and exits with a failure status.
+fn report(errors: Vec<ConfigError>) -> ! {
+    for err in errors {
+        eprintln!("{}", err);
+    }
+    process::exit(1)
+}
+
 fn main() {
+    let config_file = Arg::with_name("config")
+        .short("c")
+        .long("config")
+        .value_name("config")
+        .help("Path to a TOML config file, settings can be overridden by PEARLS_* environment variables and flags")
+        .takes_value(true);
+
+    let mode = Arg::with_name("mode")
+        .short("m")
+        .long("mode")
+        .value_name("mode")
+        .help("What to run: all (default), bot or http")
+        .takes_value(true);
+
     let homeserver = Arg::with_name("homeserver")
         .short("h")
         .long("homeserver")
@@ -36,7 +104,7 @@ fn main() {
         .help("Home server to connect to")
         .takes_value(true);
 
-    let username = Arg::with_name("username")
+    let username = Arg::with_name("user")
         .short("u")
         .long("user")
         .value_name("user")
@@ -47,7 +115,13 @@ fn main() {
         .short("p")
         .long("password")
         .value_name("password")
-        .help("Password")
+        .help("Password, visible in the process list: prefer password_file or PEARLS_PASSWORD")
+        .takes_value(true);
+
+    let password_file = Arg::with_name("password_file")
+        .long("password_file")
+        .value_name("password_file")
+        .help("Path to a file holding the password")
         .takes_value(true);
 
     let log_dir = Arg::with_name("log_dir")
@@ -78,37 +152,61 @@ fn main() {
         .help("Socket address")
         .takes_value(true);
 
+    let skip_backlog = Arg::with_name("skip_backlog")
+        .long("skip_backlog")
+        .help("Ignore messages sent while the bot was not running");
+
+    let repl = Arg::with_name("repl")
+        .short("r")
+        .long("repl")
+        .value_name("room")
+        .help("Run locally against the database of this room, reading commands from stdin")
+        .takes_value(true);
+
     let matches = App::new("Pearls")
         .version("0.1")
         .about("Chat your time")
+        .arg(config_file)
+        .arg(mode)
         .arg(homeserver)
         .arg(username)
         .arg(password)
+        .arg(password_file)
         .arg(log_dir)
         .arg(http_address)
         .arg(base_url)
         .arg(static_dir)
+        .arg(skip_backlog)
+        .arg(repl)
         .get_matches();
 
-    match (
-        matches.value_of("homeserver"),
-        matches.value_of("username"),
-        matches.value_of("password"),
-        matches.value_of("http_address"),
-        matches.value_of("base_url"),
-        matches.value_of("static_dir"),
-    ) {
-        (Some(hs), Some(us), Some(pa), Some(ha), Some(bu), Some(sd)) => {
-            run_bot(
-                hs,
-                us,
-                pa,
-                matches.value_of("log_dir").unwrap_or("."),
-                ha,
-                bu,
-                sd,
-            );
+    let config_path = matches.value_of("config").map(PathBuf::from);
+    let flags = flags(&matches);
+    let load = move || config::load(config_path.as_deref(), &flags);
+
+    let config = match load() {
+        Ok(config) => config,
+        Err(errors) => report(errors),
+    };
+    let base_url = BaseUrl::from(config.base_url.clone());
+    let policies = Policies::from(config.confirm.clone());
+
+    if let Some(room) = matches.value_of("repl") {
+        return match config.validate_repl() {
+            Ok(_) => {
+                logging::init(&config.log);
+                run_terminal(room, &config, base_url, policies)
+            }
+            Err(errors) => report(errors),
+        };
+    }
+
+    match config.validate() {
+        Ok(_) => {
+            logging::init(&config.log);
+            config::reload_on_hangup(load, config.clone(), base_url.clone(), policies.clone());
+            run(&config, base_url, policies);
         }
-        _ => println!("Missing homeserver or username or password"),
+        Err(errors) => report(errors),
     }
 }
@@ -0,0 +1,64 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::human_duration;
+use std::time;
+
+use super::undo::whose;
+
+pub fn amend(
+    handler: &mut bot::Context,
+    user: String,
+    duration: time::Duration,
+) -> Option<(String, String)> {
+    let latest = handler
+        .store
+        .select_latest_task_for(user.clone())
+        .unwrap_or_default();
+    match latest.first() {
+        None => Some(("There's nothing to !amend, sorry.".into(), String::new())),
+        Some(rec) => {
+            let amended = handler.store.atomic(|store| {
+                let group = store.select_group(rec.id)?;
+                group
+                    .iter()
+                    .try_for_each(|r| {
+                        store
+                            .update_task_end(r.id, r.start_time + duration)
+                            .map(|_| ())
+                    })
+                    .map(|_| group)
+            });
+            match amended {
+                Err(err) => Some((format!("Error: {}", err), String::new())),
+                Ok(group) => Some((
+                    format!(
+                        "{} {} now lasts {} for {}.",
+                        rec.project,
+                        rec.task,
+                        human_duration(duration),
+                        whose(&user, &group)
+                    ),
+                    String::new(),
+                )),
+            }
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "amend",
+        role: Role::Member,
+        confirm: Policy::Overwrite,
+        grammar: expr::amend,
+        usage: "!amend <duration>",
+        help: "change how long your latest task lasted, for everyone it was shared with too",
+        examples: &["!amend 45m", "!amend 1h 30m"],
+        handler: |context, user, command| match command {
+            Command::Amend(duration) => amend(context, user, duration),
+            _ => None,
+        },
+    }
+}
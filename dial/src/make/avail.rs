@@ -1,4 +1,7 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{Role, WeekPattern};
 use std::time;
 
 pub fn avail(
@@ -6,13 +9,32 @@ pub fn avail(
     user: String,
     start: time::SystemTime,
     end: time::SystemTime,
-    weekly: time::Duration,
+    pattern: WeekPattern,
 ) -> Option<(String, String)> {
-    match handler.store.insert_avail(user, start, end, weekly) {
+    match handler.store.insert_avail(user, start, end, &pattern) {
         Err(_err) => Some((
             "Sorry, Err'd while saving to DB".into(),
             "Sorry, Err'd while saving to DB".into(),
         )),
-        Ok(_) => Some((format!("Registered"), String::new())),
+        Ok(_) => Some((format!("Registered {}", pattern), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "avail",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::avail,
+        usage: "!avail <date> <date> (<duration> | mon=<duration> tue=<duration> ...)",
+        help: "set your availability during this period, either weekly over Monday to Friday or day by day; where periods overlap, the latest one counts",
+        examples: &[
+            "!avail 2024-01-01 2024-06-30 32h",
+            "!avail 2024-09-01 2025-06-30 mon=8h tue=8h wed=4h",
+        ],
+        handler: |context, user, command| match command {
+            Command::Avail(start, end, pattern) => avail(context, user, start, end, pattern),
+            _ => None,
+        },
     }
 }
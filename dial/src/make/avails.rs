@@ -0,0 +1,50 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, Element};
+use shell::expr::{self, Command};
+use shell::store::{Avail, Role};
+use shell::util::{display_username, make_table_row, st_to_datestring};
+
+fn cells(avail: &Avail) -> Vec<String> {
+    vec![
+        format!("#{}", avail.id),
+        display_username(&avail.username),
+        st_to_datestring(&avail.start_time),
+        st_to_datestring(&avail.end_time),
+        avail.pattern.to_string(),
+    ]
+}
+
+pub fn avails(handler: &mut bot::Context, user: Option<String>) -> Option<(String, String)> {
+    let avails = match user {
+        Some(user) => handler.store.select_avail_for_user(user),
+        None => handler.store.select_avail_all(),
+    };
+    match avails {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(avails) if avails.is_empty() => {
+            Some((String::from("No availability yet."), String::new()))
+        }
+        Ok(avails) => {
+            let left: Vec<String> = avails.iter().map(|a| cells(a).join("\t")).collect();
+            let rows: Vec<Element> = avails.iter().map(|a| make_table_row(cells(a))).collect();
+            Some((left.join("\n"), table(rows).as_string()))
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "avails",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::avails,
+        usage: "!avails [<@user>]",
+        help: "list the availability periods not over yet, with the number !endavail and !rmavail take",
+        examples: &["!avails", "!avails @alice:matrix.org"],
+        handler: |context, _user, command| match command {
+            Command::Avails(user) => avails(context, user),
+            _ => None,
+        },
+    }
+}
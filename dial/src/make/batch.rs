@@ -0,0 +1,89 @@
+use html::{code, div, escape, h4, paragraph, Element};
+
+/// What happened to one line of a message holding several commands.
+pub enum Outcome<'a> {
+    Ran(&'a str, Option<(String, String)>),
+    Skipped(&'a str),
+    Failed(&'a str, Option<(String, String)>),
+    Denied(&'a str, Option<(String, String)>),
+}
+
+impl<'a> Outcome<'a> {
+    fn line(&self) -> &'a str {
+        match self {
+            Outcome::Ran(line, _)
+            | Outcome::Skipped(line)
+            | Outcome::Failed(line, _)
+            | Outcome::Denied(line, _) => line,
+        }
+    }
+
+    fn reply(&self) -> (String, String) {
+        match self {
+            Outcome::Ran(_, Some(reply))
+            | Outcome::Failed(_, Some(reply))
+            | Outcome::Denied(_, Some(reply)) => reply.clone(),
+            Outcome::Ran(_, None) | Outcome::Failed(_, None) | Outcome::Denied(_, None) => {
+                (String::new(), String::new())
+            }
+            Outcome::Skipped(_) => (String::from("not run"), String::new()),
+        }
+    }
+}
+
+fn line_numbers<F>(outcomes: &[Outcome], pred: F) -> Vec<String>
+where
+    F: Fn(&Outcome) -> bool,
+{
+    outcomes
+        .iter()
+        .enumerate()
+        .filter(|(_, outcome)| pred(outcome))
+        .map(|(index, _)| (index + 1).to_string())
+        .collect()
+}
+
+fn header(outcomes: &[Outcome]) -> Option<String> {
+    let failed = line_numbers(outcomes, |o| matches!(o, Outcome::Failed(_, _)));
+    let denied = line_numbers(outcomes, |o| matches!(o, Outcome::Denied(_, _)));
+    match (failed.len(), denied.len()) {
+        (0, 0) => None,
+        (0, _) => Some(format!(
+            "Nothing was done, you are not allowed to run line {}.",
+            denied.join(", ")
+        )),
+        _ => Some(format!(
+            "Nothing was done, I could not understand line {}.",
+            failed.join(", ")
+        )),
+    }
+}
+
+fn make_text(outcomes: &[Outcome]) -> String {
+    let lines: Vec<String> = outcomes
+        .iter()
+        .map(|outcome| format!("> {}\n{}", outcome.line(), outcome.reply().0))
+        .collect();
+    match header(outcomes) {
+        Some(header) => format!("{}\n\n{}", header, lines.join("\n\n")),
+        None => lines.join("\n\n"),
+    }
+}
+
+fn make_html(outcomes: &[Outcome]) -> String {
+    let mut elements: Vec<Element> = header(outcomes).into_iter().map(h4).collect();
+    for outcome in outcomes {
+        let (text, html) = outcome.reply();
+        elements.push(div(code(escape(outcome.line()))));
+        elements.push(match html.is_empty() {
+            true => paragraph(escape(text)),
+            false => div(html),
+        });
+    }
+    div(elements).as_string()
+}
+
+/// One reply for all the commands of a message.
+pub fn batch(outcomes: &[Outcome]) -> Option<(String, String)> {
+    Some((make_text(outcomes), make_html(outcomes)))
+}
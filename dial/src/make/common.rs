@@ -1,6 +1,7 @@
 use crate::bot;
 use html::{div, h4, li, paragraph, ul, Element};
-use shell::store::ProjectRecord;
+use shell::store::{ConnectedStore, ProjectRecord, Role};
+use shell::util::display_username;
 use strsim::levenshtein;
 
 type ScoredName = (String, usize);
@@ -126,3 +127,88 @@ pub fn check_meta(handler: &mut bot::Context, project: &ProjectRecord) -> Option
             }),
     }
 }
+
+/// Whether taking the admin role from `user` would leave the room without
+/// one, in which case everyone would be admin again.
+pub fn is_last_admin(handler: &mut bot::Context, user: &str) -> bool {
+    handler
+        .store
+        .select_role_all()
+        .map(|records| {
+            let admins: Vec<_> = records.iter().filter(|r| r.role == Role::Admin).collect();
+            admins.len() == 1 && admins[0].username == user
+        })
+        .unwrap_or(false)
+}
+
+/// The owner of a record and who entered it, for commands that take an
+/// optional `@user` to act for someone else.
+pub fn on_behalf(user: String, target: Option<String>) -> (String, Option<String>) {
+    match target {
+        Some(target) if target != user => (target, Some(user)),
+        _ => (user, None),
+    }
+}
+
+/// "You are" or "alice is", depending on who a reply is about.
+pub fn who_is(owner: &str, entered_by: &Option<String>) -> String {
+    match entered_by {
+        Some(_) => format!("{} is", display_username(owner)),
+        None => String::from("You are"),
+    }
+}
+
+/// "you" or "alice", depending on who a reply is about.
+pub fn whom(owner: &str, entered_by: &Option<String>) -> String {
+    match entered_by {
+        Some(_) => display_username(owner),
+        None => String::from("you"),
+    }
+}
+
+/// The records of a task shared `with` others, as owner and who entered it:
+/// the sender typed all of those that are not theirs.
+pub fn participants(
+    owner: &str,
+    entered_by: &Option<String>,
+    with: &[String],
+) -> Vec<(String, Option<String>)> {
+    let sender = entered_by.clone().unwrap_or_else(|| owner.to_string());
+    let mut users = vec![(owner.to_string(), entered_by.clone())];
+    for other in with {
+        if users.iter().all(|(user, _)| user != other) {
+            let entered_by = match *other == sender {
+                true => None,
+                false => Some(sender.clone()),
+            };
+            users.push((other.clone(), entered_by));
+        }
+    }
+    users
+}
+
+/// "You are already doing dev" for the first of `users` whose task is still
+/// going on.
+pub fn busy(store: &ConnectedStore, users: &[(String, Option<String>)]) -> Option<String> {
+    let pendings = store.select_current_task().unwrap_or_default();
+    users.iter().find_map(|(user, entered_by)| {
+        pendings
+            .iter()
+            .find(|rec| rec.username == *user)
+            .map(|rec| format!("{} already doing {}", who_is(user, entered_by), rec.task))
+    })
+}
+
+/// ", with bob and carol" when a task is shared.
+pub fn with_whom(users: &[(String, Option<String>)]) -> String {
+    let names: Vec<String> = users
+        .iter()
+        .skip(1)
+        .map(|(user, _)| display_username(user))
+        .collect();
+    match names.split_last() {
+        None => String::new(),
+        Some((last, [])) => format!(", with {}", last),
+        Some((last, rest)) => format!(", with {} and {}", rest.join(", "), last),
+    }
+}
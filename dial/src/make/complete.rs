@@ -1,4 +1,7 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
 use super::common::select_project;
@@ -25,3 +28,19 @@ pub fn complete(
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "complete",
+        role: Role::Admin,
+        confirm: Policy::Overwrite,
+        grammar: expr::complete,
+        usage: "!complete <project-name> <date?>",
+        help: "set completion date for an existing project, if date is not provided, it will take the current time instead",
+        examples: &["!complete ac/bot", "!complete ac/bot 2024-05-31"],
+        handler: |context, _user, command| match command {
+            Command::Complete(project, end) => complete(context, project, end),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,183 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::Command;
+use shell::util::{display_username, human_duration, st_to_datestring};
+use std::time;
+
+use super::undo::whose;
+
+/// How long a pending command waits for its `!yes`.
+pub const WINDOW: time::Duration = time::Duration::from_secs(2 * 60);
+
+/// What running `command` would overwrite or remove, if anything.
+fn overwrites(context: &mut bot::Context, command: &Command, user: &str) -> Option<String> {
+    match command {
+        Command::Provision(project, d) => context
+            .store
+            .select_project_info(project.clone())
+            .ok()
+            .and_then(|info| info.provision)
+            .filter(|current| current != d)
+            .map(|current| {
+                format!(
+                    "This will overwrite the {} provision of {} with {}",
+                    human_duration(current),
+                    project,
+                    human_duration(*d)
+                )
+            }),
+        Command::Priority(project, n) => context
+            .store
+            .select_project_info(project.clone())
+            .ok()
+            .and_then(|info| info.priority)
+            .filter(|current| current != n)
+            .map(|current| {
+                format!(
+                    "This will overwrite the priority {} of {} with {}",
+                    current, project, n
+                )
+            }),
+        Command::Complete(project, date) => context
+            .store
+            .select_project_info(project.clone())
+            .ok()
+            .and_then(|info| info.completed)
+            .map(|current| {
+                format!(
+                    "This will overwrite the completion date of {}, {}, with {}",
+                    project,
+                    st_to_datestring(&current),
+                    st_to_datestring(date)
+                )
+            }),
+        Command::Parent(child, parent) => {
+            let projects = context.store.select_all_project_info().ok()?;
+            let current = projects
+                .iter()
+                .find(|p| &p.name == child)
+                .and_then(|p| p.parent)?;
+            projects
+                .iter()
+                .find(|p| p.id == current && &p.name != parent)
+                .map(|current| {
+                    format!(
+                        "This will move {} from {} to {}",
+                        child, current.name, parent
+                    )
+                })
+        }
+        Command::RemoveIntent(target, project) => {
+            let owner = target.clone().unwrap_or_else(|| String::from(user));
+            context
+                .store
+                .select_intent_for_project(project.clone())
+                .ok()?
+                .into_iter()
+                .find(|intent| intent.username == owner)
+                .map(|intent| {
+                    format!(
+                        "This will remove the {} intent of {} on {}",
+                        human_duration(intent.amount),
+                        display_username(&owner),
+                        project
+                    )
+                })
+        }
+        Command::RemoveAvail(target, id) => {
+            let owner = target.clone().unwrap_or_else(|| String::from(user));
+            context
+                .store
+                .select_avail_for_user(owner.clone())
+                .ok()?
+                .into_iter()
+                .find(|avail| avail.id == *id)
+                .map(|avail| {
+                    format!(
+                        "This will remove availability #{} of {}, {} from {} to {}",
+                        id,
+                        display_username(&owner),
+                        avail.pattern,
+                        st_to_datestring(&avail.start_time),
+                        st_to_datestring(&avail.end_time)
+                    )
+                })
+        }
+        Command::Undo | Command::Amend(_) => {
+            let latest = context
+                .store
+                .select_latest_task_for(String::from(user))
+                .ok()?;
+            let rec = latest.first()?;
+            let group = context.store.select_group(rec.id).unwrap_or_default();
+            match command {
+                Command::Amend(d) => Some(format!(
+                    "This will change {} {} from {} to {} for {}",
+                    rec.project,
+                    rec.task,
+                    human_duration(
+                        rec.end_time
+                            .duration_since(rec.start_time)
+                            .unwrap_or_default()
+                    ),
+                    human_duration(*d),
+                    whose(user, &group)
+                )),
+                _ => Some(format!(
+                    "This will remove {} {} for {}",
+                    rec.project,
+                    rec.task,
+                    whose(user, &group)
+                )),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The question to ask `user` before running `command` under `policy`.
+pub fn question(
+    context: &mut bot::Context,
+    spec: &Spec,
+    policy: Policy,
+    command: &Command,
+    user: &str,
+) -> Option<String> {
+    match policy {
+        Policy::Never => None,
+        Policy::Overwrite => overwrites(context, command, user),
+        Policy::Always => Some(
+            overwrites(context, command, user)
+                .unwrap_or_else(|| format!("This will run !{}", spec.name)),
+        ),
+    }
+}
+
+/// Keeps `body` until `user` confirms it, in place of anything they left
+/// pending before.
+pub fn ask(
+    context: &mut bot::Context,
+    user: String,
+    body: String,
+    questions: &[String],
+) -> Option<(String, String)> {
+    let reply = match questions {
+        [question] => format!(
+            "{}, reply !yes within {} to go ahead.",
+            question,
+            human_duration(WINDOW)
+        ),
+        _ => format!(
+            "{}.\nReply !yes within {} to go ahead.",
+            questions.join(".\n"),
+            human_duration(WINDOW)
+        ),
+    };
+    match context
+        .store
+        .insert_pending(user, body, time::SystemTime::now())
+    {
+        Ok(_) => Some((reply, String::new())),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
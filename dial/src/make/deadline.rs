@@ -1,4 +1,7 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
 use super::common::select_project;
@@ -25,3 +28,19 @@ pub fn deadline(
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "deadline",
+        role: Role::Admin,
+        confirm: Policy::Never,
+        grammar: expr::deadline,
+        usage: "!deadline <project-name> <date>",
+        help: "set a deadline for an existing project",
+        examples: &["!deadline ac/bot 2024-06-30", "!deadline ac/bot 30/06"],
+        handler: |context, _user, command| match command {
+            Command::Deadline(project, end) => deadline(context, project, end),
+            _ => None,
+        },
+    }
+}
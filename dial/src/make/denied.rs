@@ -0,0 +1,21 @@
+use shell::store::Role;
+
+fn with_article(role: Role) -> String {
+    match role {
+        Role::Admin => format!("an {}", role),
+        _ => format!("a {}", role),
+    }
+}
+
+/// The reply to a user whose role is below what a command requires.
+pub fn denied(name: &str, required: Role, actual: Role) -> Option<(String, String)> {
+    Some((
+        format!(
+            "!{} requires the {} role, you are {} in this room. Ask an admin to !grant it to you.",
+            name,
+            required,
+            with_article(actual)
+        ),
+        String::new(),
+    ))
+}
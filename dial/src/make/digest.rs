@@ -1,8 +1,11 @@
 use crate::bot;
-use html::{anchor, code, details, div, h2, no_display, paragraph, table, Element};
+use crate::registry::{Policy, Spec};
+use html::{anchor, code, details, div, escape, h2, no_display, paragraph, table, Element};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use shell::{
     store::NoteRecord,
-    util::{display_username, dur, human_duration, make_table_row},
+    util::{display_username, dur, human_duration, make_table_row, url_encode},
 };
 
 use super::common::select_project;
@@ -35,22 +38,26 @@ fn make_notes_html(notes: &[NoteRecord]) -> Element {
             .iter()
             .map(|note| {
                 div([
-                    code(format!(
+                    code(escape(format!(
                         "↪ {} ({})",
                         shell::util::display_username(note.username.clone()),
                         shell::util::st_to_datestring(&note.created_at)
-                    )),
-                    paragraph(note.content.clone()),
+                    ))),
+                    paragraph(escape(&note.content)),
                 ])
             })
             .collect::<Vec<_>>())),
     }
 }
 
-pub fn digest(handler: &mut bot::Context, project: String) -> Option<(String, String)> {
+pub fn digest(
+    handler: &mut bot::Context,
+    project: String,
+    tag: Option<String>,
+) -> Option<(String, String)> {
     match select_project(handler, &project) {
         Err(candidates) => Some((candidates.as_text(""), candidates.as_html(""))),
-        Ok(_) => match handler.store.select_project(project.clone()) {
+        Ok(_) => match handler.store.select_project(project.clone(), tag.clone()) {
             Ok(ref recs) => {
                 let available = handler.store.select_project_info(project.clone());
                 let note_records = handler
@@ -87,7 +94,6 @@ pub fn digest(handler: &mut bot::Context, project: String) -> Option<(String, St
                 let done = recs
                     .iter()
                     .fold(std::time::Duration::from_secs(0), |acc, task| {
-                        println!("{} {:?}", task.project, task.duration);
                         acc + task.duration
                     })
                     .as_secs()
@@ -113,9 +119,17 @@ pub fn digest(handler: &mut bot::Context, project: String) -> Option<(String, St
                     .unwrap_or((format!("{} done", done), code(format!("done: {}", done))));
 
                 let cal_url = format!(
-                    "{}/{}/calendar/{}",
-                    handler.base_url, handler.room_id, project
+                    "{}/{}/calendar/{}{}",
+                    handler.base_url,
+                    handler.room_id,
+                    url_encode(&project),
+                    tag.as_ref()
+                        .map_or(String::new(), |tag| format!("?tag={}", url_encode(tag)))
                 );
+                let title = match &tag {
+                    Some(tag) => format!("{} #{}", project, tag),
+                    None => project.clone(),
+                };
                 let (cal_string, cal_html) = (
                     format!("calendar: {}", cal_url),
                     div(anchor("Calendar Link ").set("href", cal_url)),
@@ -123,16 +137,39 @@ pub fn digest(handler: &mut bot::Context, project: String) -> Option<(String, St
                 Some((
                     format!(
                         "{}\n{}\n{}\n{}\n{}",
-                        &project,
+                        &title,
                         h0,
                         text_notes,
                         left.join("\n"),
                         cal_string
                     ),
-                    div(vec![h2(&project), h1, html_notes, table(right), cal_html]).as_string(),
+                    div(vec![
+                        h2(escape(&title)),
+                        h1,
+                        html_notes,
+                        table(right),
+                        cal_html,
+                    ])
+                    .as_string(),
                 ))
             }
             Err(_) => None,
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "digest",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::digest,
+        usage: "!digest <project-name> <#tag?>",
+        help: "give stats for a given project, only for tasks with #tag if given",
+        examples: &["!digest ac/bot", "!digest ac/bot #billable"],
+        handler: |context, _user, command| match command {
+            Command::Digest(project, tag) => digest(context, project, tag),
+            _ => None,
+        },
+    }
+}
@@ -1,62 +1,113 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{ConnectedStore, Role, StoreResult};
 use shell::util::human_duration;
 use std::time;
 
-use super::common::{check_meta, select_project};
+use super::common::{busy, check_meta, on_behalf, participants, select_project, with_whom};
 
+/// Records the task for all of `users`, starting it at the end of the last
+/// task any of them logged if that comes later, unless one of them is still
+/// doing something.
+fn record(
+    store: &mut ConnectedStore,
+    users: &[(String, Option<String>)],
+    duration: time::Duration,
+    project_name: String,
+    task: String,
+    tags: &[String],
+) -> StoreResult<String> {
+    if let Some(busy) = busy(store, users) {
+        return Ok(format!("{}, you're covered, or tricky :)", busy));
+    }
+    let now = time::SystemTime::now();
+    let given_start = now - duration;
+    let start = users
+        .iter()
+        .filter_map(|(user, _)| {
+            store
+                .select_latest_task_for(user.clone())
+                .ok()
+                .and_then(|res| res.first().map(|rec| rec.end_time))
+        })
+        .fold(given_start, |start, end| start.max(end));
+
+    let message = match start > given_start {
+        true => format!(
+            "Recorded, but adjusted to the end of your last task. Resulting in just {}",
+            human_duration(
+                start
+                    .elapsed()
+                    .unwrap_or_else(|_| time::Duration::from_millis(0))
+            )
+        ),
+        false => format!("Well recorded{}.", with_whom(users)),
+    };
+
+    match users {
+        [(user, entered_by)] => store.insert_do(
+            user.clone(),
+            start,
+            now,
+            project_name,
+            task,
+            tags,
+            entered_by.clone(),
+        ),
+        _ => store.insert_do_group(users, start, now, project_name, task, tags),
+    }
+    .map(|_| message)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn done(
     handler: &mut bot::Context,
     user: String,
+    target: Option<String>,
     duration: time::Duration,
     project_name: String,
     task: String,
+    with: Vec<String>,
+    tags: Vec<String>,
 ) -> Option<(String, String)> {
-    let now = time::SystemTime::now();
-    let pendings = handler.store.select_current_task().unwrap_or_default();
-
-    match pendings.iter().find(|rec| rec.username == user) {
-        Some(rec) => Some((
-            format!(
-                "You are already doing {}, you're covered, or tricky :)",
-                rec.task
-            ),
-            String::new(),
-        )),
-        None => match select_project(handler, &project_name) {
-            Err(candidates) => Some((candidates.as_text(""), candidates.as_html(""))),
-            Ok(project) => match check_meta(handler, &project) {
-                Some(r) => Some(r),
-                None => {
-                    let given_start = now - duration;
-                    let start = handler
-                        .store
-                        .select_latest_task_for(user.clone())
-                        .map(|res| {
-                            let i = res.first().map(|rec| rec.end_time).unwrap_or(given_start);
-                            match i < given_start {
-                                true => given_start,
-                                false => i,
-                            }
-                        })
-                        .unwrap_or(given_start);
-
-                    let message = match start > given_start {
-                        true => format!(
-                        "Recorded, but adjusted to the end of your last task. Resulting in just {}",
-                        human_duration(start.elapsed().unwrap_or_else(|_| time::Duration::from_millis(0)))
-                    ),
-                        false => "Well recorded.".into(),
-                    };
+    let (user, entered_by) = on_behalf(user, target);
+    let users = participants(&user, &entered_by, &with);
 
-                    match handler
-                        .store
-                        .insert_do(user, start, now, project_name, task)
-                    {
-                        Ok(_) => Some((message, String::new())),
-                        Err(err) => Some((format!("Error: {}", err), String::new())),
-                    }
-                }
+    match select_project(handler, &project_name) {
+        Err(candidates) => Some((candidates.as_text(""), candidates.as_html(""))),
+        Ok(project) => match check_meta(handler, &project) {
+            Some(r) => Some(r),
+            None => match handler
+                .store
+                .atomic(|store| record(store, &users, duration, project_name, task, &tags))
+            {
+                Ok(message) => Some((message, String::new())),
+                Err(err) => Some((format!("Error: {}", err), String::new())),
             },
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "done",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::done,
+        usage: "!done <@user?> <project-name> <task-name> <duration> <with @user...?> <#tag...?>",
+        help: "register a task that has lasted for <duration> from now, for everyone listed after with too; admins can register one for @user",
+        examples: &[
+            "!done ac/bot dev 2h",
+            "!done ac/bot meeting 1h 30m",
+            "!done @alice:matrix.org ac/bot dev 2h",
+            "!done ac/bot meeting 1h with @bob:matrix.org @carol:matrix.org",
+        ],
+        handler: |context, user, command| match command {
+            Command::Done(target, project, task, duration, with, tags) => {
+                done(context, user, target, duration, project, task, with, tags)
+            }
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,51 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::st_to_datestring;
+use std::time;
+
+use super::common::{on_behalf, whom};
+
+pub fn end_avail(
+    handler: &mut bot::Context,
+    user: String,
+    target: Option<String>,
+    id: i64,
+    end: Option<time::SystemTime>,
+) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
+    let end = end.unwrap_or_else(time::SystemTime::now);
+    match handler.store.end_avail(&user, id, end) {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(0) => Some((
+            format!(
+                "There's no availability #{} ending after {} for {}, sorry.",
+                id,
+                st_to_datestring(&end),
+                whom(&user, &entered_by)
+            ),
+            String::new(),
+        )),
+        Ok(_) => Some((
+            format!("Availability #{} ends on {}", id, st_to_datestring(&end)),
+            String::new(),
+        )),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "endavail",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::end_avail,
+        usage: "!endavail <@user?> <#id> [<date>]",
+        help: "bring the end of one of your availability periods, numbered as !avails shows them, forward to today or the given date; admins can do it for @user",
+        examples: &["!endavail #3", "!endavail @alice:matrix.org #3 2024-12-31"],
+        handler: |context, user, command| match command {
+            Command::EndAvail(target, id, end) => end_avail(context, user, target, id, end),
+            _ => None,
+        },
+    }
+}
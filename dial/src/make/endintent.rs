@@ -0,0 +1,57 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::st_to_datestring;
+use std::time;
+
+use super::common::{on_behalf, whom};
+
+pub fn end_intent(
+    handler: &mut bot::Context,
+    user: String,
+    target: Option<String>,
+    project: String,
+    end: Option<time::SystemTime>,
+) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
+    let by = entered_by.clone().unwrap_or_else(|| user.clone());
+    let end = end.unwrap_or_else(time::SystemTime::now);
+    match handler.store.end_intent(&user, &project, end, &by) {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(0) => Some((
+            format!(
+                "There's no intent on {} ending after {} for {}, sorry.",
+                project,
+                st_to_datestring(&end),
+                whom(&user, &entered_by)
+            ),
+            String::new(),
+        )),
+        Ok(_) => Some((
+            format!("Intent on {} ends on {}", project, st_to_datestring(&end)),
+            String::new(),
+        )),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "endintent",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::end_intent,
+        usage: "!endintent <@user?> <project-name> [<date>]",
+        help: "end your intent on a project today or on the given date, keeping what it planned until then; admins can do it for @user",
+        examples: &[
+            "!endintent ac/bot",
+            "!endintent @alice:matrix.org ac/bot 2024-12-31",
+        ],
+        handler: |context, user, command| match command {
+            Command::EndIntent(target, project, end) => {
+                end_intent(context, user, target, project, end)
+            }
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,60 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{anchor, div, paragraph, Element};
+use shell::expr::{self, Command};
+use shell::store::Role;
+
+/// The calendar feeds a feed token opens, as file name and description.
+const FEEDS: &[(&str, &str)] = &[
+    ("tasks.ics", "your logged tasks"),
+    ("plan.ics", "your planned work"),
+    ("deadlines.ics", "project deadlines and completions"),
+];
+
+const NOTE: &str = "Anyone with these links can read them, !feed renew replaces them.";
+
+pub fn feed(handler: &mut bot::Context, user: String, renew: bool) -> Option<(String, String)> {
+    match handler.store.feed_token(&user, renew) {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(token) => {
+            let urls: Vec<(String, &str)> = FEEDS
+                .iter()
+                .map(|(file, desc)| {
+                    (
+                        format!("http://{}/feed/{}/{}", handler.base_url, token, file),
+                        *desc,
+                    )
+                })
+                .collect();
+            let left: Vec<String> = urls
+                .iter()
+                .map(|(url, desc)| format!("{}: {}", desc, url))
+                .collect();
+            let mut elements: Vec<Element> = urls
+                .iter()
+                .map(|(url, desc)| div(anchor(*desc).set("href", url)))
+                .collect();
+            elements.push(paragraph(NOTE));
+            Some((
+                format!("{}\n{}", left.join("\n"), NOTE),
+                div(elements).as_string(),
+            ))
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "feed",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::feed,
+        usage: "!feed [renew]",
+        help: "give the links to subscribe to your tasks, your plan and the deadlines of the room from a calendar app; renew makes new links, the former ones stop working",
+        examples: &["!feed", "!feed renew"],
+        handler: |context, user, command| match command {
+            Command::Feed(renew) => feed(context, user, renew),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,42 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::display_username;
+
+use super::common::is_last_admin;
+
+pub fn grant(handler: &mut bot::Context, user: String, role: Role) -> Option<(String, String)> {
+    if role != Role::Admin && is_last_admin(handler, &user) {
+        return Some((
+            format!(
+                "{} is the last admin of this room, grant admin to someone else first.",
+                display_username(&user)
+            ),
+            String::new(),
+        ));
+    }
+    match handler.store.insert_role(user.clone(), role) {
+        Ok(_) => Some((
+            format!("{} is now {} in this room.", display_username(&user), role),
+            String::new(),
+        )),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "grant",
+        role: Role::Admin,
+        confirm: Policy::Never,
+        grammar: expr::grant,
+        usage: "!grant <@user> admin | member | viewer",
+        help: "give a role to a user in this room: viewers read reports, members log their work, admins manage projects and roles",
+        examples: &["!grant @bob:matrix.org viewer", "!grant @carol:matrix.org admin"],
+        handler: |context, _user, command| match command {
+            Command::Grant(user, role) => grant(context, user, role),
+            _ => None,
+        },
+    }
+}
@@ -1,167 +1,189 @@
-use html::{anchor, div, em, h4, paragraph, span};
+use html::{anchor, div, em, escape, h4, paragraph, Element, Node};
+use shell::expr::{self, Command};
+use shell::store::Role;
 
 use crate::bot;
+use crate::registry::{self, Policy, Spec};
 
 const DATE_HELP: &str = "
     Date must be formated as YYYY-MM-DD, DD-MM-YYY or DD-MM (for a date within the current year).
     The separatore can be either '-', '/' or '.'.
 ";
 
-fn make_text(handler: &mut bot::Context) -> String {
+fn takes_date(spec: &Spec) -> bool {
+    spec.usage.contains("<date")
+}
+
+/// When the command waits for a `!yes`, unless configured otherwise.
+fn confirm_help(spec: &Spec) -> Option<&'static str> {
+    match spec.confirm {
+        Policy::Always => Some("asks for a !yes before running"),
+        Policy::Overwrite => Some("asks for a !yes before overwriting or removing anything"),
+        Policy::Never => None,
+    }
+}
+
+fn spec_text(spec: &Spec, detailed: bool) -> String {
+    let mut text = format!("        {}\n            {}\n", spec.usage, spec.help);
+    if takes_date(spec) {
+        text.push_str(&format!("            {}\n", DATE_HELP.trim()));
+    }
+    if detailed {
+        text.push_str(&format!("            requires the {} role\n", spec.role));
+        if let Some(confirm) = confirm_help(spec) {
+            text.push_str(&format!("            {}\n", confirm));
+        }
+        for example in spec.examples {
+            text.push_str(&format!("            e.g. {}\n", example));
+        }
+    }
+    text
+}
+
+/// Escapes `text` and emphasizes its `<arg>` placeholders, which clients
+/// would otherwise take for tags and drop.
+fn placeholders(text: &str) -> Vec<Node> {
+    let mut pieces = text.split('<');
+    let mut nodes: Vec<Node> = pieces
+        .next()
+        .map(escape)
+        .into_iter()
+        .map(Node::from)
+        .collect();
+    for piece in pieces {
+        match piece.split_once('>') {
+            Some((arg, rest)) => {
+                nodes.push(em(escape(arg)).into());
+                nodes.push(escape(rest).into());
+            }
+            None => nodes.push(escape(format!("<{}", piece)).into()),
+        }
+    }
+    nodes
+}
+
+/// Renders `!cmd <arg> <other-arg>` with the arguments emphasized.
+pub fn usage_html(usage: &str) -> Element {
+    h4(placeholders(usage))
+}
+
+fn spec_html(spec: &Spec, detailed: bool) -> Vec<Element> {
+    let mut elements = vec![usage_html(spec.usage), paragraph(placeholders(spec.help))];
+    if takes_date(spec) {
+        elements.push(paragraph(DATE_HELP));
+    }
+    if detailed {
+        elements.push(paragraph(format!("Requires the {} role.", spec.role)));
+        if let Some(confirm) = confirm_help(spec) {
+            let mut sentence = String::from(confirm);
+            sentence[..1].make_ascii_uppercase();
+            elements.push(paragraph(format!("{}.", sentence)));
+        }
+        for example in spec.examples {
+            elements.push(paragraph(escape(format!("e.g. {}", example))));
+        }
+    }
+    elements
+}
+
+fn make_text(handler: &mut bot::Context, specs: &[Spec]) -> String {
     let base_url = &handler.base_url;
     let room_id = &handler.room_id;
-    format!("
-        !ping
-            check if the bot's still alive
-        !new <project-name>
-            register a new project
-        !deadline <project-name> <date>
-            set a deadline for an existing project
-            {DATE_HELP}
-        !provision <project-name> <duration>
-            set provisioned time for an existing project
-        !complete  <project-name> <date?> 
-            set completion date for an existing project, if
-            date is not provided, it will take the current time instead.
-            {DATE_HELP}
-        !note <project-name> <text>
-            add a note to a project, free text.
-        !meta <project-name>
-            mark this project as a meta project.
-        !parent <child-project-name> <parent-project-name>
-            attach a project to a meta project.
-        !do <project-name> <task-name> <duration>
-            start a new task that will last for <duration>
-        !done <project-name> <task-name> <duration>
-            register a task that has lasted for <duration> from now
-        !switch <project-name> <task-name>
-            stop your current task and create a new one that has the same end time as the current one
-        !stop
-            stop your current task
-        !more <duration>
-            stop your current task and create a new one with same project and task for <duration>
-        !ls
-            list current tasks
-        !digest <project-name>
-            give stat for a given project
-        !since <date or duration>
-            a summary of your tasks since date
-        !avail <date> <date> <duration>
-            Set your maximum weekly availability during this period
-        !intent <project-name> <duration>
-            Set the amount of time you intend to dedicate to a project
+    let commands: String = specs.iter().map(|spec| spec_text(spec, false)).collect();
+    format!(
+        "
+{commands}
+        !help <command> gives details and examples for a command
+
+        Several commands can go in one message, one per line; if one of them
+        is not understood, none of them is run.
+
+        Viewers can read reports, members log their work too and admins also
+        manage projects and roles; !roles tells who is what in this room.
 
         Timeline is visible at http://{base_url}/{room_id}/timeline
-        
+
         Workload is visible at http://{base_url}/{room_id}/load
-        ")
+        "
+    )
+}
+
+fn make_html(handler: &mut bot::Context, specs: &[Spec]) -> String {
+    let mut elements: Vec<Element> = specs
+        .iter()
+        .flat_map(|spec| spec_html(spec, false))
+        .collect();
+    elements.push(paragraph(
+        "<strong>!help</strong> <em>command</em> gives details and examples for a command.",
+    ));
+    elements.push(paragraph(
+        "Several commands can go in one message, one per line; if one of them is not understood, none of them is run.",
+    ));
+    elements.push(paragraph(
+        "Viewers can read reports, members log their work too and admins also manage projects and roles; <strong>!roles</strong> tells who is what in this room.",
+    ));
+    elements.push(div(vec![anchor("TIMELINE").set(
+        "href",
+        format!("{}/{}/timeline", handler.base_url, handler.room_id),
+    )]));
+    elements.push(div(vec![anchor("WORLOAD").set(
+        "href",
+        format!("{}/{}/load", handler.base_url, handler.room_id),
+    )]));
+    div(elements).as_string()
+}
+
+pub fn help(handler: &mut bot::Context, topic: Option<String>) -> Option<(String, String)> {
+    let specs = registry::registry();
+    match topic {
+        None => Some((make_text(handler, &specs), make_html(handler, &specs))),
+        Some(name) => match registry::find(&specs, &name) {
+            Some(spec) => Some((
+                spec_text(spec, true),
+                div(spec_html(spec, true)).as_string(),
+            )),
+            None => Some((
+                format!("Unknown command !{}, try !help", name),
+                div(format!(
+                    "Unknown command <strong>!{}</strong>, try <strong>!help</strong>",
+                    escape(&name)
+                ))
+                .as_string(),
+            )),
+        },
+    }
 }
 
-fn make_html(handler: &mut bot::Context) -> String {
-    div(vec![
-        h4("!ping"),
-        paragraph("check if the bot's still alive"),
-        h4(vec![span("!new "), em("project-name")]),
-        paragraph("register a new project"),
-        h4(vec![
-            span("!deadline  "),
-            em("project-name "),
-            em("date"),
-        ]),
-        paragraph("set a deadline for an existing project"),
-        paragraph(DATE_HELP),
-        h4(vec![
-            span("!provision  "),
-            em("project-name "),
-            em("duration"),
-        ]),
-        paragraph("set provisioned time for an existing project"),
-        h4(vec![span("!complete   "), em("project-name "), em("date?")]),
-        paragraph(
-            "set completion date for an existing project, if
-            date is not provided, it will take the current time instead.",
-        ),
-        paragraph(DATE_HELP),
-        h4(vec![span("!note   "),  em("project-name "), em("text")]),
-        paragraph(
-            "add a note to a project, free text.",
-        ),
-        h4(vec![span("!meta   "),  em("project-name ")]),
-        paragraph(
-            "mark this project as a meta project.",
-        ),
-        h4(vec![span("!parent   "),  em("child-project-name "),  em("parent-project-name ")]),
-        paragraph(
-            "attach a project to a meta project.",
-        ),
-        h4(vec![
-            span("!do  "),
-            em("project-name "),
-            em("task "),
-            em("duration "),
-        ]),
-        paragraph("start a new task that will last for <em>duration</em>."),
-        h4(vec![
-            span("!done  "),
-            em("project-name "),
-            em("task "),
-            em("duration "),
-        ]),
-        paragraph("register a task that has lasted for <em>duration</em> from now."),
-        h4(vec![
-            span("!switch  "),
-            em("project-name "),
-            em("task "),
-        ]),
-        paragraph("stop your current task and create a new one that has the same end time as the current one."),
-        h4(vec![
-            span("!stop  "),
-            ]),
-        paragraph("stop your current task."),
-        h4(vec![
-            span("!more  "),
-            em("duration "),
-            ]),
-        paragraph("stop your current task and create a new one with same project and task for <em>duration</em>."),
-        h4(vec![
-            span("!ls  "),
-            ]),
-        paragraph("list current tasks."),
-        h4(vec![
-            span("!digest  "),
-            em("project-name "),
-        ]),
-        paragraph("give stats for a given project."),
-        h4(vec![
-            span("!since  "),
-            em("duration "),
-        ]),
-        paragraph("a summary of your tasks since date."),
-        h4(vec![
-            span("!avail  "),
-            em("data "),
-            em("data "),
-            em("duration "),
-        ]),
-        paragraph("Set your maximum weekly availability during this period."),
-        h4(vec![
-            span("!intent  "),
-            em("project-name "),
-            em("duration "),
-        ]),
-        paragraph("Set the amount of time you intend to dedicate to a project."),
-        div(vec![
-            anchor("TIMELINE").set("href", format!("{}/{}/timeline
-        ", handler.base_url, handler.room_id))
-        ]),
-        div(vec![
-            anchor("WORLOAD").set("href", format!("{}/{}/load
-        ", handler.base_url, handler.room_id))
-        ])
-        ])
-        .as_string()
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "help",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::help,
+        usage: "!help <command?>",
+        help: "list all commands, or give details and examples for one of them",
+        examples: &["!help", "!help do"],
+        handler: |context, _user, command| match command {
+            Command::Help(topic) => help(context, topic),
+            _ => None,
+        },
+    }
 }
 
-pub fn help(handler: &mut bot::Context) -> Option<(String, String)> {
-    Some((make_text(handler), make_html(handler)))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emphasizes_placeholders() {
+        let spec = registry::registry()
+            .into_iter()
+            .find(|spec| spec.name == "done")
+            .unwrap();
+        let html = div(spec_html(&spec, true)).as_string();
+
+        assert!(html.contains("<em>duration</em>"), "{}", html);
+        assert!(html.contains("<em>project-name</em>"), "{}", html);
+        assert!(!html.contains("<duration>"), "{}", html);
+    }
 }
@@ -0,0 +1,48 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::ical;
+use shell::store::Role;
+use std::fs;
+
+/// Reads the holidays of the room from an iCalendar file kept on the bot's
+/// host, replacing those of an earlier import of the same file.
+pub fn holidays(handler: &mut bot::Context, file: String) -> Option<(String, String)> {
+    let path = match handler.store.holidays_path(&file) {
+        Some(path) => path,
+        None => return Some((format!("{} is not a plain file name.", file), String::new())),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) => {
+            return Some((
+                format!("Could not read {} from the holidays directory.", file),
+                String::new(),
+            ))
+        }
+    };
+    let events = ical::events(&text);
+    match handler.store.replace_holidays(&file, &events) {
+        Ok(n) => Some((
+            format!("Imported {} holidays from {} for the whole room.", n, file),
+            String::new(),
+        )),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "holidays",
+        role: Role::Admin,
+        confirm: Policy::Never,
+        grammar: expr::holidays,
+        usage: "!holidays <file.ics>",
+        help: "import the holidays of the whole room from an iCalendar file in the holidays directory next to the databases, again to update them",
+        examples: &["!holidays france.ics"],
+        handler: |context, _user, command| match command {
+            Command::Holidays(file) => holidays(context, file),
+            _ => None,
+        },
+    }
+}
@@ -1,4 +1,7 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
 use super::common::{check_meta, select_project};
@@ -8,6 +11,7 @@ pub fn intent(
     user: String,
     project_name: String,
     amount: time::Duration,
+    start: Option<time::SystemTime>,
 ) -> Option<(String, String)> {
     match select_project(handler, &project_name) {
         Err(candidates) => Some((
@@ -16,10 +20,31 @@ pub fn intent(
         )),
         Ok(project) => match check_meta(handler, &project) {
             Some(r) => Some(r),
-            None => match handler.store.insert_intent(user, project_name, amount) {
+            None => match handler
+                .store
+                .insert_intent(user, project_name, amount, start)
+            {
                 Ok(_) => Some(("You won't regret it".into(), String::new())),
                 Err(err) => Some((format!("Error: {}", err), String::new())),
             },
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "intent",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::intent,
+        usage: "!intent <project-name> <duration> [from <date>]",
+        help: "set the amount of time you intend to dedicate to a project, counting what you log from the given date on, or from the project start",
+        examples: &["!intent ac/bot 40h", "!intent ac/bot 20h from 2024-09-01"],
+        handler: |context, user, command| match command {
+            Command::Intent(project_name, amount, start) => {
+                intent(context, user, project_name, amount, start)
+            }
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,73 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, Element};
+use shell::expr::{self, Command};
+use shell::store::{IntentChange, Role};
+use shell::util::{display_username, human_duration, make_table_row, st_to_datestring};
+
+fn cells(change: &IntentChange) -> Vec<String> {
+    let period = match (change.start_time, change.end_time) {
+        (Some(start), Some(end)) => format!(
+            "from {} until {}",
+            st_to_datestring(&start),
+            st_to_datestring(&end)
+        ),
+        (Some(start), None) => format!("from {}", st_to_datestring(&start)),
+        (None, Some(end)) => format!("until {}", st_to_datestring(&end)),
+        (None, None) => String::new(),
+    };
+    vec![
+        st_to_datestring(&change.changed_at),
+        change.change.clone(),
+        display_username(&change.username),
+        change.project.clone(),
+        human_duration(change.amount),
+        period,
+        format!("by {}", display_username(&change.changed_by)),
+    ]
+}
+
+/// The changes made to intents, of a user when `selector` starts with
+/// `@`, or on a project.
+pub fn intent_log(
+    handler: &mut bot::Context,
+    selector: Option<String>,
+) -> Option<(String, String)> {
+    let history = match selector.as_deref() {
+        Some(user) if user.starts_with('@') => {
+            handler.store.select_intent_history(Some(user), None)
+        }
+        Some(project) => handler.store.select_intent_history(None, Some(project)),
+        None => handler.store.select_intent_history(None, None),
+    };
+    match history {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(history) if history.is_empty() => {
+            Some((String::from("No intent changed yet."), String::new()))
+        }
+        Ok(history) => {
+            let left: Vec<String> = history
+                .iter()
+                .map(|c| cells(c).join("\t").trim_end().to_string())
+                .collect();
+            let rows: Vec<Element> = history.iter().map(|c| make_table_row(cells(c))).collect();
+            Some((left.join("\n"), table(rows).as_string()))
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "intentlog",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::intent_log,
+        usage: "!intentlog [<@user> | <project-name>]",
+        help: "list who set, ended or removed intents and when, in the room, of a user or on a project",
+        examples: &["!intentlog", "!intentlog @alice:matrix.org", "!intentlog ac/bot"],
+        handler: |context, _user, command| match command {
+            Command::IntentLog(selector) => intent_log(context, selector),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,67 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, Element};
+use shell::expr::{self, Command};
+use shell::store::{Intent, Role};
+use shell::util::{display_username, human_duration, make_table_row, st_to_datestring};
+use std::time;
+
+fn cells(intent: &Intent) -> Vec<String> {
+    vec![
+        display_username(&intent.username),
+        intent.project.clone(),
+        human_duration(intent.amount),
+        format!("from {}", st_to_datestring(&intent.start_time)),
+        intent.end_time.map_or(String::new(), |end| {
+            format!("until {}", st_to_datestring(&end))
+        }),
+    ]
+}
+
+/// The intents still running, of a user when `selector` starts with `@`,
+/// or on a project.
+pub fn intents(handler: &mut bot::Context, selector: Option<String>) -> Option<(String, String)> {
+    let intents = match &selector {
+        Some(user) if user.starts_with('@') => handler.store.select_intent_for_user(user.clone()),
+        Some(project) => handler.store.select_intent_for_project(project.clone()),
+        None => handler.store.select_intent_all(),
+    };
+    let now = time::SystemTime::now();
+    match intents {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(intents) => {
+            let running: Vec<&Intent> = intents
+                .iter()
+                .filter(|i| match i.end_time {
+                    Some(end) => end > now,
+                    None => true,
+                })
+                .collect();
+            if running.is_empty() {
+                return Some((String::from("No intents yet."), String::new()));
+            }
+            let left: Vec<String> = running
+                .iter()
+                .map(|i| cells(i).join("\t").trim_end().to_string())
+                .collect();
+            let rows: Vec<Element> = running.iter().map(|i| make_table_row(cells(i))).collect();
+            Some((left.join("\n"), table(rows).as_string()))
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "intents",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::intents,
+        usage: "!intents [<@user> | <project-name>]",
+        help: "list the intents still running, in the room, of a user or on a project",
+        examples: &["!intents", "!intents @alice:matrix.org", "!intents ac/bot"],
+        handler: |context, _user, command| match command {
+            Command::Intents(selector) => intents(context, selector),
+            _ => None,
+        },
+    }
+}
@@ -1,9 +1,21 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{EndPolicy, EndPolicyRecord, Role};
 use shell::util::{display_username, human_duration};
 use std::time;
 
+fn end_policy_for(user: &str, policies: &[EndPolicyRecord]) -> EndPolicy {
+    policies
+        .iter()
+        .find(|p| p.username == user)
+        .map(|p| p.policy.clone())
+        .unwrap_or_default()
+}
+
 pub fn list(handler: &mut bot::Context) -> Option<(String, String)> {
     let now = time::SystemTime::now();
+    let policies = handler.store.select_end_policy_all().unwrap_or_default();
 
     // let timeline_url = format!("{}/{}/timeline", handler.host, handler.room_id);
 
@@ -12,18 +24,20 @@ pub fn list(handler: &mut bot::Context) -> Option<(String, String)> {
             recs.iter()
                 .map(|rec| match rec.end_time.duration_since(now) {
                     Ok(duration) => format!(
-                        "{} is {}ing on {}, they will be done in {}",
+                        "{} is {}ing on {}, they will be done in {} (at end: {})",
                         display_username(&rec.username),
                         rec.task,
                         rec.project,
-                        human_duration(duration)
+                        human_duration(duration),
+                        end_policy_for(&rec.username, &policies)
                     ),
                     Err(err) => format!(
-                        "{} is {}ing on {}, they will be done in {}",
+                        "{} is {}ing on {}, they will be done in {} (at end: {})",
                         display_username(&rec.username),
                         rec.task,
                         rec.project,
-                        err
+                        err,
+                        end_policy_for(&rec.username, &policies)
                     ),
                 })
                 .collect::<Vec<String>>()
@@ -34,3 +48,19 @@ pub fn list(handler: &mut bot::Context) -> Option<(String, String)> {
         Err(_) => None,
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "ls",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::list,
+        usage: "!ls",
+        help: "list current tasks",
+        examples: &["!ls"],
+        handler: |context, _user, command| match command {
+            Command::List => list(context),
+            _ => None,
+        },
+    }
+}
@@ -1,5 +1,8 @@
+use shell::store::Role;
 use super::common::select_project;
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
 
 pub fn meta(
     handler: &mut bot::Context,
@@ -31,3 +34,19 @@ pub fn meta(
         }
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "meta",
+        role: Role::Admin,
+        confirm: Policy::Never,
+        grammar: expr::meta,
+        usage: "!meta <project-name>",
+        help: "mark this project as a meta project",
+        examples: &["!meta ac/all"],
+        handler: |context, user, command| match command {
+            Command::Meta(project) => meta(context, user, project),
+            _ => None,
+        },
+    }
+}
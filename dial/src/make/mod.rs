@@ -1,40 +1,50 @@
-mod avail;
+pub mod amend;
+pub mod avail;
+pub mod avails;
+mod batch;
 mod common;
-mod complete;
-mod deadline;
-mod digest;
-mod done;
-mod help;
-mod intent;
-mod list;
-mod meta;
-mod more;
-mod new;
-mod note;
-mod parent;
+pub mod complete;
+pub mod confirm;
+pub mod deadline;
+mod denied;
+pub mod digest;
+pub mod done;
+pub mod endavail;
+pub mod endintent;
+pub mod feed;
+pub mod grant;
+pub mod help;
+pub mod holidays;
+pub mod intent;
+pub mod intentlog;
+pub mod intents;
+pub mod list;
+pub mod meta;
+pub mod more;
+pub mod new;
+pub mod note;
+pub mod off;
+pub mod on_end;
+pub mod parent;
 mod parse_error;
-mod provision;
-mod since;
-mod start;
-mod stop;
-mod switch;
+pub mod plan;
+pub mod ping;
+pub mod priority;
+pub mod provision;
+pub mod revoke;
+pub mod rmavail;
+pub mod rmintent;
+pub mod roles;
+pub mod since;
+pub mod start;
+pub mod stop;
+pub mod strategy;
+pub mod switch;
+pub mod tags;
+pub mod undo;
+pub mod whatif;
+pub mod yes;
 
-pub use crate::make::avail::avail;
-pub use crate::make::complete::complete;
-pub use crate::make::deadline::deadline;
-pub use crate::make::digest::digest;
-pub use crate::make::done::done;
-pub use crate::make::help::help;
-pub use crate::make::intent::intent;
-pub use crate::make::list::list;
-pub use crate::make::meta::meta;
-pub use crate::make::more::more;
-pub use crate::make::new::new;
-pub use crate::make::note::note;
-pub use crate::make::parent::parent;
+pub use crate::make::batch::{batch, Outcome};
+pub use crate::make::denied::denied;
 pub use crate::make::parse_error::parse_error;
-pub use crate::make::provision::provision;
-pub use crate::make::since::since;
-pub use crate::make::start::start;
-pub use crate::make::stop::stop;
-pub use crate::make::switch::switch;
@@ -1,12 +1,18 @@
 use crate::bot;
-use shell::store::TaskRecord;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{Role, TaskRecord};
 use std::time;
 
+use super::common::{on_behalf, whom};
+
 pub fn more(
     handler: &mut bot::Context,
     user: String,
+    target: Option<String>,
     duration: time::Duration,
 ) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
     let now = time::SystemTime::now();
     let empty: Vec<TaskRecord> = Vec::new();
     let pendings = handler
@@ -15,18 +21,46 @@ pub fn more(
         .unwrap_or(empty);
 
     match pendings.first() {
-        Some(rec) => match handler.store.update_task_end(rec.id, now).and_then(|_| {
+        Some(rec) => match handler.store.atomic(|store| {
+            store.update_task_end(rec.id, now)?;
             let end = now + duration;
-            handler
-                .store
-                .insert_do(user, now, end, rec.project.clone(), rec.task.clone())
+            let tags = store.select_tags_for_do(rec.id).unwrap_or_default();
+            store.insert_do(
+                user.clone(),
+                now,
+                end,
+                rec.project.clone(),
+                rec.task.clone(),
+                &tags,
+                entered_by.clone(),
+            )
         }) {
             Err(err) => Some((format!("Error: {}", err), String::new())),
             Ok(_) => Some(("Keep up the good work!".to_string(), String::new())),
         },
         None => Some((
-            String::from("There's nothing to !more for you, sorry."),
+            format!(
+                "There's nothing to !more for {}, sorry.",
+                whom(&user, &entered_by)
+            ),
             String::new(),
         )),
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "more",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::more,
+        usage: "!more <@user?> <duration>",
+        help:
+            "stop your current task and create a new one with same project and task for <duration>; admins can do it for @user",
+        examples: &["!more 30m", "!more @alice:matrix.org 1h"],
+        handler: |context, user, command| match command {
+            Command::More(target, d) => more(context, user, target, d),
+            _ => None,
+        },
+    }
+}
@@ -1,4 +1,7 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
 pub fn new(
@@ -17,3 +20,19 @@ pub fn new(
         Ok(_) => Some(("Yeah! New Project!".into(), String::new())),
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "new",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::add,
+        usage: "!new <project-name>",
+        help: "register a new project",
+        examples: &["!new ac/bot"],
+        handler: |context, user, command| match command {
+            Command::Add(project) => new(context, user, project),
+            _ => None,
+        },
+    }
+}
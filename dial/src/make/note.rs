@@ -1,5 +1,8 @@
+use shell::store::Role;
 use super::common::select_project;
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
 
 pub fn note(
     handler: &mut bot::Context,
@@ -18,3 +21,19 @@ pub fn note(
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "note",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::note,
+        usage: "!note <project-name> <text>",
+        help: "add a note to a project, free text",
+        examples: &["!note ac/bot the client wants a dark theme"],
+        handler: |context, user, command| match command {
+            Command::Note(project, content) => note(context, user, project, content),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,59 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::st_to_datestring;
+use std::time;
+
+const DAY: time::Duration = time::Duration::from_secs(24 * 60 * 60);
+
+/// Days off from `start` to `end`, both included, or only `start`.
+pub fn off(
+    handler: &mut bot::Context,
+    user: String,
+    start: time::SystemTime,
+    end: Option<time::SystemTime>,
+    half: bool,
+) -> Option<(String, String)> {
+    let last = end.unwrap_or(start).max(start);
+    let portion = if half { 0.5 } else { 1.0 };
+    match handler
+        .store
+        .insert_absence(Some(user), start, last + DAY, portion, "off")
+    {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(_) => {
+            let days = match (last == start, half) {
+                (true, true) => format!("Half of {} off", st_to_datestring(&start)),
+                (true, false) => format!("{} off", st_to_datestring(&start)),
+                (false, true) => format!(
+                    "Half days off from {} to {}",
+                    st_to_datestring(&start),
+                    st_to_datestring(&last)
+                ),
+                (false, false) => format!(
+                    "Off from {} to {}",
+                    st_to_datestring(&start),
+                    st_to_datestring(&last)
+                ),
+            };
+            Some((format!("{}, enjoy.", days), String::new()))
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "off",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::off,
+        usage: "!off <date> [<date>] [half-day]",
+        help: "take days off, the last date included; the planner books nothing on them",
+        examples: &["!off 2026-12-21 2027-01-03", "!off tomorrow half-day"],
+        handler: |context, user, command| match command {
+            Command::Off(start, end, half) => off(context, user, start, end, half),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,39 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{EndPolicy, Role};
+
+pub fn on_end(
+    handler: &mut bot::Context,
+    user: String,
+    policy: EndPolicy,
+) -> Option<(String, String)> {
+    let message = match policy {
+        EndPolicy::Extend(_) => format!(
+            "From now on, when a task runs out I'll {} until you !stop.",
+            policy
+        ),
+        EndPolicy::Ask => "From now on, I'll ask you before a task runs out.".into(),
+        EndPolicy::Silent => "From now on, your tasks will end silently.".into(),
+    };
+    match handler.store.insert_end_policy(user, policy) {
+        Ok(_) => Some((message, String::new())),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "onend",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::on_end,
+        usage: "!onend extend <duration> | ask | silent",
+        help: "choose what happens when your task runs out: extend it by <duration> until you !stop, ask you to !more (default), or end it silently",
+        examples: &["!onend extend 30m", "!onend ask", "!onend silent"],
+        handler: |context, user, command| match command {
+            Command::OnEnd(policy) => on_end(context, user, policy),
+            _ => None,
+        },
+    }
+}
@@ -1,5 +1,8 @@
+use shell::store::Role;
 use super::common::select_project;
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
 
 pub fn parent(
     handler: &mut bot::Context,
@@ -61,3 +64,19 @@ pub fn parent(
         }
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "parent",
+        role: Role::Admin,
+        confirm: Policy::Overwrite,
+        grammar: expr::parent,
+        usage: "!parent <child-project-name> <parent-project-name>",
+        help: "attach a project to a meta project",
+        examples: &["!parent ac/bot ac/all"],
+        handler: |context, user, command| match command {
+            Command::Parent(child, parent_name) => parent(context, user, child, parent_name),
+            _ => None,
+        },
+    }
+}
@@ -1,14 +1,111 @@
-use html::div;
-use shell::parser_ext::ParseCommandError;
+use html::{div, escape, paragraph, pre, Element};
+use shell::parser_ext::{CommandError, ParseCommandError};
+use strsim::levenshtein;
 
-fn make_text(err: &ParseCommandError) -> String {
-    format!("Oops! {}", err)
+use super::help::usage_html;
+use crate::registry::{find, Spec};
+
+/// How far a typo can be from a command name and still be suggested.
+const MAX_DISTANCE: usize = 2;
+
+fn suggest<'a>(body: &str, specs: &'a [Spec]) -> Option<&'a str> {
+    let typed = body
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .trim_start_matches('!');
+    specs
+        .iter()
+        .map(|spec| (spec.name, levenshtein(typed, spec.name)))
+        .filter(|(_, distance)| *distance <= MAX_DISTANCE)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(name, _)| name)
+}
+
+/// The command as typed with a `^` under the character at `position`.
+fn marked(body: &str, position: usize) -> String {
+    let column = body
+        .get(..position)
+        .map_or(0, |before| before.chars().count());
+    format!("{}\n{}^", body, " ".repeat(column))
+}
+
+fn make_text(body: &str, err: &CommandError, specs: &[Spec]) -> String {
+    match (
+        &err.error,
+        err.command.as_deref().and_then(|name| find(specs, name)),
+    ) {
+        (ParseCommandError::NotFound, _) => match suggest(body, specs) {
+            Some(name) => format!("Oops! {}, did you mean !{}?", err, name),
+            None => format!("Oops! {}, try !help", err),
+        },
+        (_, Some(spec)) => match err.position {
+            Some(position) => format!(
+                "Oops! {}\n{}\nUsage: {}",
+                err,
+                marked(body, position),
+                spec.usage
+            ),
+            None => format!("Oops! {}\nUsage: {}", err, spec.usage),
+        },
+        (_, None) => format!("Oops! {}, try !help", err),
+    }
+}
+
+fn make_html(body: &str, err: &CommandError, specs: &[Spec]) -> String {
+    let elements: Vec<Element> = match (
+        &err.error,
+        err.command.as_deref().and_then(|name| find(specs, name)),
+    ) {
+        (ParseCommandError::NotFound, _) => match suggest(body, specs) {
+            Some(name) => vec![paragraph(format!(
+                "Oops! {}, did you mean <strong>!{}</strong>?",
+                err, name
+            ))],
+            None => vec![paragraph(format!(
+                "Oops! {}, try <strong>!help</strong>",
+                err
+            ))],
+        },
+        (_, Some(spec)) => {
+            let mut elements = vec![paragraph(format!("Oops! {}", err))];
+            if let Some(position) = err.position {
+                elements.push(pre(escape(marked(body, position))));
+            }
+            elements.push(paragraph("Usage:"));
+            elements.push(usage_html(spec.usage));
+            elements
+        }
+        (_, None) => vec![paragraph(format!(
+            "Oops! {}, try <strong>!help</strong>",
+            err
+        ))],
+    };
+    div(elements).as_string()
 }
 
-fn make_html(err: &ParseCommandError) -> String {
-    div(format!("Oops! {}", err)).as_string()
+pub fn parse_error(body: &str, err: &CommandError, specs: &[Spec]) -> Option<(String, String)> {
+    Some((make_text(body, err, specs), make_html(body, err, specs)))
 }
 
-pub fn parse_error(err: &ParseCommandError) -> Option<(String, String)> {
-    Some((make_text(err), make_html(err)))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::registry;
+
+    #[test]
+    fn suggests_the_closest_command() {
+        let specs = registry();
+        assert_eq!(suggest("!dne ac/bot dev 1h", &specs), Some("done"));
+        assert_eq!(suggest("!swich ac/bot review", &specs), Some("switch"));
+        assert_eq!(suggest("!frobnicate", &specs), None);
+    }
+
+    #[test]
+    fn marks_the_offending_argument() {
+        assert_eq!(
+            marked("!deadline ac/bot 31/31", 17),
+            "!deadline ac/bot 31/31\n                 ^"
+        );
+    }
 }
@@ -0,0 +1,19 @@
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "ping",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::ping,
+        usage: "!ping",
+        help: "check if the bot's still alive",
+        examples: &["!ping"],
+        handler: |_context, _user, command| match command {
+            Command::Ping => Some(("pong".into(), String::new())),
+            _ => None,
+        },
+    }
+}
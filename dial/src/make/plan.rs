@@ -0,0 +1,86 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, th, tr, Element};
+use shell::expr::{self, Command};
+use shell::plan::{plan_all, projections, room_strategy};
+use shell::store::{ConnectedStore, Role, StoreResult};
+use shell::util::{display_username, make_table_row};
+use std::time;
+
+pub fn date(dt: Option<chrono::DateTime<chrono::Local>>) -> String {
+    dt.map_or(String::from("-"), |dt| dt.format("%F").to_string())
+}
+
+fn report(store: &ConnectedStore) -> StoreResult<(String, String)> {
+    let strategy = room_strategy(store)?;
+    let plan = plan_all(
+        &store.select_all_project_info()?,
+        &store.select_intent_all()?,
+        &store.select_avail_all()?,
+        &store.select_absence_all()?,
+        &store.select_done_for_intents()?,
+        time::SystemTime::now(),
+        strategy.as_ref(),
+    );
+    let rows = projections(&plan)
+        .into_iter()
+        .map(|(username, projection)| {
+            vec![
+                projection.project.clone(),
+                display_username(username),
+                date(projection.finish),
+                date(projection.deadline),
+                match projection.late() {
+                    true => format!("⚠ {}", projection.outlook()),
+                    false => projection.outlook(),
+                },
+            ]
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return Ok((
+            String::from("Nothing planned, set an !intent and an !avail first."),
+            String::new(),
+        ));
+    }
+    let title = format!("Planned with the {} strategy:", strategy.name());
+    let text = std::iter::once(title.clone())
+        .chain(rows.iter().map(|cells| cells.join("\t")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let header = tr(["project", "user", "finish", "deadline", "outlook"]
+        .iter()
+        .map(|name| th(*name))
+        .collect::<Vec<_>>());
+    let body = rows.into_iter().map(make_table_row);
+    let html = table(
+        std::iter::once(header)
+            .chain(body)
+            .collect::<Vec<Element>>(),
+    );
+    Ok((text, format!("<p>{}</p>{}", title, html.as_string())))
+}
+
+pub fn plan(handler: &mut bot::Context) -> Option<(String, String)> {
+    match report(handler.store) {
+        Ok(reply) => Some(reply),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "plan",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::plan,
+        usage: "!plan",
+        help:
+            "when each intent is projected to be done, and whether it makes its project's deadline",
+        examples: &["!plan"],
+        handler: |context, _user, command| match command {
+            Command::Plan => plan(context),
+            _ => None,
+        },
+    }
+}
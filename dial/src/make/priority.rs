@@ -0,0 +1,41 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+
+use super::common::select_project;
+
+pub fn priority(handler: &mut bot::Context, project: String, n: i64) -> Option<(String, String)> {
+    match select_project(handler, &project) {
+        Err(candidates) => Some((
+            candidates.as_text("Or if it's a new project, you must !new it first."),
+            candidates.as_html("Or if it's a new project, you must !new it first."),
+        )),
+        Ok(_) => match handler.store.update_priority(project.clone(), n) {
+            Err(_err) => Some((
+                "Sorry, Err'd while saving to DB".into(),
+                "Sorry, Err'd while saving to DB".into(),
+            )),
+            Ok(_) => Some((
+                format!("Priority of {} set to {}", project, n),
+                String::new(),
+            )),
+        },
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "priority",
+        role: Role::Admin,
+        confirm: Policy::Overwrite,
+        grammar: expr::priority,
+        usage: "!priority <project-name> <n>",
+        help: "set the priority of a project, higher first, used by the priority and proportional strategies",
+        examples: &["!priority ac/bot 3"],
+        handler: |context, _user, command| match command {
+            Command::Priority(project, n) => priority(context, project, n),
+            _ => None,
+        },
+    }
+}
@@ -1,4 +1,7 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
 use super::common::select_project;
@@ -22,3 +25,19 @@ pub fn provision(
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "provision",
+        role: Role::Admin,
+        confirm: Policy::Overwrite,
+        grammar: expr::provision,
+        usage: "!provision <project-name> <duration>",
+        help: "set provisioned time for an existing project",
+        examples: &["!provision ac/bot 120h"],
+        handler: |context, _user, command| match command {
+            Command::Provision(project, d) => provision(context, project, d),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,53 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::display_username;
+
+use super::common::is_last_admin;
+
+pub fn revoke(handler: &mut bot::Context, user: String) -> Option<(String, String)> {
+    if is_last_admin(handler, &user) {
+        return Some((
+            format!(
+                "{} is the last admin of this room, grant admin to someone else first.",
+                display_username(&user)
+            ),
+            String::new(),
+        ));
+    }
+    match handler.store.delete_role(user.clone()) {
+        Ok(0) => Some((
+            format!(
+                "{} had no role granted in this room.",
+                display_username(&user)
+            ),
+            String::new(),
+        )),
+        Ok(_) => Some((
+            format!(
+                "{} is back to {} in this room.",
+                display_username(&user),
+                Role::Member
+            ),
+            String::new(),
+        )),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "revoke",
+        role: Role::Admin,
+        confirm: Policy::Never,
+        grammar: expr::revoke,
+        usage: "!revoke <@user>",
+        help: "take back the role granted to a user, who is then a member",
+        examples: &["!revoke @bob:matrix.org"],
+        handler: |context, _user, command| match command {
+            Command::Revoke(user) => revoke(context, user),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,43 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+
+use super::common::{on_behalf, whom};
+
+pub fn remove_avail(
+    handler: &mut bot::Context,
+    user: String,
+    target: Option<String>,
+    id: i64,
+) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
+    match handler.store.delete_avail(&user, id) {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(0) => Some((
+            format!(
+                "There's no availability #{} for {}, sorry.",
+                id,
+                whom(&user, &entered_by)
+            ),
+            String::new(),
+        )),
+        Ok(_) => Some((format!("Removed availability #{}", id), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "rmavail",
+        role: Role::Member,
+        confirm: Policy::Overwrite,
+        grammar: expr::remove_avail,
+        usage: "!rmavail <@user?> <#id>",
+        help: "remove one of your availability periods, numbered as !avails shows them; admins can do it for @user",
+        examples: &["!rmavail #3", "!rmavail @alice:matrix.org #3"],
+        handler: |context, user, command| match command {
+            Command::RemoveAvail(target, id) => remove_avail(context, user, target, id),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,44 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+
+use super::common::{on_behalf, whom};
+
+pub fn remove_intent(
+    handler: &mut bot::Context,
+    user: String,
+    target: Option<String>,
+    project: String,
+) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
+    let by = entered_by.clone().unwrap_or_else(|| user.clone());
+    match handler.store.delete_intent(&user, &project, &by) {
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+        Ok(0) => Some((
+            format!(
+                "There's no intent on {} for {}, sorry.",
+                project,
+                whom(&user, &entered_by)
+            ),
+            String::new(),
+        )),
+        Ok(_) => Some((format!("Removed intent on {}", project), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "rmintent",
+        role: Role::Member,
+        confirm: Policy::Overwrite,
+        grammar: expr::remove_intent,
+        usage: "!rmintent <@user?> <project-name>",
+        help: "remove your intent on a project, as if it was never set; admins can do it for @user",
+        examples: &["!rmintent ac/bot", "!rmintent @alice:matrix.org ac/bot"],
+        handler: |context, user, command| match command {
+            Command::RemoveIntent(target, project) => remove_intent(context, user, target, project),
+            _ => None,
+        },
+    }
+}
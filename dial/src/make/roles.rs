@@ -0,0 +1,50 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, Element};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::{display_username, make_table_row};
+
+pub fn roles(handler: &mut bot::Context) -> Option<(String, String)> {
+    match handler.store.select_role_all() {
+        Ok(records) if !records.iter().any(|r| r.role == Role::Admin) => Some((
+            String::from(
+                "This room has no admin yet, everyone can do everything. Use !grant to name one.",
+            ),
+            String::new(),
+        )),
+        Ok(records) => {
+            let left: Vec<String> = records
+                .iter()
+                .map(|rec| format!("{}\t{}", display_username(&rec.username), rec.role))
+                .collect();
+            let rows: Vec<Element> = records
+                .iter()
+                .map(|rec| {
+                    make_table_row(vec![display_username(&rec.username), rec.role.to_string()])
+                })
+                .collect();
+            Some((
+                format!("{}\nEveryone else is {}.", left.join("\n"), Role::Member),
+                table(rows).as_string(),
+            ))
+        }
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "roles",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::roles,
+        usage: "!roles",
+        help: "list the roles granted in this room",
+        examples: &["!roles"],
+        handler: |context, _user, command| match command {
+            Command::Roles => roles(context),
+            _ => None,
+        },
+    }
+}
@@ -1,23 +1,50 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
 use html::{table, Element};
-use shell::util::{human_duration, make_table_row};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::{display_username, human_duration, make_table_row};
 use std::time;
 
+fn usernames(names: &[String]) -> Option<String> {
+    match names.is_empty() {
+        true => None,
+        false => Some(
+            names
+                .iter()
+                .map(display_username)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    }
+}
+
 pub fn since(
     handler: &mut bot::Context,
     user: String,
+    target: Option<String>,
     since: time::SystemTime,
+    tag: Option<String>,
 ) -> Option<(String, String)> {
-    match handler.store.select_user(user, since) {
+    match handler
+        .store
+        .select_user(target.unwrap_or(user), since, tag)
+    {
         Ok(results) => {
             let left: Vec<String> = results
                 .iter()
                 .map(|rec| {
                     format!(
-                        "{}\t{}\t{}",
+                        "{}\t{}\t{}{}{}",
                         rec.project,
                         rec.task,
-                        human_duration(rec.duration)
+                        human_duration(rec.duration),
+                        usernames(&rec.with)
+                            .map(|names| format!("\twith {}", names))
+                            .unwrap_or_default(),
+                        usernames(&rec.entered_by)
+                            .map(|names| format!("\t(entered by {})", names))
+                            .unwrap_or_default()
                     )
                 })
                 .collect();
@@ -28,6 +55,8 @@ pub fn since(
                         rec.project.clone(),
                         rec.task.clone(),
                         human_duration(rec.duration),
+                        usernames(&rec.with).unwrap_or_default(),
+                        usernames(&rec.entered_by).unwrap_or_default(),
                     ])
                 })
                 .collect();
@@ -37,3 +66,27 @@ pub fn since(
         Err(_) => None,
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "since",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::since,
+        usage: "!since <@user?> <date-or-duration> <#tag?>",
+        help:
+            "a summary of your tasks, or those of @user, since date, only those with #tag if given",
+        examples: &[
+            "!since 2024-01-01",
+            "!since 1week",
+            "!since 1week #billable",
+            "!since @alice:matrix.org 1week",
+        ],
+        handler: |context, user, command| match command {
+            Command::Since(target, since_time, tag) => {
+                since(context, user, target, since_time, tag)
+            }
+            _ => None,
+        },
+    }
+}
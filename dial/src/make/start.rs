@@ -1,42 +1,95 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{ConnectedStore, Role, StoreResult};
 use std::time;
 
-use super::common::{check_meta, select_project};
+use super::common::{busy, check_meta, on_behalf, participants, select_project, with_whom};
 
+/// Starts the task for all of `users`, unless one of them is still doing
+/// something.
+fn record(
+    store: &mut ConnectedStore,
+    users: &[(String, Option<String>)],
+    duration: time::Duration,
+    project_name: String,
+    task: String,
+    tags: &[String],
+) -> StoreResult<String> {
+    if let Some(busy) = busy(store, users) {
+        return Ok(format!(
+            "{}, you should stop it first with !stop or use !switch",
+            busy
+        ));
+    }
+    let start = time::SystemTime::now();
+    match users {
+        [(user, entered_by)] => store.insert_do(
+            user.clone(),
+            start,
+            start + duration,
+            project_name,
+            task,
+            tags,
+            entered_by.clone(),
+        ),
+        _ => store.insert_do_group(users, start, start + duration, project_name, task, tags),
+    }
+    .map(|_| format!("doing OK{}", with_whom(users)))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn start(
     handler: &mut bot::Context,
     user: String,
+    target: Option<String>,
     duration: time::Duration,
     project_name: String,
     task: String,
+    with: Vec<String>,
+    tags: Vec<String>,
 ) -> Option<(String, String)> {
-    let pendings = handler.store.select_current_task().unwrap_or_default();
-    match pendings.iter().find(|rec| rec.username == user) {
-        Some(rec) => Some((
-            format!(
-                "You are already doing {}, you should stop it first with !stop or use !switch",
-                rec.task
-            ),
-            String::new(),
+    let (user, entered_by) = on_behalf(user, target);
+    let users = participants(&user, &entered_by, &with);
+    match select_project(handler, &project_name) {
+        Err(candidates) => Some((
+            candidates.as_text("Or if it's a new project, you can !new it first."),
+            candidates.as_html("Or if it's a new project, you can !new it first."),
         )),
-        None => match select_project(handler, &project_name) {
-            Err(candidates) => Some((
-                candidates.as_text("Or if it's a new project, you can !new it first."),
-                candidates.as_html("Or if it's a new project, you can !new it first."),
-            )),
-            Ok(project) => match check_meta(handler, &project) {
-                Some(r) => Some(r),
-                None => {
-                    let start = time::SystemTime::now();
-                    match handler
-                        .store
-                        .insert_do(user, start, start + duration, project_name, task)
-                    {
-                        Ok(_) => Some(("doing OK".into(), String::new())),
-                        Err(err) => Some((format!("Error: {}", err), String::new())),
-                    }
-                }
+        Ok(project) => match check_meta(handler, &project) {
+            Some(r) => Some(r),
+            None => match handler
+                .store
+                .atomic(|store| record(store, &users, duration, project_name, task, &tags))
+            {
+                Ok(message) => Some((message, String::new())),
+                Err(err) => Some((format!("Error: {}", err), String::new())),
             },
         },
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "do",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::start,
+        usage: "!do <@user?> <project-name> <task-name> <duration> <with @user...?> <#tag...?>",
+        help:
+            "start a new task that will last for <duration>, quote the task name if it has spaces and add #tags to sort your work, list who shares it after with; admins can start one for @user",
+        examples: &[
+            "!do ac/bot dev 2h",
+            "!do ac/bot \"client meeting\" 1h",
+            "!do ac/bot support 30m #billable",
+            "!do ac/bot meeting 1h with @bob:matrix.org",
+            "!do @alice:matrix.org ac/bot dev 1h",
+        ],
+        handler: |context, user, command| match command {
+            Command::Do(target, project, task, duration, with, tags) => {
+                start(context, user, target, duration, project, task, with, tags)
+            }
+            _ => None,
+        },
+    }
+}
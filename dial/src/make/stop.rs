@@ -1,10 +1,20 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
-pub fn stop(handler: &mut bot::Context, user: String) -> Option<(String, String)> {
+use super::common::{on_behalf, whom};
+
+pub fn stop(
+    handler: &mut bot::Context,
+    user: String,
+    target: Option<String>,
+) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
     let pendings = handler
         .store
-        .select_current_task_for(user).unwrap_or_default();
+        .select_current_task_for(user.clone()).unwrap_or_default();
     let pending = pendings.first();
     match pending {
         Some(rec) => match handler
@@ -18,8 +28,24 @@ pub fn stop(handler: &mut bot::Context, user: String) -> Option<(String, String)
             )),
         },
         None => Some((
-            String::from("Ther's nothing to !stop for you"),
+            format!("Ther's nothing to !stop for {}", whom(&user, &entered_by)),
             String::new(),
         )),
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "stop",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::stop,
+        usage: "!stop <@user?>",
+        help: "stop your current task, or the one of @user for admins",
+        examples: &["!stop", "!stop @alice:matrix.org"],
+        handler: |context, user, command| match command {
+            Command::Stop(target) => stop(context, user, target),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,30 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+
+pub fn strategy(handler: &mut bot::Context, name: String) -> Option<(String, String)> {
+    match handler.store.insert_setting("strategy", &name) {
+        Ok(_) => Some((
+            format!("From now on, the !plan uses the {} strategy.", name),
+            String::new(),
+        )),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "strategy",
+        role: Role::Admin,
+        confirm: Policy::Never,
+        grammar: expr::strategy,
+        usage: "!strategy deadline | priority | proportional",
+        help: "choose how the !plan shares the time of each user: earliest deadline first (default), highest !priority first, or every week in proportion to priorities",
+        examples: &["!strategy deadline", "!strategy priority", "!strategy proportional"],
+        handler: |context, _user, command| match command {
+            Command::Strategy(name) => strategy(context, name),
+            _ => None,
+        },
+    }
+}
@@ -1,35 +1,78 @@
 use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
 use std::time;
 
-use super::common::select_project;
+use super::common::{on_behalf, select_project};
 
 pub fn switch(
     handler: &mut bot::Context,
     user: String,
+    target: Option<String>,
     project: String,
     task: String,
+    tags: Vec<String>,
 ) -> Option<(String, String)> {
+    let (user, entered_by) = on_behalf(user, target);
     let now = time::SystemTime::now();
     let pendings = handler
         .store
-        .select_current_task_for(user.clone()).unwrap_or_default();
+        .select_current_task_for(user.clone())
+        .unwrap_or_default();
 
     match pendings.first() {
         Some(rec) => match select_project(handler, &project) {
             Err(candidates) => Some((candidates.as_text(""), candidates.as_html(""))),
-            Ok(_) =>  match handler
-            .store
-            .update_task_end(rec.id, now)
-            .and_then(|_| {
-                handler.store.insert_do(user, now, rec.end_time, project, task.clone())
-            }) {
-            Err(err) => Some((format!("Error: {}", err), String::new())),
-            Ok(_) => Some((format!("Good {}ing!", task), String::new())),
-        }
-    },
+            Ok(_) => {
+                // the new task keeps the tags of the current one unless
+                // others are given
+                let tags = match tags.is_empty() {
+                    true => handler.store.select_tags_for_do(rec.id).unwrap_or_default(),
+                    false => tags,
+                };
+                match handler.store.atomic(|store| {
+                    store.update_task_end(rec.id, now)?;
+                    store.insert_do(
+                        user,
+                        now,
+                        rec.end_time,
+                        project,
+                        task.clone(),
+                        &tags,
+                        entered_by,
+                    )
+                }) {
+                    Err(err) => Some((format!("Error: {}", err), String::new())),
+                    Ok(_) => Some((format!("Good {}ing!", task), String::new())),
+                }
+            }
+        },
         None => Some((
             String::from("There's nothing to !switch from, you might want to !do."),
             String::from("There's nothing to <strong>!switch</strong> from, you might want to <strong>!do<strong>."),
         )),
     }
 }
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "switch",
+        role: Role::Member,
+        confirm: Policy::Never,
+        grammar: expr::switch,
+        usage: "!switch <@user?> <project-name> <task-name> [#<tag> ...]",
+        help: "stop your current task and create a new one that has the same end time and tags as the current one, or the tags given; admins can do it for @user",
+        examples: &[
+            "!switch ac/bot review",
+            "!switch @alice:matrix.org ac/bot review",
+            "!switch ac/bot support #billable",
+        ],
+        handler: |context, user, command| match command {
+            Command::Switch(target, project, task, tags) => {
+                switch(context, user, target, project, task, tags)
+            }
+            _ => None,
+        },
+    }
+}
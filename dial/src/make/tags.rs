@@ -0,0 +1,50 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, Element};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::{human_duration, make_table_row};
+use std::time;
+
+pub fn tags(handler: &mut bot::Context, since: time::SystemTime) -> Option<(String, String)> {
+    match handler
+        .store
+        .select_tag_hours(since, time::SystemTime::now())
+    {
+        Ok(records) if records.is_empty() => Some((
+            String::from("No tagged work over this period."),
+            String::new(),
+        )),
+        Ok(records) => {
+            let left: Vec<String> = records
+                .iter()
+                .map(|rec| format!("#{}\t{}", rec.name, human_duration(rec.duration)))
+                .collect();
+            let rows: Vec<Element> = records
+                .iter()
+                .map(|rec| {
+                    make_table_row(vec![format!("#{}", rec.name), human_duration(rec.duration)])
+                })
+                .collect();
+
+            Some((left.join("\n"), table(rows).as_string()))
+        }
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "tags",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::tags_report,
+        usage: "!tags <date-or-duration>",
+        help: "hours spent per tag in this room since date",
+        examples: &["!tags 2024-01-01", "!tags 4weeks"],
+        handler: |context, _user, command| match command {
+            Command::Tags(since) => tags(context, since),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,68 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::{Role, TaskRecord};
+use shell::util::display_username;
+
+/// "you, bob and carol", those a change to a group of records touches.
+pub fn whose(user: &str, records: &[TaskRecord]) -> String {
+    let names: Vec<String> = records
+        .iter()
+        .map(|rec| match rec.username == user {
+            true => String::from("you"),
+            false => display_username(&rec.username),
+        })
+        .collect();
+    match names.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+pub fn undo(handler: &mut bot::Context, user: String) -> Option<(String, String)> {
+    let latest = handler
+        .store
+        .select_latest_task_for(user.clone())
+        .unwrap_or_default();
+    match latest.first() {
+        None => Some(("There's nothing to !undo, sorry.".into(), String::new())),
+        Some(rec) => {
+            let removed = handler.store.atomic(|store| {
+                let group = store.select_group(rec.id)?;
+                group
+                    .iter()
+                    .try_for_each(|r| store.delete_do(r.id).map(|_| ()))
+                    .map(|_| group)
+            });
+            match removed {
+                Err(err) => Some((format!("Error: {}", err), String::new())),
+                Ok(group) => Some((
+                    format!(
+                        "Removed {} {} for {}.",
+                        rec.project,
+                        rec.task,
+                        whose(&user, &group)
+                    ),
+                    String::new(),
+                )),
+            }
+        }
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "undo",
+        role: Role::Member,
+        confirm: Policy::Always,
+        grammar: expr::undo,
+        usage: "!undo",
+        help: "remove your latest task, for everyone it was shared with too",
+        examples: &["!undo"],
+        handler: |context, user, command| match command {
+            Command::Undo => undo(context, user),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,83 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use html::{table, th, tr, Element};
+use shell::expr::{self, Command};
+use shell::plan::{room_strategy, what_if, Baseline, Hypothesis};
+use shell::store::{ConnectedStore, Role, StoreResult};
+use shell::util::{display_username, make_table_row};
+use std::time;
+
+use super::plan::date;
+
+fn report(store: &ConnectedStore, hypotheses: &[Hypothesis]) -> StoreResult<(String, String)> {
+    let strategy = room_strategy(store)?;
+    let changes = what_if(
+        &Baseline::load(store)?,
+        hypotheses,
+        time::SystemTime::now(),
+        strategy.as_ref(),
+    );
+    if changes.is_empty() {
+        return Ok((String::from("Nothing moves."), String::new()));
+    }
+    let rows = changes
+        .iter()
+        .map(|change| {
+            vec![
+                change.project.clone(),
+                display_username(&change.username),
+                change.before.map_or(String::from("-"), date),
+                date(change.after),
+                date(change.deadline),
+                match change.late() {
+                    true => format!("⚠ {}", change.outlook()),
+                    false => change.outlook(),
+                },
+            ]
+        })
+        .collect::<Vec<_>>();
+    let title = String::from("What moves:");
+    let text = std::iter::once(title.clone())
+        .chain(rows.iter().map(|cells| cells.join("\t")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let header = tr(
+        ["project", "user", "before", "after", "deadline", "outlook"]
+            .iter()
+            .map(|name| th(*name))
+            .collect::<Vec<_>>(),
+    );
+    let body = rows.into_iter().map(make_table_row);
+    let html = table(
+        std::iter::once(header)
+            .chain(body)
+            .collect::<Vec<Element>>(),
+    );
+    Ok((text, format!("<p>{}</p>{}", title, html.as_string())))
+}
+
+pub fn whatif(handler: &mut bot::Context, hypotheses: Vec<Hypothesis>) -> Option<(String, String)> {
+    match report(handler.store, &hypotheses) {
+        Ok(reply) => Some(reply),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "whatif",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::what_if,
+        usage: "!whatif <user> <duration> on <project-name> [by <date>] | <user> avail <start> <end> <duration> [and ...]",
+        help: "simulate the !plan with hypothetical intents or availability, and list the finish dates that move; nothing is saved",
+        examples: &[
+            "!whatif @alice:matrix.org 80h on ac/newsite by 2027-03-01",
+            "!whatif @alice:matrix.org 80h on ac/newsite and @bob:matrix.org avail 2027-01-01 2027-03-01 mon=8h tue=8h",
+        ],
+        handler: |context, _user, command| match command {
+            Command::WhatIf(hypotheses) => whatif(context, hypotheses),
+            _ => None,
+        },
+    }
+}
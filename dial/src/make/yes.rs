@@ -0,0 +1,48 @@
+use crate::bot;
+use crate::registry::{Policy, Spec};
+use shell::expr::{self, Command};
+use shell::store::Role;
+use shell::util::human_duration;
+
+use super::confirm::WINDOW;
+
+pub fn yes(context: &mut bot::Context, user: String) -> Option<(String, String)> {
+    match context.store.select_pending_for_user(&user) {
+        Ok(Some(pending)) => {
+            if let Err(err) = context.store.delete_pending(user.clone()) {
+                return Some((format!("Error: {}", err), String::new()));
+            }
+            match pending.created.elapsed().map(|age| age < WINDOW) {
+                Ok(true) => bot::exec_command(context, user, pending.body, true),
+                _ => Some((
+                    format!(
+                        "That was more than {} ago, send the command again.",
+                        human_duration(WINDOW)
+                    ),
+                    String::new(),
+                )),
+            }
+        }
+        Ok(None) => Some((
+            "There's nothing waiting for your !yes.".into(),
+            String::new(),
+        )),
+        Err(err) => Some((format!("Error: {}", err), String::new())),
+    }
+}
+
+pub fn spec<'a>() -> Spec<'a> {
+    Spec {
+        name: "yes",
+        role: Role::Viewer,
+        confirm: Policy::Never,
+        grammar: expr::yes,
+        usage: "!yes",
+        help: "go ahead with the command the bot asked you to confirm",
+        examples: &["!yes"],
+        handler: |context, user, command| match command {
+            Command::Yes => yes(context, user),
+            _ => None,
+        },
+    }
+}
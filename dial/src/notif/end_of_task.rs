@@ -1,14 +1,16 @@
+use crate::transport::Transport;
 use crossbeam_channel::tick;
-use matrix_bot_api::{ActiveBot, MessageType};
-use shell::store::{ConnectedStore, Store};
-use shell::util::human_duration;
+use shell::metrics;
+use shell::store::{ConnectedStore, EndPolicy, Store, TaskRecord};
+use shell::util::{display_username, human_duration};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time;
+use tracing::{error, info_span};
 
-fn notify(
+fn notify<T: Transport>(
     connected: &mut ConnectedStore,
-    bot: &ActiveBot,
+    bot: &T,
     task_id: i64,
     end: time::SystemTime,
     user: &str,
@@ -19,50 +21,96 @@ fn notify(
         .unwrap_or_else(|_| time::Duration::from_secs(0));
     let success_message = format!(
         "{}: Your current task will end in {}
-        You can !more <duration> to continue",
+        Reply `!more 30m` (or any other duration) to continue",
         user,
         human_duration(d)
     );
     let error_message = format!(
         "{}: Your current task will end in {}
-        You can !more <duration> to continue.
+        Reply `!more 30m` (or any other duration) to continue.
         Besides, note that we failed to record this notification, 
         it might come back again, sorry for the inconvenience",
         user,
         human_duration(d)
     );
 
+    metrics::incr(metrics::NOTIFICATIONS, &[("kind", "ask")]);
     match connected.insert_notification(task_id, end) {
         Ok(_) => {
-            bot.send_message(
-                &success_message,
-                &connected.room_id(),
-                MessageType::TextMessage,
-            );
+            bot.send_text(&connected.room_id(), &success_message);
         }
         Err(_) => {
-            bot.send_message(
-                &error_message,
+            bot.send_text(&connected.room_id(), &error_message);
+        }
+    }
+}
+
+fn extend<T: Transport>(
+    connected: &mut ConnectedStore,
+    bot: &T,
+    rec: &TaskRecord,
+    amount: time::Duration,
+) {
+    let new_end = rec.end_time + amount;
+    match connected
+        .insert_notification(rec.id, rec.end_time)
+        .and_then(|_| connected.update_task_end(rec.id, new_end))
+    {
+        Ok(_) => {
+            metrics::incr(metrics::NOTIFICATIONS, &[("kind", "extend")]);
+            bot.send_notice(
                 &connected.room_id(),
-                MessageType::TextMessage,
-            );
+                &format!(
+                    "{}: {} on {} has been extended by {}, !stop when you're done",
+                    display_username(&rec.username),
+                    rec.task,
+                    rec.project,
+                    human_duration(amount)
+                ),
+            )
         }
+        Err(_) => error!("Failed to extend task {}", rec.id),
+    }
+}
+
+fn silent(connected: &mut ConnectedStore, rec: &TaskRecord) {
+    if connected.insert_notification(rec.id, rec.end_time).is_err() {
+        error!("Failed to record notification for task {}", rec.id);
     }
 }
 
-pub fn end_of_task(bot: ActiveBot, store: Arc<Mutex<Store>>) {
+pub fn end_of_task<T: Transport>(bot: T, store: Arc<Mutex<Store>>) {
     thread::spawn(move || {
         for _ in tick(time::Duration::from_millis(2_600)).iter() {
             if let Ok(mut store) = store.lock() {
                 store
                     .iter_mut()
-                    .map(|connected| match connected.select_ending_tasks() {
-                        Ok(recs) => {
-                            for rec in recs.into_iter() {
-                                notify(connected, &bot, rec.id, rec.end_time, &rec.username);
+                    .map(|connected| {
+                        let span = info_span!("room", room = %connected.room_id());
+                        let _entered = span.enter();
+                        match connected.select_ending_tasks() {
+                            Ok(recs) => {
+                                for rec in recs.into_iter() {
+                                    match connected
+                                        .select_end_policy_for_user(rec.username.clone())
+                                        .unwrap_or_default()
+                                    {
+                                        EndPolicy::Extend(amount) => {
+                                            extend(connected, &bot, &rec, amount)
+                                        }
+                                        EndPolicy::Silent => silent(connected, &rec),
+                                        EndPolicy::Ask => notify(
+                                            connected,
+                                            &bot,
+                                            rec.id,
+                                            rec.end_time,
+                                            &rec.username,
+                                        ),
+                                    }
+                                }
                             }
+                            Err(_) => error!("Failed to select ending tasks"),
                         }
-                        Err(_) => println!("notifications Error"),
                     })
                     .for_each(drop);
             }
@@ -0,0 +1,104 @@
+use crate::bot;
+use crate::make;
+use serde::Deserialize;
+use shell::expr::{Command, Grammar};
+use shell::store::Role;
+
+/// Runs a parsed command, `user` being the sender of the message.
+pub type Handler = fn(&mut bot::Context, String, Command) -> Option<(String, String)>;
+
+/// When to ask the sender to confirm a command with `!yes` before running it.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    Always,
+    /// Only when it would overwrite or remove something.
+    Overwrite,
+    Never,
+}
+
+/// Everything the bot knows about a command: how to parse it, how to
+/// document it and what to do with it.
+pub struct Spec<'a> {
+    pub name: &'static str,
+    /// The least role a user needs in the room to run it.
+    pub role: Role,
+    /// Unless configured otherwise, see `Config::confirm`.
+    pub confirm: Policy,
+    pub grammar: Grammar<'a>,
+    pub usage: &'static str,
+    pub help: &'static str,
+    pub examples: &'static [&'static str],
+    pub handler: Handler,
+}
+
+/// All commands, in the order their grammars are tried.
+pub fn registry<'a>() -> Vec<Spec<'a>> {
+    vec![
+        make::ping::spec(),
+        make::help::spec(),
+        make::new::spec(),
+        make::start::spec(),
+        make::done::spec(),
+        make::stop::spec(),
+        make::list::spec(),
+        make::digest::spec(),
+        make::since::spec(),
+        make::tags::spec(),
+        make::more::spec(),
+        make::switch::spec(),
+        make::deadline::spec(),
+        make::provision::spec(),
+        make::complete::spec(),
+        make::note::spec(),
+        make::meta::spec(),
+        make::parent::spec(),
+        make::avail::spec(),
+        make::avails::spec(),
+        make::endavail::spec(),
+        make::rmavail::spec(),
+        make::intent::spec(),
+        make::intents::spec(),
+        make::endintent::spec(),
+        make::rmintent::spec(),
+        make::intentlog::spec(),
+        make::off::spec(),
+        make::holidays::spec(),
+        make::plan::spec(),
+        make::priority::spec(),
+        make::strategy::spec(),
+        make::whatif::spec(),
+        make::feed::spec(),
+        make::on_end::spec(),
+        make::grant::spec(),
+        make::revoke::spec(),
+        make::roles::spec(),
+        make::undo::spec(),
+        make::amend::spec(),
+        make::yes::spec(),
+    ]
+}
+
+pub fn find<'a, 'b>(specs: &'b [Spec<'a>], name: &str) -> Option<&'b Spec<'a>> {
+    specs.iter().find(|spec| spec.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shell::expr::parse_command;
+
+    #[test]
+    fn examples_parse_with_their_own_grammar() {
+        let specs = registry();
+        let grammars: Vec<_> = specs.iter().map(|spec| spec.grammar).collect();
+        for (index, spec) in specs.iter().enumerate() {
+            for example in spec.examples {
+                match parse_command(example, &grammars) {
+                    Ok((found, _)) => assert_eq!(found, index, "{}", example),
+                    Err(err) => panic!("{}: {}", example, err),
+                }
+            }
+        }
+    }
+}
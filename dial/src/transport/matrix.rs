@@ -0,0 +1,119 @@
+use crate::bot::CommandHandler;
+use crate::config::{BaseUrl, Policies};
+use crate::notif::end_of_task;
+use crate::transport::{Event, Incoming, Relation, Transport};
+use crossbeam_channel::{unbounded, Receiver};
+use matrix_bot_api::handlers::{self, HandleResult, Message, MessageHandler};
+use matrix_bot_api::{ActiveBot, MatrixBot, MessageType, Room};
+use shell::store::Store;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Clone)]
+pub struct MatrixTransport(ActiveBot);
+
+impl Transport for MatrixTransport {
+    fn send_text(&self, room: &str, text: &str) {
+        self.0.send_message(text, room, MessageType::TextMessage)
+    }
+
+    fn send_notice(&self, room: &str, text: &str) {
+        self.0.send_message(text, room, MessageType::RoomNotice)
+    }
+
+    fn send_html(&self, room: &str, text: &str, html: &str) {
+        self.0
+            .send_html_message(text, html, room, MessageType::RoomNotice)
+    }
+}
+
+struct MatrixAdapter(CommandHandler);
+
+impl MessageHandler for MatrixAdapter {
+    fn handle_join(&mut self, bot: &ActiveBot, room: &Room) -> HandleResult {
+        self.0
+            .handle_event(&MatrixTransport(bot.clone()), Event::Join(room.id.clone()));
+        HandleResult::StopHandling
+    }
+
+    fn handle_leave(&mut self, bot: &ActiveBot, room: &Room) -> HandleResult {
+        self.0
+            .handle_event(&MatrixTransport(bot.clone()), Event::Leave(room.id.clone()));
+        HandleResult::StopHandling
+    }
+
+    fn handle_message(&mut self, bot: &ActiveBot, message: &Message) -> HandleResult {
+        let (body, relation) = match handlers::relation(message) {
+            Some(handlers::Relation::Edit { event_id, body }) => {
+                (body, Some(Relation::Edit(event_id)))
+            }
+            Some(handlers::Relation::Reply { sender, body, .. })
+                if sender.is_some() && sender.as_deref() == bot.uid() =>
+            {
+                (body, Some(Relation::ReplyToBot))
+            }
+            Some(handlers::Relation::Reply { body, .. }) => (body, None),
+            None => (message.body.clone(), None),
+        };
+        let incoming = Incoming {
+            id: message.id.clone(),
+            room: message.room.clone(),
+            sender: message.sender.clone(),
+            body,
+            relation,
+        };
+        self.0
+            .handle_event(&MatrixTransport(bot.clone()), Event::Message(incoming));
+        HandleResult::ContinueHandling
+    }
+
+    fn handle_sync(&mut self, bot: &ActiveBot, since: &str) {
+        self.0
+            .handle_event(&MatrixTransport(bot.clone()), Event::Sync(since.into()));
+    }
+}
+
+pub fn start_matrix(
+    path: &Path,
+    homeserver: &str,
+    user: &str,
+    password: &str,
+    base_url: BaseUrl,
+    policies: Policies,
+    skip_backlog: bool,
+) -> Receiver<String> {
+    let (s, r) = unbounded::<String>();
+    let h = String::from(homeserver);
+    let u = String::from(user);
+    let p = String::from(password);
+    let mut store = Store::new(String::from(path.to_string_lossy()));
+    // Without a token the bot starts from now, ignoring what was said while
+    // it was away.
+    let since = match skip_backlog {
+        true => None,
+        false => store.select_sync_token().unwrap_or_default(),
+    };
+    let arc_store = Arc::new(Mutex::new(store));
+
+    thread::spawn(move || {
+        let mut bot = MatrixBot::resume(
+            MatrixAdapter(CommandHandler::new(
+                arc_store.clone(),
+                base_url,
+                policies,
+                s,
+            )),
+            since,
+        );
+
+        end_of_task(
+            MatrixTransport(bot.get_activebot_clone()),
+            arc_store.clone(),
+        );
+        bot.set_verbose(false);
+        bot.run(&u, &p, &h);
+    });
+
+    r
+}
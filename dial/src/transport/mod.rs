@@ -0,0 +1,48 @@
+mod matrix;
+mod terminal;
+
+pub use crate::transport::matrix::start_matrix;
+pub use crate::transport::terminal::start_terminal;
+
+/// How a message relates to an earlier one of its room.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Relation {
+    /// A correction of the message with this id, the body being the new text.
+    Edit(String),
+    /// An answer to the bot, the body reads as a command even without `!`.
+    ReplyToBot,
+}
+
+/// A chat message as the command engine sees it, whatever network it came from.
+#[derive(Clone, Debug)]
+pub struct Incoming {
+    pub id: String,
+    pub room: String,
+    pub sender: String,
+    pub body: String,
+    pub relation: Option<Relation>,
+}
+
+/// What a transport feeds into the command engine.
+#[derive(Clone, Debug)]
+pub enum Event {
+    Message(Incoming),
+    Join(String),
+    Leave(String),
+    /// The token to resume syncing from after a restart.
+    Sync(String),
+}
+
+/// The way out of the command engine, implemented once per chat network.
+///
+/// Transports get cloned into the notification thread, hence the bounds.
+pub trait Transport: Clone + Send + 'static {
+    /// A plain message, meant to catch the attention of people in the room.
+    fn send_text(&self, room: &str, text: &str);
+
+    /// A reply from the bot.
+    fn send_notice(&self, room: &str, text: &str);
+
+    /// A reply from the bot, with an HTML rendering for clients that support it.
+    fn send_html(&self, room: &str, text: &str, html: &str);
+}
@@ -0,0 +1,91 @@
+use crate::bot::CommandHandler;
+use crate::config::{BaseUrl, Policies};
+use crate::notif::end_of_task;
+use crate::transport::{Event, Incoming, Transport};
+use crossbeam_channel::{unbounded, Receiver};
+use shell::store::Store;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time;
+
+/// Prints everything the bot says on stdout, one room per prefix.
+#[derive(Clone)]
+pub struct TerminalTransport;
+
+impl Transport for TerminalTransport {
+    fn send_text(&self, room: &str, text: &str) {
+        println!("[{}] {}", room, text);
+    }
+
+    fn send_notice(&self, room: &str, text: &str) {
+        println!("[{}] {}", room, text);
+    }
+
+    fn send_html(&self, room: &str, text: &str, _html: &str) {
+        println!("[{}] {}", room, text);
+    }
+}
+
+const AS_PREFIX: &str = "/as ";
+
+/// Runs the bot against a single room database, reading messages from stdin.
+///
+/// Lines are sent as `user`, type `/as @someone:server` to speak as someone else.
+pub fn start_terminal(
+    path: &Path,
+    room: &str,
+    user: &str,
+    base_url: BaseUrl,
+    policies: Policies,
+) -> Receiver<String> {
+    let (s, r) = unbounded::<String>();
+    let room = String::from(room);
+    let mut sender = String::from(user);
+    let store = Store::new(String::from(path.to_string_lossy()));
+    let arc_store = Arc::new(Mutex::new(store));
+    let mut handler = CommandHandler::new(arc_store.clone(), base_url, policies, s);
+
+    thread::spawn(move || {
+        let transport = TerminalTransport;
+        end_of_task(transport.clone(), arc_store);
+        handler.handle_event(&transport, Event::Join(room.clone()));
+
+        let stdin = io::stdin();
+        // Ids must not repeat across sessions, handled ones are not run again.
+        let session = time::SystemTime::now()
+            .duration_since(time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let mut count: u64 = 0;
+        print!("{}> ", sender);
+        io::stdout().flush().unwrap_or(());
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if let Some(name) = line.strip_prefix(AS_PREFIX) {
+                sender = String::from(name.trim());
+            } else if !line.trim().is_empty() {
+                count += 1;
+                handler.handle_event(
+                    &transport,
+                    Event::Message(Incoming {
+                        id: format!("terminal-{}-{}", session, count),
+                        room: room.clone(),
+                        sender: sender.clone(),
+                        body: line,
+                        relation: None,
+                    }),
+                );
+            }
+            print!("{}> ", sender);
+            io::stdout().flush().unwrap_or(());
+        }
+        handler.handle_event(&transport, Event::Leave(room));
+    });
+
+    r
+}
@@ -38,10 +38,26 @@ impl From<String> for Node {
 
 pub type Attributes = HashMap<String, String>;
 
+/// Escapes text that comes from users before it goes into markup,
+/// text nodes being written as is.
+pub fn escape<S: AsRef<str>>(s: S) -> String {
+    s.as_ref().chars().fold(String::new(), |mut acc, c| {
+        match c {
+            '&' => acc.push_str("&amp;"),
+            '<' => acc.push_str("&lt;"),
+            '>' => acc.push_str("&gt;"),
+            '"' => acc.push_str("&quot;"),
+            '\'' => acc.push_str("&#39;"),
+            c => acc.push(c),
+        };
+        acc
+    })
+}
+
 fn attrs_as_string(attrs: &Attributes) -> String {
     attrs
         .iter()
-        .map(|(k, v)| format!(" {}=\"{}\"", k, v))
+        .map(|(k, v)| format!(" {}=\"{}\"", k, escape(v)))
         .collect()
 }
 
@@ -307,8 +323,10 @@ known!(span);
 known!(em);
 known!(table);
 known!(tr);
+known!(th);
 known!(td);
 known!(code);
+known!(pre);
 known!(h1);
 known!(h2);
 known!(h3);
@@ -321,6 +339,10 @@ known!(li);
 known!(p, paragraph);
 known!(details);
 known!(summary);
+known!(form);
+known!(label);
+known!(input);
+known!(button);
 
 pub fn with_doctype(e: Element) -> String {
     format!("<!DOCTYPE html>\n{}", e.as_string())
@@ -330,6 +352,14 @@ pub fn with_doctype(e: Element) -> String {
 mod tests {
     use super::*;
     #[test]
+    fn escape_user_text() {
+        let e = div(escape("<b>réunion</b> & \"co\"")).set("title", "a \"b\"");
+        assert_eq!(
+            e.as_string(),
+            "<div title=\"a &quot;b&quot;\">&lt;b&gt;réunion&lt;/b&gt; &amp; &quot;co&quot;</div>"
+        );
+    }
+    #[test]
     fn create_element_with_attrs() {
         let e = div(Empty).set("foo", "bar");
         assert_eq!(e.as_string(), "<div foo=\"bar\"></div>");
@@ -1,14 +1,16 @@
 use crate::common::{with_store, ArcStore};
 use chrono::Datelike;
 use html::{
-    anchor, body, div, h1, head, html, no_display, span, style, with_doctype, Element, Empty,
+    anchor, body, div, escape, h1, head, html, no_display, span, style, with_doctype, Element,
+    Empty,
 };
 use shell::cal::{day_of_week, month_name, Calendar, CalendarEvent, CalendarItem, LocalTime};
 use shell::store::TaskRecord;
 use shell::util::{
-    after_once, date_time_from_st, display_username, dur, human_duration, string, ts,
+    after_once, date_time_from_st, display_username, dur, human_duration, string, ts, url_decode,
+    url_encode,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::{self, SystemTime};
 use warp::Filter;
 
@@ -16,7 +18,11 @@ fn format_tasklist(tasks: impl Iterator<Item = TaskRecord>) -> Vec<Element> {
     tasks
         .map(|rec| {
             div(vec![
-                div(format!("{}({})", display_username(&rec.username), rec.task)),
+                div(escape(format!(
+                    "{}({})",
+                    display_username(&rec.username),
+                    rec.task
+                ))),
                 div(human_duration(
                     rec.end_time
                         .duration_since(rec.start_time)
@@ -84,7 +90,7 @@ where
     }
 }
 
-fn make_csv_link<I>(base_url_tabular: &str, events: &[I]) -> Element
+fn make_csv_link<I>(base_url_tabular: &str, query: &str, events: &[I]) -> Element
 where
     I: Interval,
 {
@@ -96,10 +102,11 @@ where
             anchor("csv").set(
                 "href",
                 format!(
-                    "/{}/{}/{}",
+                    "/{}/{}/{}{}",
                     base_url_tabular,
                     ts(&first.start()) - 1,
-                    ts(&last.end())
+                    ts(&last.end()),
+                    query
                 ),
             )
         }
@@ -107,7 +114,7 @@ where
     }
 }
 
-fn cal_project(recs: &[TaskRecord], base_url_tabular: &str) -> Element {
+fn cal_project(recs: &[TaskRecord], base_url_tabular: &str, query: &str) -> Element {
     let mut cal: Calendar<TaskRecord> = Calendar::new();
     for t in recs.iter() {
         cal.push(
@@ -134,7 +141,7 @@ fn cal_project(recs: &[TaskRecord], base_url_tabular: &str) -> Element {
                         b,
                         div([
                             h1(month_and_year(&d)),
-                            make_csv_link(base_url_tabular, &events),
+                            make_csv_link(base_url_tabular, query, &events),
                         ])
                         .set("class", "month"),
                         w,
@@ -145,7 +152,7 @@ fn cal_project(recs: &[TaskRecord], base_url_tabular: &str) -> Element {
                         b + (m + w),
                         div([
                             h1(month_and_year(&d)),
-                            make_csv_link(base_url_tabular, &events),
+                            make_csv_link(base_url_tabular, query, &events),
                         ])
                         .set("class", "month"),
                         div(Empty).set("class", "week empty"),
@@ -179,15 +186,33 @@ fn cal_project(recs: &[TaskRecord], base_url_tabular: &str) -> Element {
     res + (b + w)
 }
 
-fn cal(token: String, store: ArcStore, project: String) -> Option<String> {
+/// Links to the same calendar restricted to each tag known in the room.
+fn tag_links(base_url_calendar: &str, tags: &[String], current: &Option<String>) -> Element {
+    let mut links = vec![anchor("all").set("href", base_url_calendar)];
+    for tag in tags {
+        let link = anchor(escape(format!("#{}", tag))).set(
+            "href",
+            format!("{}?tag={}", base_url_calendar, url_encode(tag)),
+        );
+        links.push(match current {
+            Some(current) if current == tag => link.set("class", "current"),
+            _ => link,
+        });
+    }
+    div(links).set("class", "tags")
+}
+
+fn cal(token: String, store: ArcStore, project: String, tag: Option<String>) -> Option<String> {
     if let Ok(mut store) = store.lock() {
         if let Ok(connected) = store.connect_existing(&token) {
             let available = connected
                 .select_project_info(project.clone())
                 .map(|rec| rec.provision.map_or(0, |d| dur(&d)) / (1000 * 60 * 60))
                 .unwrap_or(0);
-            let base_url_tabular = format!("{}/tabular/{}", &token, &project);
-            match connected.select_project_detail(project) {
+            let base_url_tabular = format!("{}/tabular/{}", &token, url_encode(&project));
+            let base_url_calendar = format!("/{}/calendar/{}", &token, url_encode(&project));
+            let tags = connected.select_tags().unwrap_or_default();
+            match connected.select_project_detail(project, tag.clone()) {
                 Ok(ref recs) => {
                     let names = recs
                         .iter()
@@ -206,8 +231,11 @@ fn cal(token: String, store: ArcStore, project: String) -> Option<String> {
                             .unwrap_or_else(|_| time::Duration::from_secs(0)))
                     }) / (1000 * 60 * 60);
 
-                    let cal_element = cal_project(recs, &base_url_tabular);
-                    let title = h1(names);
+                    let query = tag
+                        .as_ref()
+                        .map_or(String::new(), |tag| format!("?tag={}", url_encode(tag)));
+                    let cal_element = cal_project(recs, &base_url_tabular, &query);
+                    let title = h1(escape(names));
                     let subtitle = div(vec![
                         div(vec![
                             span(string("Done: ")),
@@ -217,7 +245,8 @@ fn cal(token: String, store: ArcStore, project: String) -> Option<String> {
                             span(string("Avail: ")),
                             span(format!("{} hours", available)),
                         ]),
-                        make_csv_link(&base_url_tabular, recs),
+                        make_csv_link(&base_url_tabular, &query, recs),
+                        tag_links(&base_url_calendar, &tags, &tag),
                     ])
                     .set("class", "summary");
                     let css = style(String::from(include_str!("cal.css"))).set("type", "text/css");
@@ -243,10 +272,17 @@ pub fn calendar(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!(String / "calendar" / String / String)
         .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
         .and(with_store(s))
         .and_then(
-            |token: String, client: String, name: String, store: ArcStore| async move {
-                match cal(token, store, format!("{}/{}", client, name)) {
+            |token: String,
+             client: String,
+             name: String,
+             query: HashMap<String, String>,
+             store: ArcStore| async move {
+                let project = url_decode(&format!("{}/{}", client, name));
+                let tag = query.get("tag").cloned();
+                match cal(token, store, project, tag) {
                     Some(body) => Ok(warp::reply::html(body)),
                     None => Err(warp::reject()),
                 }
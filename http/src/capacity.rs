@@ -0,0 +1,204 @@
+use std::{collections::HashMap, convert::Infallible, error::Error, time::SystemTime};
+
+use csv::Writer;
+use html::{
+    anchor, body, div, escape, h1, head, html, style, table, td, th, tr, with_doctype, Element,
+};
+use shell::{
+    plan::{capacity, iso_week, Balance, Baseline, Capacity, UNASSIGNED},
+    store::{ConnectedStore, StoreResult},
+    util::display_username,
+};
+use warp::Filter;
+
+use crate::common::{with_store, ArcStore};
+
+/// Weeks shown when not asked for, and at most.
+const WEEKS: usize = 26;
+const MAX_WEEKS: usize = 104;
+
+fn hours(secs: u64) -> String {
+    format!("{:.0}", secs as f64 / 3600.0)
+}
+
+/// Demand over capacity, overbooked weeks and those left idle for more
+/// than half of their capacity being highlighted.
+fn make_cell(balance: &Balance) -> Element {
+    let cell = td(format!(
+        "{} / {}",
+        hours(balance.demand),
+        hours(balance.capacity)
+    ));
+    if balance.overbooked() {
+        cell.class("overbooked")
+    } else if balance.idle() * 2 > balance.capacity {
+        cell.class("idle")
+    } else {
+        cell
+    }
+}
+
+fn make_row(name: String, row: &[Balance], cell: fn(&Balance) -> Element) -> Element {
+    tr(std::iter::once(th(escape(name)))
+        .chain(row.iter().map(cell))
+        .collect::<Vec<_>>())
+}
+
+fn demand_cell(balance: &Balance) -> Element {
+    td(hours(balance.demand))
+}
+
+fn user_name(username: &str) -> String {
+    match username {
+        UNASSIGNED => String::from(UNASSIGNED),
+        _ => display_username(username),
+    }
+}
+
+pub fn render_capacity(report: &Capacity) -> Element {
+    let header = |title: &str| {
+        tr(std::iter::once(th(String::from(title)))
+            .chain(report.weeks.iter().map(|week| th(iso_week(week))))
+            .collect::<Vec<_>>())
+    };
+    let users = report
+        .users
+        .iter()
+        .map(|(username, row)| make_row(user_name(username), row, make_cell));
+    let clients = report
+        .clients
+        .iter()
+        .map(|(client, row)| make_row(client.clone(), row, demand_cell));
+
+    div([
+        h1("Capacity and demand, in hours"),
+        anchor("csv").set("href", format!("capacity.csv?weeks={}", report.weeks.len())),
+        table(
+            std::iter::once(header("room"))
+                .chain(std::iter::once(make_row(
+                    String::from("all"),
+                    &report.total,
+                    make_cell,
+                )))
+                .chain(std::iter::once(header("user")))
+                .chain(users)
+                .chain(std::iter::once(header("client")))
+                .chain(clients)
+                .collect::<Vec<_>>(),
+        )
+        .class("capacity-list"),
+    ])
+    .class("capacity-block")
+}
+
+fn to_csv(report: &Capacity) -> Result<String, Box<dyn Error>> {
+    let mut writer = Writer::from_writer(Vec::new());
+    writer.write_record([
+        "week",
+        "scope",
+        "name",
+        "capacity (hours)",
+        "demand (hours)",
+        "idle (hours)",
+        "overbooked",
+    ])?;
+    let rows = std::iter::once(("room", String::from("all"), &report.total))
+        .chain(
+            report
+                .users
+                .iter()
+                .map(|(username, row)| ("user", user_name(username), row)),
+        )
+        .chain(
+            report
+                .clients
+                .iter()
+                .map(|(client, row)| ("client", client.clone(), row)),
+        );
+    for (scope, name, row) in rows {
+        for (week, balance) in report.weeks.iter().zip(row.iter()) {
+            writer.write_record([
+                iso_week(week),
+                String::from(scope),
+                name.clone(),
+                hours(balance.capacity),
+                hours(balance.demand),
+                hours(balance.idle()),
+                balance.overbooked().to_string(),
+            ])?;
+        }
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn load_report(conn: &ConnectedStore, query: &HashMap<String, String>) -> StoreResult<Capacity> {
+    let weeks = query
+        .get("weeks")
+        .and_then(|weeks| weeks.parse::<usize>().ok())
+        .unwrap_or(WEEKS)
+        .min(MAX_WEEKS);
+    Ok(capacity(&Baseline::load(conn)?, SystemTime::now(), weeks))
+}
+
+async fn capacity_handler(
+    token: String,
+    query: HashMap<String, String>,
+    arc_store: ArcStore,
+) -> Result<impl warp::Reply, Infallible> {
+    let css = style(String::from(include_str!("workload.css"))).set("type", "text/css");
+    if let Ok(mut store) = arc_store.lock() {
+        if let Ok(report) = store
+            .connect_existing(&token)
+            .and_then(|connected| load_report(connected, &query))
+        {
+            return Ok(warp::reply::html(with_doctype(html([
+                head(css),
+                body(render_capacity(&report)),
+            ]))));
+        }
+    }
+
+    Ok(warp::reply::html(with_doctype(html([
+        head(css),
+        body(div("Error")),
+    ]))))
+}
+
+async fn csv_handler(
+    token: String,
+    query: HashMap<String, String>,
+    arc_store: ArcStore,
+) -> Result<impl warp::Reply, Infallible> {
+    if let Ok(mut store) = arc_store.lock() {
+        if let Ok(report) = store
+            .connect_existing(&token)
+            .and_then(|connected| load_report(connected, &query))
+        {
+            if let Ok(csv) = to_csv(&report) {
+                return Ok(warp::reply::with_header(csv, "content-type", "text/csv"));
+            }
+        }
+    }
+
+    Ok(warp::reply::with_header(
+        String::from("Error"),
+        "content-type",
+        "text/plain",
+    ))
+}
+
+pub fn capacity_report(
+    s: ArcStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let page = warp::path!(String / "capacity")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_store(s.clone()))
+        .and_then(capacity_handler);
+    let csv = warp::path!(String / "capacity.csv")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_store(s))
+        .and_then(csv_handler);
+    page.or(csv)
+}
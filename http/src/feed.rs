@@ -0,0 +1,131 @@
+use std::{
+    convert::Infallible,
+    time::{Duration, SystemTime},
+};
+
+use shell::{
+    cal::Calendar,
+    ical::{calendar, Event},
+    store::{ConnectedStore, Feed, TaskRecord},
+    util::{date_time_from_st, display_username, room_uid, st_from_date_time},
+};
+use warp::Filter;
+
+use crate::common::{with_store, ArcStore};
+use crate::schedule::{events, load_schedule};
+
+/// How far back the feed of logged tasks goes.
+const TASK_DAYS: u64 = 365;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The tasks `username` logged over the last year, one event per record.
+/// Uids end with `calendar_id`, which names the room without telling it.
+fn task_events(conn: &ConnectedStore, calendar_id: &str, username: &str) -> Vec<Event> {
+    let since = SystemTime::now() - DAY * TASK_DAYS as u32;
+    let mut tasks: Calendar<TaskRecord> = Calendar::new();
+    for rec in conn
+        .select_task_for_user(String::from(username), since)
+        .unwrap_or_default()
+    {
+        tasks.push(
+            date_time_from_st(&rec.start_time),
+            date_time_from_st(&rec.end_time),
+            rec,
+        );
+    }
+    tasks
+        .events()
+        .iter()
+        .map(|event| Event {
+            uid: Some(format!("task-{}@{}", event.data.id, calendar_id)),
+            summary: format!("{} {}", event.data.project, event.data.task),
+            start: st_from_date_time(&event.start_time),
+            end: st_from_date_time(&event.end_time),
+            all_day: false,
+        })
+        .collect()
+}
+
+/// The deadline and the completion of each project, as all-day events.
+fn deadline_events(conn: &ConnectedStore, calendar_id: &str) -> Vec<Event> {
+    let projects = conn.select_all_project_info().unwrap_or_default();
+    let deadlines = projects.iter().filter_map(|project| {
+        project.end_time.map(|end| Event {
+            uid: Some(format!("deadline-{}@{}", project.id, calendar_id)),
+            summary: format!("{} deadline", project.name),
+            start: end,
+            end: end + DAY,
+            all_day: true,
+        })
+    });
+    let completions = projects.iter().filter_map(|project| {
+        project.completed.map(|completed| Event {
+            uid: Some(format!("completed-{}@{}", project.id, calendar_id)),
+            summary: format!("{} completed", project.name),
+            start: completed,
+            end: completed + DAY,
+            all_day: true,
+        })
+    });
+    deadlines.chain(completions).collect()
+}
+
+/// The calendar `file` stands for, read by the owner of `feed` in `room`.
+fn render_feed(conn: &ConnectedStore, room: &str, feed: &Feed, file: &str) -> Option<String> {
+    let calendar_id = room_uid(room);
+    let username = &feed.username;
+    let name = display_username(username);
+    match file {
+        "tasks.ics" => Some(calendar(
+            &format!("Tasks of {}", name),
+            &task_events(conn, &calendar_id, username),
+        )),
+        "plan.ics" => {
+            let schedule = load_schedule(conn, Some(username)).ok()?;
+            Some(calendar(
+                &format!("Plan of {}", name),
+                &events(&calendar_id, &schedule, Some(username)),
+            ))
+        }
+        "deadlines.ics" => Some(calendar("Deadlines", &deadline_events(conn, &calendar_id))),
+        _ => None,
+    }
+}
+
+/// The feeds of a user are found by their token alone, which keeps the
+/// name of the room, and with it its pages, out of calendar apps.
+async fn feed_handler(
+    token: String,
+    file: String,
+    arc_store: ArcStore,
+) -> Result<impl warp::Reply, Infallible> {
+    if let Ok(mut store) = arc_store.lock() {
+        if let Ok(Some((room, feed))) = store.find_feed(&token) {
+            if let Ok(connected) = store.connect_existing(&room) {
+                if let Some(body) = render_feed(connected, &room, &feed, &file) {
+                    return Ok(warp::reply::with_header(
+                        body,
+                        "content-type",
+                        "text/calendar; charset=utf-8",
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(warp::reply::with_header(
+        String::from("Error"),
+        "content-type",
+        "text/plain",
+    ))
+}
+
+pub fn feed(
+    s: ArcStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("feed" / String / String)
+        .and(warp::get())
+        .and(with_store(s))
+        .and_then(feed_handler)
+}
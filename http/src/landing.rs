@@ -1,8 +1,10 @@
-use html::{anchor, body, div, h2, h3, head, html, span, style, title, with_doctype, Empty};
+use html::{
+    anchor, body, div, escape, h2, h3, head, html, span, style, title, with_doctype, Empty,
+};
 use serde_json::json;
 use shell::{
     store::{ConnectedStore, ProjectRecord, StoreError},
-    util::{display_username, human_duration},
+    util::{display_username, human_duration, url_encode},
 };
 use std::{cmp::Ordering, convert::Infallible, time};
 use warp::Filter;
@@ -108,8 +110,10 @@ async fn room(
                 for project in projects {
                     content = content.append(div([
                         span("→"),
-                        anchor(&project.name)
-                            .set("href", format!("/{}/calendar/{}", &token, &project.name)),
+                        anchor(escape(&project.name)).set(
+                            "href",
+                            format!("/{}/calendar/{}", &token, url_encode(&project.name)),
+                        ),
                     ]));
                 }
             }
@@ -5,13 +5,18 @@ use std::{net::SocketAddr, path::Path};
 use warp::Filter;
 
 mod calendar;
+mod capacity;
 mod common;
 mod context;
+mod feed;
 mod helpers;
 mod landing;
+mod metrics;
+mod schedule;
 mod tabular;
 mod timeline;
 mod timeline2;
+mod whatif;
 mod workload;
 
 pub fn start_http(path: &Path, host: &str, static_dir: &str) {
@@ -30,8 +35,14 @@ pub fn start_http(path: &Path, host: &str, static_dir: &str) {
             .or(tabular::tabular(arc_store.clone()))
             .or(landing::room_landing(arc_store.clone()))
             .or(workload::workload(arc_store.clone()))
+            .or(capacity::capacity_report(arc_store.clone()))
+            .or(whatif::whatif(arc_store.clone()))
+            .or(schedule::schedule(arc_store.clone()))
+            .or(feed::feed(arc_store.clone()))
+            .or(metrics::metrics())
             .or(statics)
-            .or(landing::landing(ctx.clone()));
+            .or(landing::landing(ctx.clone()))
+            .with(metrics::timing());
 
         // let runtime = tokio::runtime::Runtime::new().expect("Failed to start tokio runtime");
         let mut runtime = tokio::runtime::Builder::new()
@@ -0,0 +1,72 @@
+use shell::metrics::{self, HTTP_REQUESTS};
+use warp::Filter;
+
+pub fn metrics() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("metrics").and(warp::get()).map(|| {
+        warp::reply::with_header(
+            metrics::render(),
+            "content-type",
+            "text/plain; version=0.0.4",
+        )
+    })
+}
+
+/// Pages served under a room token, by their second path segment.
+const ROOM_ROUTES: &[&str] = &[
+    "calendar",
+    "capacity",
+    "capacity.csv",
+    "load",
+    "schedule",
+    "schedule.ics",
+    "tabular",
+    "timeline",
+    "timeline2",
+    "whatif",
+];
+
+/// The fixed name of the route serving `path`, leaving out room tokens,
+/// feed secrets and project names; paths no route serves are all `other`.
+fn route_name(path: &str) -> &'static str {
+    let mut segments = path.trim_start_matches('/').split('/');
+    match (segments.next(), segments.next()) {
+        (None, _) | (Some(""), None) => "landing",
+        (Some("metrics"), None) => "metrics",
+        (Some("static"), Some(_)) => "static",
+        (Some("feed"), Some(_)) => "feed",
+        (Some(_), None) | (Some(_), Some("")) => "room",
+        (Some(_), Some(page)) => ROOM_ROUTES
+            .iter()
+            .find(|route| **route == page)
+            .copied()
+            .unwrap_or("other"),
+    }
+}
+
+/// Times every request, by the route it went to.
+pub fn timing() -> warp::log::Log<impl Fn(warp::log::Info) + Copy> {
+    warp::log::custom(|info| {
+        metrics::observe(
+            HTTP_REQUESTS,
+            &[("route", route_name(info.path()))],
+            info.elapsed(),
+        );
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_routes_without_tokens() {
+        assert_eq!(route_name("/"), "landing");
+        assert_eq!(route_name("/metrics"), "metrics");
+        assert_eq!(route_name("/static/app.css"), "static");
+        assert_eq!(route_name("/secret-room"), "room");
+        assert_eq!(route_name("/secret-room/calendar/ac/bot"), "calendar");
+        assert_eq!(route_name("/feed/secret/tasks.ics"), "feed");
+        assert_eq!(route_name("/secret-room/wp-admin.php"), "other");
+        assert_eq!(route_name("/secret-room/secret/probe"), "other");
+    }
+}
@@ -0,0 +1,200 @@
+use std::{collections::HashMap, convert::Infallible, time::SystemTime};
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use html::{anchor, body, div, escape, h1, head, html, span, style, with_doctype, Element};
+use shell::{
+    ical::{calendar, Event},
+    plan::{room_strategy, Baseline, DayLoad},
+    store::{ConnectedStore, StoreResult},
+    util::{display_username, room_uid, st_from_date_time, url_encode},
+};
+use warp::Filter;
+
+use crate::common::{with_store, ArcStore};
+
+fn monday(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+fn query_string(week: NaiveDate, user: Option<&String>) -> String {
+    match user {
+        Some(user) => format!("?week={}&user={}", week.format("%F"), url_encode(user)),
+        None => format!("?week={}", week.format("%F")),
+    }
+}
+
+pub(crate) fn load_schedule(
+    conn: &ConnectedStore,
+    user: Option<&String>,
+) -> StoreResult<Vec<DayLoad>> {
+    let strategy = room_strategy(conn)?;
+    Ok(Baseline::load(conn)?
+        .schedule(SystemTime::now(), strategy.as_ref())
+        .into_iter()
+        .filter(|load| match user {
+            Some(user) => &load.user == user,
+            None => true,
+        })
+        .collect())
+}
+
+fn make_day(date: NaiveDate, loads: Vec<&DayLoad>) -> Element {
+    let slots = loads
+        .into_iter()
+        .map(|load| {
+            div([
+                span(format!(
+                    "{}–{}",
+                    load.start.format("%R"),
+                    load.end().format("%R")
+                ))
+                .class("hours"),
+                span(escape(display_username(&load.user))).class("user"),
+                span(escape(&load.project)).class("project"),
+            ])
+            .class("slot")
+        })
+        .collect::<Vec<_>>();
+    div([
+        div(date.format("%a %d").to_string()).class("weekday"),
+        div(slots).class("slot-list"),
+    ])
+    .class("day")
+}
+
+pub fn render_schedule(
+    conn: &ConnectedStore,
+    week: NaiveDate,
+    user: Option<&String>,
+) -> StoreResult<Element> {
+    let schedule = load_schedule(conn, user)?;
+    let days = (0..7)
+        .map(|i| week + Duration::days(i))
+        .map(|date| {
+            make_day(
+                date,
+                schedule.iter().filter(|load| load.day() == date).collect(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let calendar_link = match user {
+        Some(user) => format!("schedule.ics?user={}", url_encode(user)),
+        None => String::from("schedule.ics"),
+    };
+
+    Ok(div([
+        h1(format!("Week of {}", week.format("%F"))),
+        div([
+            anchor("previous week").set("href", query_string(week - Duration::weeks(1), user)),
+            anchor("next week").set("href", query_string(week + Duration::weeks(1), user)),
+            anchor("calendar").set("href", calendar_link),
+        ])
+        .class("schedule-nav"),
+        div(days).class("schedule-week"),
+    ])
+    .class("schedule-block"))
+}
+
+/// The schedule as calendar events, named after the project alone when
+/// it is the schedule of one user. A uid tells the calendar, user, project
+/// and day of a load, and which of the loads of that day it is, so
+/// calendars update events as the plan moves.
+pub(crate) fn events(calendar_id: &str, schedule: &[DayLoad], user: Option<&String>) -> Vec<Event> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    schedule
+        .iter()
+        .map(|load| {
+            let key = format!(
+                "{}-{}-{}",
+                load.user,
+                load.project,
+                load.day().format("%Y%m%d")
+            );
+            let n = seen.entry(key.clone()).or_insert(0);
+            *n += 1;
+            Event {
+                uid: Some(format!("plan-{}-{}@{}", key, n, calendar_id)),
+                summary: match user {
+                    Some(_) => load.project.clone(),
+                    None => format!("{} {}", load.project, display_username(&load.user)),
+                },
+                start: st_from_date_time(&load.start),
+                end: st_from_date_time(&load.end()),
+                all_day: false,
+            }
+        })
+        .collect()
+}
+
+async fn schedule_handler(
+    token: String,
+    query: HashMap<String, String>,
+    arc_store: ArcStore,
+) -> Result<impl warp::Reply, Infallible> {
+    let css = style(String::from(include_str!("schedule.css"))).set("type", "text/css");
+    let week = query
+        .get("week")
+        .and_then(|week| NaiveDate::parse_from_str(week, "%F").ok())
+        .unwrap_or_else(|| Local::today().naive_local());
+    if let Ok(mut store) = arc_store.lock() {
+        if let Ok(connected) = store.connect_existing(&token) {
+            if let Ok(page) = render_schedule(connected, monday(week), query.get("user")) {
+                return Ok(warp::reply::html(with_doctype(html([
+                    head(css),
+                    body(page),
+                ]))));
+            }
+        }
+    }
+
+    Ok(warp::reply::html(with_doctype(html([
+        head(css),
+        body(div("Error")),
+    ]))))
+}
+
+async fn calendar_handler(
+    token: String,
+    query: HashMap<String, String>,
+    arc_store: ArcStore,
+) -> Result<impl warp::Reply, Infallible> {
+    let user = query.get("user");
+    if let Ok(mut store) = arc_store.lock() {
+        if let Ok(schedule) = store
+            .connect_existing(&token)
+            .and_then(|connected| load_schedule(connected, user))
+        {
+            let name = match user {
+                Some(user) => format!("Schedule of {}", display_username(user)),
+                None => String::from("Schedule"),
+            };
+            return Ok(warp::reply::with_header(
+                calendar(&name, &events(&room_uid(&token), &schedule, user)),
+                "content-type",
+                "text/calendar; charset=utf-8",
+            ));
+        }
+    }
+
+    Ok(warp::reply::with_header(
+        String::from("Error"),
+        "content-type",
+        "text/plain",
+    ))
+}
+
+pub fn schedule(
+    s: ArcStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let page = warp::path!(String / "schedule")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_store(s.clone()))
+        .and_then(schedule_handler);
+    let feed = warp::path!(String / "schedule.ics")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_store(s))
+        .and_then(calendar_handler);
+    page.or(feed)
+}
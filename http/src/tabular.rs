@@ -2,9 +2,11 @@ use bytes::{BufMut, Bytes, BytesMut};
 use csv::Writer;
 use shell::{
     store::TaskRecord,
-    util::{st_from_ts, st_to_datestring},
+    util::{st_from_ts, st_to_datestring, url_decode},
 };
+use std::collections::HashMap;
 use std::error::Error;
+use tracing::debug;
 use warp::{http, Filter};
 
 use crate::common::{with_store, ArcStore};
@@ -59,6 +61,8 @@ pub fn make_table(records: &[TaskRecord]) -> Vec<Vec<String>> {
                 st_to_datestring(&record.start_time),
                 format_duration_time(duration),
                 format_duration_decimal(duration),
+                record.entered_by.clone().unwrap_or_default(),
+                record.group_id.clone().unwrap_or_default(),
             ]
         })
         .collect()
@@ -76,6 +80,8 @@ fn to_csv(records: Vec<TaskRecord>) -> Result<BytesWrapper, Box<dyn Error>> {
             "date",
             "duration (time)",
             "duration (decimal)",
+            "entered by",
+            "group",
         ])?;
         for record in make_table(&records) {
             writer.write_record(record)?;
@@ -91,19 +97,20 @@ fn collect_records(
     start: i64,
     end: i64,
     token: String,
+    tag: Option<String>,
     store: ArcStore,
 ) -> Vec<TaskRecord> {
-    let project_name = format!("{}/{}", client, name);
+    let project_name = url_decode(&format!("{}/{}", client, name));
     let start_time = st_from_ts(start);
     let end_time = st_from_ts(end);
-    println!(
+    debug!(
         "TABULAR {}  -->  {}",
         st_to_datestring(&start_time),
         st_to_datestring(&end_time)
     );
     if let Ok(mut store) = store.lock() {
         if let Ok(connected) = store.connect_existing(&token) {
-            return match connected.select_project_detail(project_name) {
+            return match connected.select_project_detail(project_name, tag) {
                 Err(_) => Vec::new(),
                 Ok(tasks) => tasks
                     .into_iter()
@@ -120,13 +127,50 @@ pub fn tabular(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::path!(String / "tabular" / String / String / i64 / i64)
         .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
         .and(with_store(s))
         .and_then(
-            |token: String, client: String, name: String, start: i64, end: i64, store: ArcStore| async move {
-                match to_csv(collect_records(client, name, start, end, token , store)) {
+            |token: String,
+             client: String,
+             name: String,
+             start: i64,
+             end: i64,
+             query: HashMap<String, String>,
+             store: ArcStore| async move {
+                let tag = query.get("tag").cloned();
+                match to_csv(collect_records(client, name, start, end, token, tag, store)) {
                     Ok(body) => Ok(body),
                     Err(_) => Err(warp::reject()),
                 }
             },
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn csv_quotes_names_with_separators() {
+        let record = TaskRecord {
+            id: 1,
+            username: String::from("@zoé:matrix.org"),
+            start_time: UNIX_EPOCH,
+            end_time: UNIX_EPOCH + Duration::from_secs(3600),
+            project: String::from("atelier/café"),
+            task: String::from("meeting, \"client\""),
+            entered_by: None,
+            group_id: None,
+        };
+        let csv = to_csv(vec![record]).map(|wrapper| wrapper.0).unwrap();
+        let line = String::from_utf8_lossy(&csv)
+            .lines()
+            .nth(1)
+            .map(String::from);
+        assert_eq!(
+            line.map(|l| l.starts_with("@zoé:matrix.org,atelier/café,\"meeting, \"\"client\"\"\",")),
+            Some(true)
+        );
+    }
+}
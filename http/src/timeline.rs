@@ -281,7 +281,7 @@ fn get_projects(store: &mut ConnectedStore) -> Result<Vec<TimelineProject>, Stor
                 if record.completed.is_some() {
                     None
                 } else {
-                    let tasks = store.select_project(record.name.clone());
+                    let tasks = store.select_project(record.name.clone(), None);
                     let done = tasks.map(get_done);
                     let notes = store.select_notes(record.name.clone()).unwrap_or_default();
                     Some((
@@ -296,7 +296,7 @@ fn get_projects(store: &mut ConnectedStore) -> Result<Vec<TimelineProject>, Stor
             .iter()
             .filter_map(|record| {
                 record.completed.map(|_| {
-                    let tasks = store.select_project(record.name.clone());
+                    let tasks = store.select_project(record.name.clone(), None);
                     let done = tasks.map(get_done);
                     let notes = store.select_notes(record.name.clone()).unwrap_or_default();
                     (
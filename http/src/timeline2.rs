@@ -133,7 +133,7 @@ fn get_projects(store: &mut ConnectedStore) -> Vec<TimelineProject> {
                     if record.completed.is_some() {
                         None
                     } else {
-                        let tasks = store.select_project(record.name.clone());
+                        let tasks = store.select_project(record.name.clone(), None);
                         let done = tasks.map(get_done);
                         let notes = store.select_notes(record.name.clone()).unwrap_or_default();
                         Some(TimelineProject::new(
@@ -0,0 +1,159 @@
+use std::{collections::HashMap, convert::Infallible, time::SystemTime};
+
+use chrono::{DateTime, Local};
+use html::{
+    body, button, div, escape, form, h1, head, html, input, label, style, table, td, th, tr,
+    with_doctype, Element, Empty,
+};
+use shell::{
+    expr::{parse_command, what_if, Command},
+    plan::{room_strategy, Baseline, Change},
+    store::{ConnectedStore, StoreResult},
+    util::display_username,
+};
+use warp::Filter;
+
+use crate::common::{with_store, ArcStore};
+
+const FIELDS: &[(&str, &str, &str)] = &[
+    ("user", "User", "@alice:matrix.org"),
+    ("amount", "Takes", "80h"),
+    ("project", "On", "ac/newsite"),
+    ("deadline", "By", "2027-03-01"),
+    ("avail", "Available", "mon=8h tue=8h or 20h"),
+    ("from", "From", "2027-01-01"),
+    ("until", "Until", "2027-03-01"),
+];
+
+/// The form, filled with what was asked.
+fn make_form(query: &HashMap<String, String>) -> Element {
+    let fields = FIELDS
+        .iter()
+        .map(|(name, text, placeholder)| {
+            label([
+                div(*text),
+                input(Empty)
+                    .set("name", *name)
+                    .set("placeholder", *placeholder)
+                    .set("value", query.get(*name).cloned().unwrap_or_default()),
+            ])
+        })
+        .chain(std::iter::once(button("Simulate").set("type", "submit")))
+        .collect::<Vec<_>>();
+    form(fields).set("method", "get").class("whatif-form")
+}
+
+/// The `!whatif` command the form stands for, availability being set only
+/// when its three fields are.
+fn command(query: &HashMap<String, String>) -> Option<String> {
+    let field = |name: &str| {
+        query
+            .get(name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+    let user = field("user")?;
+    let mut clauses = Vec::new();
+    if let (Some(amount), Some(project)) = (field("amount"), field("project")) {
+        clauses.push(match field("deadline") {
+            Some(deadline) => format!("{} {} on {} by {}", user, amount, project, deadline),
+            None => format!("{} {} on {}", user, amount, project),
+        });
+    }
+    if let (Some(avail), Some(from), Some(until)) = (field("avail"), field("from"), field("until"))
+    {
+        clauses.push(format!("{} avail {} {} {}", user, from, until, avail));
+    }
+    match clauses.is_empty() {
+        true => None,
+        false => Some(format!("!whatif {}", clauses.join(" and "))),
+    }
+}
+
+fn date(dt: Option<DateTime<Local>>) -> String {
+    dt.map_or(String::from("-"), |dt| dt.format("%F").to_string())
+}
+
+fn make_changes(changes: &[Change]) -> Element {
+    if changes.is_empty() {
+        return div("Nothing moves.").class("whatif-result");
+    }
+    let header = tr(
+        ["project", "user", "before", "after", "deadline", "outlook"]
+            .iter()
+            .map(|name| th(*name))
+            .collect::<Vec<_>>(),
+    );
+    let rows = changes.iter().map(|change| {
+        tr(vec![
+            td(escape(&change.project)),
+            td(escape(display_username(&change.username))),
+            td(change.before.map_or(String::from("-"), date)),
+            td(date(change.after)),
+            td(date(change.deadline)),
+            td(change.outlook()).class(match change.late() {
+                true => "outlook late",
+                false => "outlook",
+            }),
+        ])
+    });
+    table(std::iter::once(header).chain(rows).collect::<Vec<_>>()).class("whatif-result")
+}
+
+fn render_whatif(conn: &ConnectedStore, query: &HashMap<String, String>) -> StoreResult<Element> {
+    let result = match command(query) {
+        None => div(Empty),
+        Some(text) => match parse_command(&text, &[what_if]) {
+            Ok((_, Command::WhatIf(hypotheses))) => {
+                let strategy = room_strategy(conn)?;
+                let changes = shell::plan::what_if(
+                    &Baseline::load(conn)?,
+                    &hypotheses,
+                    SystemTime::now(),
+                    strategy.as_ref(),
+                );
+                div([
+                    div(escape(text)).class("whatif-command"),
+                    make_changes(&changes),
+                ])
+            }
+            Ok(_) => div(Empty),
+            Err(err) => div(escape(format!("{}", err))).class("whatif-error"),
+        },
+    };
+    Ok(div([h1("What if"), make_form(query), result]).class("whatif-block"))
+}
+
+async fn whatif_handler(
+    token: String,
+    query: HashMap<String, String>,
+    arc_store: ArcStore,
+) -> Result<impl warp::Reply, Infallible> {
+    let css = style(String::from(include_str!("workload.css"))).set("type", "text/css");
+    if let Ok(mut store) = arc_store.lock() {
+        if let Ok(page) = store
+            .connect_existing(&token)
+            .and_then(|connected| render_whatif(connected, &query))
+        {
+            return Ok(warp::reply::html(with_doctype(html([
+                head(css),
+                body(page),
+            ]))));
+        }
+    }
+
+    Ok(warp::reply::html(with_doctype(html([
+        head(css),
+        body(div("Error")),
+    ]))))
+}
+
+pub fn whatif(
+    s: ArcStore,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!(String / "whatif")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_store(s))
+        .and_then(whatif_handler)
+}
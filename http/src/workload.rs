@@ -5,11 +5,15 @@ use std::{
 };
 
 use chrono::{DateTime, Datelike, Duration, Local};
-use html::{body, div, h2, h3, head, html, style, with_doctype, Element, Empty};
+use html::{
+    body, div, escape, h2, h3, head, html, style, table, td, th, tr, with_doctype, Element, Empty,
+};
 use shell::{
     cal::month_name,
-    plan::{find_loads, next_monday, plan_all, WorkLoad, WorkPlan},
-    store::{ConnectedStore, ProjectRecord},
+    plan::{
+        avail_of, find_loads, next_monday, plan_all, projections, room_strategy, WorkLoad, WorkPlan,
+    },
+    store::{Avail, ConnectedStore, ProjectRecord},
     util::{date_time_from_st, display_username},
 };
 use warp::Filter;
@@ -81,18 +85,50 @@ fn make_week(
     .class("week-load")
 }
 
-fn make_users(plan: &WorkPlan, umap: &mut UserMap) -> Element {
+fn make_users(plan: &WorkPlan, avails: &[Avail], umap: &mut UserMap) -> Element {
+    let now = SystemTime::now();
     let user_list = plan
         .iter()
         .map(|(username, _)| username)
         .collect::<HashSet<_>>()
         .into_iter()
-        .map(|username| div(display_username(username)).class(umap.alias(username)))
+        .map(|username| {
+            let pattern = avail_of(username, &now, avails)
+                .map_or(String::from("not available"), |a| a.pattern.to_string());
+            div(display_username(username))
+                .class(umap.alias(username))
+                .set("title", &pattern)
+        })
         .collect::<Vec<_>>();
 
     div(user_list).class("user-list")
 }
 
+/// When each intent is projected to be done, late ones first.
+fn make_outlook(plan: &WorkPlan) -> Element {
+    let date = |dt: Option<DateTime<Local>>| {
+        dt.map_or(String::from("-"), |dt| dt.format("%F").to_string())
+    };
+    let header = tr(["project", "user", "finish", "deadline", "outlook"]
+        .iter()
+        .map(|name| th(*name))
+        .collect::<Vec<_>>());
+    let rows = projections(plan).into_iter().map(|(username, projection)| {
+        tr(vec![
+            td(escape(&projection.project)),
+            td(escape(display_username(username))),
+            td(date(projection.finish)),
+            td(date(projection.deadline)),
+            td(projection.outlook()),
+        ])
+        .class(match projection.late() {
+            true => "outlook late",
+            false => "outlook",
+        })
+    });
+    table(std::iter::once(header).chain(rows).collect::<Vec<_>>()).class("outlook-list")
+}
+
 fn in_window(t: &SystemTime, start: &DateTime<Local>, end: &DateTime<Local>) -> bool {
     let dt = &date_time_from_st(t);
     dt >= start && dt < end
@@ -131,8 +167,18 @@ pub fn render_workload(conn: &ConnectedStore) -> Element {
     let projects = conn.select_all_project_info().unwrap();
     let intents = conn.select_intent_all().unwrap();
     let avails = conn.select_avail_all().unwrap();
-    let dones = conn.select_current_task().unwrap();
-    let plan = plan_all(&projects, &intents, &avails, &dones, SystemTime::now());
+    let absences = conn.select_absence_all().unwrap();
+    let dones = conn.select_done_for_intents().unwrap();
+    let strategy = room_strategy(conn).unwrap();
+    let plan = plan_all(
+        &projects,
+        &intents,
+        &avails,
+        &absences,
+        &dones,
+        SystemTime::now(),
+        strategy.as_ref(),
+    );
 
     let max_avail = date_time_from_st(
         &avails
@@ -167,7 +213,13 @@ pub fn render_workload(conn: &ConnectedStore) -> Element {
         start = end;
     }
 
-    div([make_users(&plan, &mut umap), div(months).class("months")]).class("workload-block")
+    div([
+        make_users(&plan, &avails, &mut umap),
+        div(format!("Planned with the {} strategy", strategy.name())).class("strategy"),
+        make_outlook(&plan),
+        div(months).class("months"),
+    ])
+    .class("workload-block")
 }
 
 async fn workload_handler(
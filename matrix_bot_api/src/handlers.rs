@@ -1,5 +1,6 @@
 pub use fractal_matrix_api::types::Message;
 use fractal_matrix_api::types::Room;
+use serde_json::value::Value as JsonValue;
 
 /// What to do after finished handling a message
 pub enum HandleResult {
@@ -25,6 +26,15 @@ pub trait MessageHandler {
         HandleResult::ContinueHandling
     }
 
+    /// Will be called when the bot leaves a room, or is kicked out of it
+    fn handle_leave(&mut self, _bot: &ActiveBot, _room: &Room) -> HandleResult {
+        HandleResult::ContinueHandling
+    }
+
+    /// Will be called after each successful sync with the token to resume
+    /// from, see `MatrixBot::resume`
+    fn handle_sync(&mut self, _bot: &ActiveBot, _since: &str) {}
+
     /// Will be called once the bot has started
     fn init_handler(&mut self, _bot: &ActiveBot) {}
 }
@@ -44,6 +54,71 @@ pub fn extract_command<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
     None
 }
 
+/// How a text message relates to an earlier event of its room.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Relation {
+    /// It corrects `event_id`, `body` being the new text.
+    Edit { event_id: String, body: String },
+    /// It answers `event_id`, sent by `sender` when the client quoted it,
+    /// `body` being the text without the quote.
+    Reply {
+        event_id: String,
+        sender: Option<String>,
+        body: String,
+    },
+}
+
+/// Reads edits and replies out of the source event of `message`.
+/// Returns None for a plain message.
+pub fn relation(message: &Message) -> Option<Relation> {
+    let source: JsonValue = message
+        .source
+        .as_ref()
+        .and_then(|source| serde_json::from_str(source).ok())
+        .unwrap_or(JsonValue::Null);
+    let content = &source["content"];
+    let relates_to = &content["m.relates_to"];
+    if relates_to["rel_type"] == "m.replace" {
+        let event_id = relates_to["event_id"].as_str()?;
+        // Clients put "* " before the fallback text of an edit
+        let body = content["m.new_content"]["body"]
+            .as_str()
+            .unwrap_or_else(|| message.body.trim_start_matches("* "));
+        return Some(Relation::Edit {
+            event_id: event_id.to_string(),
+            body: body.to_string(),
+        });
+    }
+    let event_id = message.in_reply_to.clone().or_else(|| {
+        relates_to["m.in_reply_to"]["event_id"]
+            .as_str()
+            .map(String::from)
+    })?;
+    let (sender, body) = strip_reply_fallback(&message.body);
+    Some(Relation::Reply {
+        event_id,
+        sender,
+        body,
+    })
+}
+
+/// Splits the `> <@sender> quoted text` lines clients put before a reply
+/// from what was actually written.
+fn strip_reply_fallback(body: &str) -> (Option<String>, String) {
+    let sender = body
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("> <"))
+        .and_then(|line| line.split('>').next())
+        .map(String::from);
+    let text: Vec<&str> = body
+        .lines()
+        .skip_while(|line| line.starts_with('>'))
+        .skip_while(|line| line.trim().is_empty())
+        .collect();
+    (sender, text.join("\n"))
+}
+
 pub mod stateless_handler;
 pub use self::stateless_handler::StatelessHandler;
 
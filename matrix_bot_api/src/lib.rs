@@ -85,6 +85,16 @@ pub struct MatrixBot {
 impl MatrixBot {
     /// Consumes any struct that implements the MessageHandler-trait.
     pub fn new<M>(handler: M) -> MatrixBot
+    where
+        M: handlers::MessageHandler + 'static + Send,
+    {
+        MatrixBot::resume(handler, None)
+    }
+
+    /// Like `new`, but picks up syncing from `since`, a token previously
+    /// given to `MessageHandler::handle_sync`, so that messages sent while
+    /// the bot was away get handled. Without a token, starts from now.
+    pub fn resume<M>(handler: M, since: Option<String>) -> MatrixBot
     where
         M: handlers::MessageHandler + 'static + Send,
     {
@@ -94,7 +104,7 @@ impl MatrixBot {
         // sync a limit-parameter.
         // Until then, the workaround is to send "since" of the backend to "now".
         // Not interested in any messages since login
-        bk.data.lock().unwrap().since = Some(Local::now().to_string());
+        bk.data.lock().unwrap().since = Some(since.unwrap_or_else(|| Local::now().to_string()));
         MatrixBot {
             backend: bk.run(),
             rx,
@@ -185,7 +195,12 @@ impl MatrixBot {
                 active_bot.uid = self.uid.clone();
                 self.backend.send(BKCommand::Sync(None, true)).unwrap();
             }
-            BKResponse::Sync(_) => self.backend.send(BKCommand::Sync(None, false)).unwrap(),
+            BKResponse::Sync(since) => {
+                for handler in self.handlers.iter_mut() {
+                    handler.handle_sync(active_bot, &since);
+                }
+                self.backend.send(BKCommand::Sync(None, false)).unwrap()
+            }
             BKResponse::SyncError(_) => self.backend.send(BKCommand::Sync(None, false)).unwrap(),
             BKResponse::ShutDown => {
                 return false;
@@ -249,6 +264,13 @@ impl MatrixBot {
                         HandleResult::StopHandling => break,
                     }
                 }
+            } else if rr.membership.is_left() {
+                for handler in self.handlers.iter_mut() {
+                    match handler.handle_leave(active_bot, &rr) {
+                        HandleResult::ContinueHandling => continue,
+                        HandleResult::StopHandling => break,
+                    }
+                }
             }
         }
     }
@@ -264,6 +286,11 @@ pub struct ActiveBot {
 }
 
 impl ActiveBot {
+    /// The user id of the bot, once logged in.
+    pub fn uid(&self) -> Option<&str> {
+        self.uid.as_deref()
+    }
+
     /// Will shutdown the bot. The bot will not leave any rooms.
     pub fn shutdown(&self) {
         self.backend.send(BKCommand::ShutDown).unwrap();
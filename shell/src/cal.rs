@@ -235,6 +235,10 @@ where
             .collect()
     }
 
+    pub fn events(&self) -> &[CalendarEvent<T>] {
+        &self.events
+    }
+
     pub fn start_time(&self) -> LocalTime {
         let initial = Local.ymd(3000, 1, 1).and_hms(0, 0, 0);
         self.events
@@ -1,12 +1,15 @@
 use crate::parser_ext::{
-    ctx_command, err_date_format, err_duration_format, err_ident, err_project_ident, new_context,
-    with_error, with_success, ParseCommandError, SharedContext,
+    ctx_command, err_date_format, err_duration_format, err_empty_quote, err_ident,
+    err_project_ident, err_quote, err_role, err_user, new_context, with_error, with_success,
+    CommandError, ParseCommandError, SharedContext,
 };
+use crate::plan::{Hypothesis, STRATEGIES};
+use crate::store::{EndPolicy, Role, WeekPattern, WEEKDAYS};
 use chrono::Datelike;
 use chrono::TimeZone;
 use chrono::{offset::Utc, LocalResult};
 use humantime;
-use pom::parser::{end, is_a, one_of, seq, sym, Parser};
+use pom::parser::{empty, end, is_a, none_of, one_of, seq, sym, Parser};
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::convert::TryInto;
@@ -15,24 +18,87 @@ use std::time;
 #[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
 pub enum Command {
     Ping,
-    Help,
+    Help(Option<String>),
     Add(String),
-    Do(String, String, time::Duration),
-    Done(String, String, time::Duration),
-    Switch(String, String),
-    Stop,
-    More(time::Duration),
+    Do(
+        Option<String>,
+        String,
+        String,
+        time::Duration,
+        Vec<String>,
+        Vec<String>,
+    ),
+    Done(
+        Option<String>,
+        String,
+        String,
+        time::Duration,
+        Vec<String>,
+        Vec<String>,
+    ),
+    Switch(Option<String>, String, String, Vec<String>),
+    Stop(Option<String>),
+    More(Option<String>, time::Duration),
     List,
-    Digest(String),
-    Since(time::SystemTime),
+    Digest(String, Option<String>),
+    Since(Option<String>, time::SystemTime, Option<String>),
+    Tags(time::SystemTime),
     Deadline(String, time::SystemTime),
     Provision(String, time::Duration),
     Complete(String, time::SystemTime),
     Note(String, String),
     Meta(String),
     Parent(String, String),
-    Avail(time::SystemTime, time::SystemTime, time::Duration),
-    Intent(String, time::Duration),
+    Avail(time::SystemTime, time::SystemTime, WeekPattern),
+    Intent(String, time::Duration, Option<time::SystemTime>),
+    Intents(Option<String>),
+    EndIntent(Option<String>, String, Option<time::SystemTime>),
+    RemoveIntent(Option<String>, String),
+    IntentLog(Option<String>),
+    Avails(Option<String>),
+    EndAvail(Option<String>, i64, Option<time::SystemTime>),
+    RemoveAvail(Option<String>, i64),
+    Off(time::SystemTime, Option<time::SystemTime>, bool),
+    Holidays(String),
+    Plan,
+    Priority(String, i64),
+    Strategy(String),
+    WhatIf(Vec<Hypothesis>),
+    Feed(bool),
+    OnEnd(EndPolicy),
+    Grant(String, Role),
+    Revoke(String),
+    Roles,
+    Undo,
+    Amend(time::Duration),
+    Yes,
+}
+
+impl Command {
+    /// The user a command acts for when it is not the sender.
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            Command::Do(target, ..)
+            | Command::Done(target, ..)
+            | Command::Switch(target, ..)
+            | Command::Stop(target)
+            | Command::More(target, _)
+            | Command::EndIntent(target, ..)
+            | Command::RemoveIntent(target, _)
+            | Command::EndAvail(target, ..)
+            | Command::RemoveAvail(target, _)
+            | Command::Since(target, ..) => target.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The users a task is shared with, besides its owner.
+    pub fn with(&self) -> &[String] {
+        match self {
+            Command::Do(.., with, _) | Command::Done(.., with, _) => with,
+            _ => &[],
+        }
+    }
 }
 
 fn space<'a>() -> Parser<'a, u8, ()> {
@@ -87,11 +153,51 @@ fn fixed_int<'a>(i: usize) -> Parser<'a, u8, u32> {
         .map(|s| s.parse::<u32>().unwrap_or(0))
 }
 
+fn number<'a>() -> Parser<'a, u8, i64> {
+    digit()
+        .repeat(1..)
+        .convert(String::from_utf8)
+        .convert(|s| s.parse::<i64>())
+}
+
+fn ident_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-.".contains(c)
+}
+
+/// Letters from any script, digits and `_-.`, non-ASCII characters coming
+/// in as their UTF-8 bytes.
 fn ident<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
-    let char_string = (letter() | digit() | one_of(b"_-.")).repeat(1..);
-    with_error(char_string.convert(String::from_utf8), move || {
-        err_ident(ctx.clone())
-    })
+    let char_string = (letter() | digit() | one_of(b"_-.") | is_a(|b| b >= 0x80)).repeat(1..);
+    let checked = char_string.convert(|bytes| match String::from_utf8(bytes) {
+        Ok(s) if s.chars().all(ident_char) => Ok(s),
+        _ => Err(ParseCommandError::IdentFormat),
+    });
+    with_error(checked, move |position| err_ident(ctx.clone(), position))
+}
+
+/// A `"double quoted"` string, where `\"` and `\\` stand for themselves;
+/// `""` is refused, as no name is empty.
+fn quoted<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
+    let escaped = sym(b'\\') * one_of(b"\\\"");
+    let plain = none_of(b"\\\"");
+    let inner = (escaped | plain).repeat(1..).convert(String::from_utf8);
+    let opening = sym(b'"');
+    let qctx = ctx.clone();
+    let rest = with_error(inner - sym(b'"'), move |position| {
+        err_quote(qctx.clone(), position)
+    });
+    let refused = empty().convert(|_| Err::<String, _>(ParseCommandError::EmptyQuote));
+    // told where the name was expected, right after the opening quote
+    let nothing = sym(b'"')
+        * with_error(refused, move |position| {
+            err_empty_quote(ctx.clone(), position - 1)
+        });
+    opening * (rest | nothing)
+}
+
+/// A task name, either a single word or quoted.
+fn name<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
+    ident(ctx.clone()) | quoted(ctx)
 }
 
 fn project_ident<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
@@ -99,8 +205,10 @@ fn project_ident<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
     let sep = sym(b'/');
     let name = ident(ctx.clone());
     let all = client - sep + name;
-    with_error(all, move || err_project_ident(ctx.clone()))
-        .map(|(client, name)| format!("{}/{}", client, name))
+    with_error(all, move |position| {
+        err_project_ident(ctx.clone(), position)
+    })
+    .map(|(client, name)| format!("{}/{}", client, name))
 }
 
 // fn duration_() -> Parser<u8, time::Duration> {
@@ -110,15 +218,27 @@ fn project_ident<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
 //     })
 // }
 
+/// Number and unit pairs such as `2h`, `1h 30m` or `1h30m`, leaving room
+/// for other arguments after a duration.
+fn duration_span<'a>() -> Parser<'a, u8, String> {
+    let part = || (digit().repeat(1..) + letter().repeat(1..)).discard();
+    let span = part() + (space().opt() * part()).repeat(0..);
+    span.collect()
+        .convert(|bytes| String::from_utf8(bytes.to_vec()))
+}
+
 fn duration<'a>(ctx: SharedContext) -> Parser<'a, u8, time::Duration> {
-    let string_parser = string();
+    let span = duration_span();
     Parser::new(
-        move |input: &[u8], start: usize| match string_parser.parse(&input[start..]) {
-            Err(e) => Err(e),
-            Ok(s) => match humantime::parse_duration(&s) {
-                Ok(d) => Ok((d, start + s.len())),
+        move |input: &'a [u8], start: usize| match (span.method)(input, start) {
+            Err(e) => {
+                err_duration_format(ctx.clone(), start);
+                Err(e)
+            }
+            Ok((s, end)) => match humantime::parse_duration(&s) {
+                Ok(d) => Ok((d, end)),
                 Err(err) => {
-                    err_duration_format(ctx.clone());
+                    err_duration_format(ctx.clone(), start);
                     Err(pom::Error::Custom {
                         message: format!("HumanTimeError {}", err),
                         position: start,
@@ -130,6 +250,41 @@ fn duration<'a>(ctx: SharedContext) -> Parser<'a, u8, time::Duration> {
     )
 }
 
+/// A Matrix user id such as `@alice:matrix.org`.
+fn user<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
+    let id = sym(b'@') + none_of(b" \t").repeat(1..);
+    let parsed = id
+        .collect()
+        .convert(|bytes| String::from_utf8(bytes.to_vec()));
+    with_error(parsed, move |position| err_user(ctx.clone(), position))
+}
+
+fn role<'a>(ctx: SharedContext) -> Parser<'a, u8, Role> {
+    let admin = seq(b"admin").map(|_| Role::Admin);
+    let member = seq(b"member").map(|_| Role::Member);
+    let viewer = seq(b"viewer").map(|_| Role::Viewer);
+    with_error(admin | member | viewer, move |position| {
+        err_role(ctx.clone(), position)
+    })
+}
+
+fn tag<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
+    space() * sym(b'#') * ident(ctx)
+}
+
+/// `with @bob @carol`, others sharing a task.
+fn with<'a>(ctx: SharedContext) -> Parser<'a, u8, Vec<String>> {
+    let users = (space() * user(ctx)).repeat(1..);
+    (space() * seq(b"with") * users)
+        .opt()
+        .map(Option::unwrap_or_default)
+}
+
+/// Any number of `#tag` after a task.
+fn tags<'a>(ctx: SharedContext) -> Parser<'a, u8, Vec<String>> {
+    tag(ctx).repeat(0..)
+}
+
 fn st_from_ts(ts: i64) -> Result<time::SystemTime, impl std::error::Error> {
     ts.try_into()
         .map(|x| time::SystemTime::UNIX_EPOCH + time::Duration::from_millis(x))
@@ -165,24 +320,38 @@ fn date<'a>(ctx: SharedContext) -> Parser<'a, u8, time::SystemTime> {
         }
     });
 
-    with_error(mapped1 | mapped2, move || err_date_format(ctx.clone()))
+    // today, tomorrow
+    let relative = (seq(b"today").map(|_| 0) | seq(b"tomorrow").map(|_| 1)).convert(|days| {
+        let day = chrono::Local::today().naive_local() + chrono::Duration::days(days);
+        st_from_ts(Utc.from_utc_date(&day).and_hms(0, 1, 1).timestamp_millis())
+            .map_err(|_| ParseCommandError::DateFormat)
+    });
+
+    with_error(mapped1 | mapped2 | relative, move |position| {
+        err_date_format(ctx.clone(), position)
+    })
 }
 
-type CommandParser<'a> = Parser<'a, u8, Command>;
+pub type CommandParser<'a> = Parser<'a, u8, Command>;
+
+/// A command's grammar, as listed in a registry of commands.
+pub type Grammar<'a> = fn(SharedContext) -> CommandParser<'a>;
 
-fn ping<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn ping<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let cn = with_success(seq(b"!ping"), move || ctx_command("ping", ctx.clone()));
     cn.map(|_| Command::Ping)
 }
 
-fn help<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn help<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let cn = with_success(seq(b"!help") | seq(b"!h"), move || {
         ctx_command("help", ctx.clone())
     });
-    cn.map(|_| Command::Help)
+    let topic = space() * sym(b'!').opt() * letter().repeat(1..).convert(String::from_utf8);
+    let all = cn + topic.opt();
+    all.map(|(_, topic)| Command::Help(topic))
 }
 
-fn add<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn add<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!new") - space(), move || {
         ctx_command("new", mctx.clone())
@@ -192,84 +361,112 @@ fn add<'a>(ctx: SharedContext) -> CommandParser<'a> {
     all.map(|(_, project_name)| Command::Add(project_name))
 }
 
-fn digest<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn digest<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!digest") - space(), move || {
         ctx_command("digest", mctx.clone())
     });
-    let id = project_ident(ctx);
-    let all = cn + id;
-    all.map(|(_, project_name)| Command::Digest(project_name))
+    let id = project_ident(ctx.clone());
+    let all = cn + id + tag(ctx).opt();
+    all.map(|((_, project_name), tag)| Command::Digest(project_name, tag))
 }
 
-fn start<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn start<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!do") - space(), move || {
         ctx_command("do", mctx.clone())
     });
+    let target = (user(ctx.clone()) - space()).opt();
     let id = project_ident(ctx.clone()) - space();
-    let task = ident(ctx.clone()) - space();
-    let d = duration(ctx);
-    let all = cn + id + task + d;
-    all.map(|(((_, project_name), task), duration)| Command::Do(project_name, task, duration))
-        .name("do")
+    let task = name(ctx.clone()) - space();
+    let d = duration(ctx.clone());
+    let all = cn + target + id + task + d + with(ctx.clone()) + tags(ctx);
+    all.map(
+        |((((((_, target), project_name), task), duration), with), tags)| {
+            Command::Do(target, project_name, task, duration, with, tags)
+        },
+    )
+    .name("do")
 }
 
-fn done<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn done<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!done") - space(), move || {
         ctx_command("done", mctx.clone())
     });
+    let target = (user(ctx.clone()) - space()).opt();
     let id = project_ident(ctx.clone()) - space();
-    let task = ident(ctx.clone()) - space();
-    let d = duration(ctx);
-    let all = cn + id + task + d;
-    all.map(|(((_, project_name), task), duration)| Command::Done(project_name, task, duration))
-        .name("done")
+    let task = name(ctx.clone()) - space();
+    let d = duration(ctx.clone());
+    let all = cn + target + id + task + d + with(ctx.clone()) + tags(ctx);
+    all.map(
+        |((((((_, target), project_name), task), duration), with), tags)| {
+            Command::Done(target, project_name, task, duration, with, tags)
+        },
+    )
+    .name("done")
 }
 
-fn switch<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn switch<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!switch") - space(), move || {
         ctx_command("switch", mctx.clone())
     });
+    let target = (user(ctx.clone()) - space()).opt();
     let id = project_ident(ctx.clone()) - space();
-    let task = ident(ctx);
-    let all = cn + id + task;
-    all.map(|((_, project_name), task)| Command::Switch(project_name, task))
-        .name("switch")
+    let task = name(ctx.clone());
+    let all = cn + target + id + task + tags(ctx);
+    all.map(|((((_, target), project_name), task), tags)| {
+        Command::Switch(target, project_name, task, tags)
+    })
+    .name("switch")
 }
 
-fn stop<'a>(ctx: SharedContext) -> CommandParser<'a> {
-    let cn = with_success(seq(b"!stop"), move || ctx_command("stop", ctx.clone()));
-    cn.map(|_| Command::Stop).name("stop")
+pub fn stop<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!stop"), move || ctx_command("stop", mctx.clone()));
+    let all = cn + (space() * user(ctx)).opt();
+    all.map(|(_, target)| Command::Stop(target)).name("stop")
 }
 
-fn more<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn more<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!more") - space(), move || {
         ctx_command("more", mctx.clone())
     });
+    let target = (user(ctx.clone()) - space()).opt();
     let d = duration(ctx);
-    let all = cn + d;
-    all.map(|(_, duration)| Command::More(duration))
+    let all = cn + target + d;
+    all.map(|((_, target), duration)| Command::More(target, duration))
         .name("more")
 }
 
-fn list<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn list<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let cn = with_success(seq(b"!ls"), move || ctx_command("ls", ctx.clone()));
     cn.map(|_| Command::List).name("list")
 }
 
-fn since<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn since<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!since"), move || ctx_command("since", mctx.clone()));
+    let t = date(ctx.clone()) | duration(ctx.clone()).map(|d| time::SystemTime::now() - d);
+    let target = (user(ctx.clone()) - space()).opt();
+    let all = cn - space() + target + t + tag(ctx).opt();
+    all.map(|(((_, target), st), tag)| Command::Since(target, st, tag))
+        .name("since")
+}
+
+pub fn tags_report<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!tags") - space(), move || {
+        ctx_command("tags", mctx.clone())
+    });
     let t = date(ctx.clone()) | duration(ctx).map(|d| time::SystemTime::now() - d);
-    let all = cn - space() + t;
-    all.map(|(_, st)| Command::Since(st)).name("since")
+    let all = cn + t;
+    all.map(|(_, st)| Command::Tags(st)).name("tags")
 }
 
-fn deadline<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn deadline<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!deadline") - space(), move || {
         ctx_command("deadline", mctx.clone())
@@ -281,7 +478,7 @@ fn deadline<'a>(ctx: SharedContext) -> CommandParser<'a> {
         .name("deadline")
 }
 
-fn provision<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn provision<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!provision") - space(), move || {
         ctx_command("provision", mctx.clone())
@@ -293,7 +490,7 @@ fn provision<'a>(ctx: SharedContext) -> CommandParser<'a> {
         .name("provision")
 }
 
-fn complete<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn complete<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!complete") - space(), move || {
         ctx_command("complete", mctx.clone())
@@ -308,19 +505,19 @@ fn complete<'a>(ctx: SharedContext) -> CommandParser<'a> {
     .name("complete")
 }
 
-fn note<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn note<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!note") - space(), move || {
         ctx_command("note", mctx.clone())
     });
-    let id = project_ident(ctx) - space();
-    let content = string();
+    let id = project_ident(ctx.clone()) - space();
+    let content = (quoted(ctx) - end()) | string();
     let all = cn + id + content;
     all.map(|((_, project_name), c)| Command::Note(project_name, c))
         .name("note")
 }
 
-fn meta<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn meta<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!meta") - space(), move || {
         ctx_command("meta", mctx.clone())
@@ -331,7 +528,7 @@ fn meta<'a>(ctx: SharedContext) -> CommandParser<'a> {
         .name("meta")
 }
 
-fn parent<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn parent<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!parent") - space(), move || {
         ctx_command("parent", mctx.clone())
@@ -343,67 +540,355 @@ fn parent<'a>(ctx: SharedContext) -> CommandParser<'a> {
         .name("parent")
 }
 
-fn avail<'a>(ctx: SharedContext) -> CommandParser<'a> {
+pub fn avail<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!avail") - space(), move || {
         ctx_command("avail", mctx.clone())
     });
     let start = date(ctx.clone()) - space();
     let end = date(ctx.clone()) - space();
-    let all = cn + start + end + duration(ctx.clone());
-    all.map(|(((_, start), end), weekly)| Command::Avail(start, end, weekly))
+    let weekly = duration(ctx.clone()).map(WeekPattern::spread);
+    let all = cn + start + end + (week_pattern(ctx) | weekly);
+    all.map(|(((_, start), end), pattern)| Command::Avail(start, end, pattern))
         .name("avail")
 }
 
-fn intent<'a>(ctx: SharedContext) -> CommandParser<'a> {
+fn weekday<'a>() -> Parser<'a, u8, usize> {
+    WEEKDAYS
+        .iter()
+        .enumerate()
+        .map(|(index, name)| seq(name.as_bytes()).map(move |_| index))
+        .reduce(|a, b| a | b)
+        .expect("there are days in a week")
+}
+
+/// `mon=8h tue=8h wed=4h`, days left out being days off.
+fn week_pattern<'a>(ctx: SharedContext) -> Parser<'a, u8, WeekPattern> {
+    let day = || weekday() - sym(b'=') + duration(ctx.clone());
+    let days = day() + (space() * day()).repeat(0..);
+    days.map(|(first, rest)| {
+        let mut pattern = WeekPattern::default();
+        for (index, amount) in std::iter::once(first).chain(rest) {
+            pattern.0[index] = amount;
+        }
+        pattern
+    })
+}
+
+pub fn intent<'a>(ctx: SharedContext) -> CommandParser<'a> {
     let mctx = ctx.clone();
     let cn = with_success(seq(b"!intent") - space(), move || {
         ctx_command("intent", mctx.clone())
     });
     let id = project_ident(ctx.clone()) - space();
     let amount = duration(ctx.clone());
-    let all = cn + id + amount;
-    all.map(|((_, project_name), amount)| Command::Intent(project_name, amount))
+    let start = (space() * seq(b"from") * space() * date(ctx)).opt();
+    let all = cn + id + amount + start;
+    all.map(|(((_, project), amount), start)| Command::Intent(project, amount, start))
         .name("intent")
 }
 
-fn command<'a>(ctx: SharedContext) -> CommandParser<'a> {
-    {
-        ping(ctx.clone())
-            | help(ctx.clone())
-            | add(ctx.clone())
-            | start(ctx.clone())
-            | done(ctx.clone())
-            | stop(ctx.clone())
-            | list(ctx.clone())
-            | digest(ctx.clone())
-            | since(ctx.clone())
-            | more(ctx.clone())
-            | switch(ctx.clone())
-            | deadline(ctx.clone())
-            | provision(ctx.clone())
-            | complete(ctx.clone())
-            | note(ctx.clone())
-            | meta(ctx.clone())
-            | parent(ctx.clone())
-            | avail(ctx.clone())
-            | intent(ctx.clone())
+/// A user, or a project when it does not start with `@`.
+fn user_or_project<'a>(ctx: SharedContext) -> Parser<'a, u8, String> {
+    user(ctx.clone()) | project_ident(ctx)
+}
+
+pub fn intents<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!intents"), move || {
+        ctx_command("intents", mctx.clone())
+    });
+    let all = cn + (space() * user_or_project(ctx)).opt();
+    all.map(|(_, selector)| Command::Intents(selector))
+        .name("intents")
+}
+
+pub fn end_intent<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!endintent") - space(), move || {
+        ctx_command("endintent", mctx.clone())
+    });
+    let target = (user(ctx.clone()) - space()).opt();
+    let end = (space() * date(ctx.clone())).opt();
+    let all = cn + target + project_ident(ctx) + end;
+    all.map(|(((_, target), project), end)| Command::EndIntent(target, project, end))
+        .name("endintent")
+}
+
+pub fn remove_intent<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!rmintent") - space(), move || {
+        ctx_command("rmintent", mctx.clone())
+    });
+    let target = (user(ctx.clone()) - space()).opt();
+    let all = cn + target + project_ident(ctx);
+    all.map(|((_, target), project)| Command::RemoveIntent(target, project))
+        .name("rmintent")
+}
+
+pub fn intent_log<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!intentlog"), move || {
+        ctx_command("intentlog", mctx.clone())
+    });
+    let all = cn + (space() * user_or_project(ctx)).opt();
+    all.map(|(_, selector)| Command::IntentLog(selector))
+        .name("intentlog")
+}
+
+pub fn avails<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!avails"), move || ctx_command("avails", mctx.clone()));
+    let all = cn + (space() * user(ctx)).opt();
+    all.map(|(_, username)| Command::Avails(username))
+        .name("avails")
+}
+
+/// The id of an availability period, as `!avails` shows it.
+fn avail_id<'a>() -> Parser<'a, u8, i64> {
+    sym(b'#').opt() * number()
+}
+
+pub fn end_avail<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!endavail") - space(), move || {
+        ctx_command("endavail", mctx.clone())
+    });
+    let target = (user(ctx.clone()) - space()).opt();
+    let end = (space() * date(ctx)).opt();
+    let all = cn + target + avail_id() + end;
+    all.map(|(((_, target), id), end)| Command::EndAvail(target, id, end))
+        .name("endavail")
+}
+
+pub fn remove_avail<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!rmavail") - space(), move || {
+        ctx_command("rmavail", mctx.clone())
+    });
+    let target = (user(ctx) - space()).opt();
+    let all = cn + target + avail_id();
+    all.map(|((_, target), id)| Command::RemoveAvail(target, id))
+        .name("rmavail")
+}
+
+pub fn off<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!off") - space(), move || {
+        ctx_command("off", mctx.clone())
+    });
+    let start = date(ctx.clone());
+    let end = (space() * date(ctx)).opt();
+    let half = (space() * seq(b"half-day"))
+        .opt()
+        .map(|half| half.is_some());
+    let all = cn + start + end + half;
+    all.map(|(((_, start), end), half)| Command::Off(start, end, half))
+        .name("off")
+}
+
+pub fn holidays<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!holidays") - space(), move || {
+        ctx_command("holidays", ctx.clone())
+    });
+    let file = none_of(b" \t")
+        .repeat(1..)
+        .collect()
+        .convert(|bytes| String::from_utf8(bytes.to_vec()));
+    let all = cn + file;
+    all.map(|(_, file)| Command::Holidays(file))
+        .name("holidays")
+}
+
+pub fn plan<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!plan"), move || ctx_command("plan", ctx.clone()));
+    cn.map(|_| Command::Plan).name("plan")
+}
+
+pub fn priority<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!priority") - space(), move || {
+        ctx_command("priority", mctx.clone())
+    });
+    let all = cn + (project_ident(ctx) - space()) + number();
+    all.map(|((_, project), n)| Command::Priority(project, n))
+        .name("priority")
+}
+
+pub fn strategy<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!strategy") - space(), move || {
+        ctx_command("strategy", ctx.clone())
+    });
+    let name = STRATEGIES
+        .iter()
+        .map(|name| seq(name.as_bytes()).map(move |_| String::from(*name)))
+        .reduce(|a, b| a | b)
+        .expect("there are strategies");
+    let all = cn + name;
+    all.map(|(_, name)| Command::Strategy(name))
+        .name("strategy")
+}
+
+/// `@alice:matrix.org 80h on ac/newsite by 2027-03-01`
+fn intent_hypothesis<'a>(ctx: SharedContext) -> Parser<'a, u8, Hypothesis> {
+    let username = user(ctx.clone()) - space();
+    let amount = duration(ctx.clone()) - space() - seq(b"on") - space();
+    let deadline = (space() * seq(b"by") * space() * date(ctx.clone())).opt();
+    let all = username + amount + project_ident(ctx) + deadline;
+    all.map(
+        |(((username, amount), project), deadline)| Hypothesis::Intent {
+            username,
+            project,
+            amount,
+            deadline,
+        },
+    )
+}
+
+/// `@alice:matrix.org avail 2027-01-01 2027-03-01 20h`
+fn avail_hypothesis<'a>(ctx: SharedContext) -> Parser<'a, u8, Hypothesis> {
+    let username = user(ctx.clone()) - space() - seq(b"avail") - space();
+    let start = date(ctx.clone()) - space();
+    let end = date(ctx.clone()) - space();
+    let weekly = duration(ctx.clone()).map(WeekPattern::spread);
+    let all = username + start + end + (week_pattern(ctx) | weekly);
+    all.map(|(((username, start), end), pattern)| Hypothesis::Avail {
+        username,
+        start,
+        end,
+        pattern,
+    })
+}
+
+pub fn what_if<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!whatif") - space(), move || {
+        ctx_command("whatif", mctx.clone())
+    });
+    let hypothesis = || avail_hypothesis(ctx.clone()) | intent_hypothesis(ctx.clone());
+    let and = space() - seq(b"and") - space();
+    let all = cn + hypothesis() + (and * hypothesis()).repeat(0..);
+    all.map(|((_, first), rest)| Command::WhatIf(std::iter::once(first).chain(rest).collect()))
+        .name("whatif")
+}
+
+pub fn on_end<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!onend") - space(), move || {
+        ctx_command("onend", mctx.clone())
+    });
+    let extend = (seq(b"extend") - space()) * duration(ctx).map(EndPolicy::Extend);
+    let ask = seq(b"ask").map(|_| EndPolicy::Ask);
+    let silent = seq(b"silent").map(|_| EndPolicy::Silent);
+    let all = cn + (extend | ask | silent);
+    all.map(|(_, policy)| Command::OnEnd(policy)).name("onend")
+}
+
+pub fn grant<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!grant") - space(), move || {
+        ctx_command("grant", mctx.clone())
+    });
+    let all = cn + (user(ctx.clone()) - space()) + role(ctx);
+    all.map(|((_, user), role)| Command::Grant(user, role))
+        .name("grant")
+}
+
+pub fn revoke<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!revoke") - space(), move || {
+        ctx_command("revoke", mctx.clone())
+    });
+    let all = cn + user(ctx);
+    all.map(|(_, user)| Command::Revoke(user)).name("revoke")
+}
+
+/// `!feed`, or `!feed renew` to replace the token of the feeds.
+pub fn feed<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!feed"), move || ctx_command("feed", ctx.clone()));
+    let all = cn + (space() * seq(b"renew")).opt();
+    all.map(|(_, renew)| Command::Feed(renew.is_some()))
+        .name("feed")
+}
+
+pub fn roles<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!roles"), move || ctx_command("roles", ctx.clone()));
+    cn.map(|_| Command::Roles).name("roles")
+}
+
+pub fn undo<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!undo"), move || ctx_command("undo", ctx.clone()));
+    cn.map(|_| Command::Undo).name("undo")
+}
+
+pub fn amend<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let mctx = ctx.clone();
+    let cn = with_success(seq(b"!amend") - space(), move || {
+        ctx_command("amend", mctx.clone())
+    });
+    let all = cn + duration(ctx);
+    all.map(|(_, duration)| Command::Amend(duration))
+        .name("amend")
+}
+
+pub fn yes<'a>(ctx: SharedContext) -> CommandParser<'a> {
+    let cn = with_success(seq(b"!yes"), move || ctx_command("yes", ctx.clone()));
+    cn.map(|_| Command::Yes).name("yes")
+}
+
+/// The furthest position a (possibly nested) pom error points at.
+fn furthest(err: &pom::Error, len: usize) -> usize {
+    match err {
+        pom::Error::Incomplete => len,
+        pom::Error::Mismatch { position, .. } | pom::Error::Conversion { position, .. } => {
+            *position
+        }
+        pom::Error::Expect {
+            position, inner, ..
+        } => (*position).max(furthest(inner, len)),
+        pom::Error::Custom {
+            position, inner, ..
+        } => inner
+            .as_ref()
+            .map_or(*position, |inner| (*position).max(furthest(inner, len))),
     }
-    .name("command")
-        - trailing_space()
-}
-
-pub fn parse_command<'a>(expr: &'a str) -> Result<Command, ParseCommandError> {
-    let ctx = new_context();
-    command(ctx.clone()).parse(expr.as_bytes()).map_err(|_| {
-        let ctx = ctx.borrow();
-        match (ctx.command.clone(), ctx.error.clone()) {
-            (None, _) => ParseCommandError::NotFound,
-            (Some(_), Some(err)) => err,
-            (Some(name), _) => ParseCommandError::Unknown(name),
-            // (_, _) => Err(ParseCommandError::Mysterious),
+}
+
+/// Parses `expr` with the first of `grammars` that accepts it,
+/// returning the index of that grammar along with the command.
+///
+/// When none does, the error comes from the last grammar that
+/// recognized the command name, if any.
+pub fn parse_command<'a>(
+    expr: &'a str,
+    grammars: &[Grammar<'a>],
+) -> Result<(usize, Command), CommandError> {
+    let mut failure = CommandError {
+        command: None,
+        error: ParseCommandError::NotFound,
+        position: None,
+    };
+    for (index, grammar) in grammars.iter().enumerate() {
+        let ctx = new_context();
+        let parser = (grammar(ctx.clone()) - trailing_space() - end()).name("command");
+        match parser.parse(expr.as_bytes()) {
+            Ok(command) => return Ok((index, command)),
+            Err(err) => {
+                let ctx = ctx.borrow();
+                if let Some(name) = ctx.command.clone() {
+                    failure = CommandError {
+                        error: ctx
+                            .error
+                            .clone()
+                            .unwrap_or_else(|| ParseCommandError::Unknown(name.clone())),
+                        position: ctx.position.or_else(|| Some(furthest(&err, expr.len()))),
+                        command: Some(name),
+                    };
+                }
+            }
         }
-    })
+    }
+    Err(failure)
 }
 
 #[cfg(test)]
@@ -413,7 +898,7 @@ mod tests {
     #[test]
     fn parse_error_is_nice() {
         let expected = String::from("Command does not exists");
-        let result = match parse_command("!truc") {
+        let result = match parse_command("!truc", &[ping, help, start]) {
             Ok(_) => String::from("Should not be OK"),
             Err(err) => format!("{}", err),
         };
@@ -421,19 +906,185 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn parse_error_points_at_the_argument() {
+        match parse_command("!deadline ac/bot 31/31", &[start, deadline]) {
+            Ok(_) => panic!("Should not be OK"),
+            Err(err) => {
+                assert_eq!(err.command, Some(String::from("deadline")));
+                assert_eq!(err.position, Some(17));
+                assert_eq!(err.expected(), Some("a date like 2024-06-30 or 30/06"));
+            }
+        }
+        match parse_command("!do ac-bot dev 2h", &[start, deadline]) {
+            Ok(_) => panic!("Should not be OK"),
+            Err(err) => {
+                assert_eq!(err.command, Some(String::from("do")));
+                assert_eq!(err.position, Some(4));
+            }
+        }
+    }
+
     #[test]
     fn parse_do_ok() {
-        let expected: Result<Command, ParseCommandError> = Ok(Command::Do(
+        let expected: Result<Command, CommandError> = Ok(Command::Do(
+            None,
             "foo/0".into(),
             "dev".into(),
             time::Duration::from_secs(3 * 60 * 60 + (30 * 60)),
+            Vec::new(),
+            Vec::new(),
         ));
         assert_eq!(
-            parse_command("!do foo/0 dev 3h 30m").is_ok(),
+            parse_command("!do foo/0 dev 3h 30m", &[start, done]).is_ok(),
             expected.is_ok()
         );
     }
     #[test]
+    fn parse_tags() {
+        assert_eq!(
+            parse_command("!do ac/bot dev 1h 30m #billable #support", &[start]).ok(),
+            Some((
+                0,
+                Command::Do(
+                    None,
+                    "ac/bot".into(),
+                    "dev".into(),
+                    time::Duration::from_secs(90 * 60),
+                    Vec::new(),
+                    vec!["billable".into(), "support".into()]
+                )
+            ))
+        );
+        assert_eq!(
+            parse_command("!digest ac/bot #billable", &[digest]).ok(),
+            Some((0, Command::Digest("ac/bot".into(), Some("billable".into()))))
+        );
+        assert!(matches!(
+            parse_command("!since 2week #support", &[since]),
+            Ok((0, Command::Since(None, _, Some(tag)))) if tag == "support"
+        ));
+        assert!(parse_command("!do ac/bot dev 1h nonsense", &[start]).is_err());
+    }
+    #[test]
+    fn parse_grant() {
+        assert_eq!(
+            parse_command("!grant @bob:matrix.org viewer", &[grant, revoke]).ok(),
+            Some((0, Command::Grant("@bob:matrix.org".into(), Role::Viewer)))
+        );
+        match parse_command("!grant @bob:matrix.org boss", &[grant, revoke]) {
+            Ok(_) => panic!("Should not be OK"),
+            Err(err) => assert_eq!(err.position, Some(23)),
+        }
+    }
+    #[test]
+    fn parse_on_behalf() {
+        assert_eq!(
+            parse_command("!done @alice:matrix.org ac/bot dev 2h", &[start, done]).ok(),
+            Some((
+                1,
+                Command::Done(
+                    Some("@alice:matrix.org".into()),
+                    "ac/bot".into(),
+                    "dev".into(),
+                    time::Duration::from_secs(2 * 60 * 60),
+                    Vec::new(),
+                    Vec::new()
+                )
+            ))
+        );
+        assert_eq!(
+            parse_command("!stop @alice:matrix.org", &[stop]).ok(),
+            Some((0, Command::Stop(Some("@alice:matrix.org".into()))))
+        );
+        assert_eq!(
+            parse_command("!stop", &[stop]).ok(),
+            Some((0, Command::Stop(None)))
+        );
+        assert!(matches!(
+            parse_command("!since @alice:matrix.org 1week", &[since]),
+            Ok((0, Command::Since(Some(_), _, None)))
+        ));
+    }
+    #[test]
+    fn parse_with() {
+        assert_eq!(
+            parse_command(
+                "!done ac/bot meeting 1h with @bob @carol #billable",
+                &[done]
+            )
+            .ok(),
+            Some((
+                0,
+                Command::Done(
+                    None,
+                    "ac/bot".into(),
+                    "meeting".into(),
+                    time::Duration::from_secs(60 * 60),
+                    vec!["@bob".into(), "@carol".into()],
+                    vec!["billable".into()]
+                )
+            ))
+        );
+        assert!(parse_command("!do ac/bot meeting 1h with", &[start]).is_err());
+        assert_eq!(
+            parse_command("!amend 45m", &[undo, amend]).ok(),
+            Some((1, Command::Amend(time::Duration::from_secs(45 * 60))))
+        );
+        assert_eq!(
+            parse_command("!undo", &[undo, amend]).ok(),
+            Some((0, Command::Undo))
+        );
+    }
+    #[test]
+    fn parse_week_pattern() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        match parse_command(
+            "!avail 2024-01-01 2024-06-30 mon=8h tue=8h wed=4h",
+            &[avail],
+        ) {
+            Ok((_, Command::Avail(_, _, pattern))) => {
+                assert_eq!(pattern.day(chrono::Weekday::Wed), hours(4));
+                assert_eq!(pattern.day(chrono::Weekday::Thu), hours(0));
+                assert_eq!(pattern.weekly(), hours(20));
+                assert_eq!(pattern.to_string(), "mon=8h tue=8h wed=4h");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match parse_command("!avail 2024-01-01 2024-06-30 32h", &[avail]) {
+            Ok((_, Command::Avail(_, _, pattern))) => {
+                assert_eq!(pattern, WeekPattern::spread(hours(32)));
+                assert_eq!(pattern.day(chrono::Weekday::Sat), hours(0));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(parse_command("!avail 2024-01-01 2024-06-30 mon=", &[avail]).is_err());
+    }
+    #[test]
+    fn parse_off() {
+        let day = |s: &str| match parse_command(&format!("!deadline a/b {}", s), &[deadline]) {
+            Ok((_, Command::Deadline(_, t))) => t,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(
+            parse_command("!off 2026-12-21 2027-01-03", &[off]).ok(),
+            Some((
+                0,
+                Command::Off(day("2026-12-21"), Some(day("2027-01-03")), false)
+            ))
+        );
+        match parse_command("!off tomorrow half-day", &[off]) {
+            Ok((_, Command::Off(start, None, true))) => {
+                assert!(start > time::SystemTime::now())
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            parse_command("!holidays france.ics", &[off, holidays]).ok(),
+            Some((1, Command::Holidays("france.ics".into())))
+        );
+    }
+    #[test]
     fn parse_new_ok() {
         assert_eq!(
             add(new_context()).parse("!new ac/bot".as_bytes()),
@@ -459,6 +1110,55 @@ mod tests {
         );
     }
     #[test]
+    fn parse_unicode_and_quoted_names() {
+        assert_eq!(
+            parse_command("!do atelier/café \"réunion client\" 1h", &[start]).ok(),
+            Some((
+                0,
+                Command::Do(
+                    None,
+                    "atelier/café".into(),
+                    "réunion client".into(),
+                    time::Duration::from_secs(60 * 60),
+                    Vec::new(),
+                    Vec::new()
+                )
+            ))
+        );
+        assert_eq!(
+            parse_command("!switch ac/bot \"say \\\"hi\\\"\"", &[switch]).ok(),
+            Some((
+                0,
+                Command::Switch(None, "ac/bot".into(), "say \"hi\"".into(), Vec::new())
+            ))
+        );
+        assert_eq!(
+            parse_command("!switch ac/bot review #billable", &[switch]).ok(),
+            Some((
+                0,
+                Command::Switch(
+                    None,
+                    "ac/bot".into(),
+                    "review".into(),
+                    vec!["billable".into()]
+                )
+            ))
+        );
+        assert_eq!(
+            parse_command("!note ac/bot \"à faire\"", &[note]).ok(),
+            Some((0, Command::Note("ac/bot".into(), "à faire".into())))
+        );
+        match parse_command("!do ac/bot \"client meeting 1h", &[start]) {
+            Ok(_) => panic!("Should not be OK"),
+            Err(err) => assert_eq!(err.expected(), Some("a closing \"")),
+        }
+        match parse_command("!do ac/bot \"\" 1h", &[start]) {
+            Ok(_) => panic!("Should not be OK"),
+            Err(err) => assert_eq!(err.expected(), Some("a name between the quotes")),
+        }
+        assert!(parse_command("!do ac/bot r\u{e9}union\u{2192}client 1h", &[start]).is_err());
+    }
+    #[test]
     fn parse_date_iso() {
         assert_eq!(
             date(new_context()).parse("2042-05-29".as_bytes()),
@@ -473,9 +1173,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_help_topic() {
+        assert_eq!(
+            parse_command("!help !deadline", &[help]).ok(),
+            Some((0, Command::Help(Some("deadline".into()))))
+        );
+        assert_eq!(
+            parse_command("!do ac/bot dev 1h", &[help, start])
+                .map(|(i, _)| i)
+                .ok(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn parse_on_end() {
+        assert_eq!(
+            parse_command("!onend extend 30m", &[on_end]).ok(),
+            Some((
+                0,
+                Command::OnEnd(EndPolicy::Extend(time::Duration::from_secs(30 * 60)))
+            ))
+        );
+        assert_eq!(
+            parse_command("!onend silent", &[on_end]).ok(),
+            Some((0, Command::OnEnd(EndPolicy::Silent)))
+        );
+    }
+
+    #[test]
+    fn parse_priority_and_strategy() {
+        assert_eq!(
+            parse_command("!priority ac/bot 3", &[priority]).ok(),
+            Some((0, Command::Priority("ac/bot".into(), 3)))
+        );
+        assert!(parse_command("!priority ac/bot high", &[priority]).is_err());
+        assert_eq!(
+            parse_command("!strategy proportional", &[priority, strategy]).ok(),
+            Some((1, Command::Strategy("proportional".into())))
+        );
+        assert!(parse_command("!strategy random", &[strategy]).is_err());
+    }
+
+    #[test]
+    fn parse_feed() {
+        assert_eq!(
+            parse_command("!feed", &[feed]).ok(),
+            Some((0, Command::Feed(false)))
+        );
+        assert_eq!(
+            parse_command("!feed renew", &[feed]).ok(),
+            Some((0, Command::Feed(true)))
+        );
+        assert!(parse_command("!feed again", &[feed]).is_err());
+    }
+
+    #[test]
+    fn parse_what_if() {
+        match parse_command(
+            "!whatif @alice:matrix.org 80h on ac/newsite by 2027-03-01 and @bob:matrix.org avail 2027-01-01 2027-03-01 mon=8h",
+            &[what_if],
+        ) {
+            Ok((_, Command::WhatIf(hypotheses))) => match hypotheses.as_slice() {
+                [Hypothesis::Intent {
+                    username,
+                    project,
+                    amount,
+                    deadline: Some(_),
+                }, Hypothesis::Avail { pattern, .. }] => {
+                    assert_eq!(username, "@alice:matrix.org");
+                    assert_eq!(project, "ac/newsite");
+                    assert_eq!(*amount, time::Duration::from_secs(80 * 60 * 60));
+                    assert_eq!(pattern.weekly(), time::Duration::from_secs(8 * 60 * 60));
+                }
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(parse_command("!whatif @alice:matrix.org 80h on ac/newsite", &[what_if]).is_ok());
+        assert!(parse_command("!whatif ac/newsite 80h", &[what_if]).is_err());
+    }
+
+    #[test]
+    fn parse_intent_and_avail_management() {
+        let grammars = [intent, intents, end_intent, remove_intent, intent_log];
+        assert_eq!(
+            parse_command("!intents", &grammars).ok(),
+            Some((1, Command::Intents(None)))
+        );
+        assert_eq!(
+            parse_command("!intents ac/bot", &grammars).ok(),
+            Some((1, Command::Intents(Some("ac/bot".into()))))
+        );
+        assert_eq!(
+            parse_command("!intentlog @bob:matrix.org", &grammars).ok(),
+            Some((4, Command::IntentLog(Some("@bob:matrix.org".into()))))
+        );
+        assert_eq!(
+            parse_command("!endintent ac/bot", &grammars).ok(),
+            Some((2, Command::EndIntent(None, "ac/bot".into(), None)))
+        );
+        match parse_command("!endintent @bob:matrix.org ac/bot 2026-12-31", &grammars) {
+            Ok((2, Command::EndIntent(Some(target), project, Some(_)))) => {
+                assert_eq!(target, "@bob:matrix.org");
+                assert_eq!(project, "ac/bot");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            parse_command("!rmintent ac/bot", &grammars).ok(),
+            Some((3, Command::RemoveIntent(None, "ac/bot".into())))
+        );
+
+        let grammars = [avail, avails, end_avail, remove_avail];
+        assert_eq!(
+            parse_command("!avails @bob:matrix.org", &grammars).ok(),
+            Some((1, Command::Avails(Some("@bob:matrix.org".into()))))
+        );
+        assert!(matches!(
+            parse_command("!endavail #12 2026-12-31", &grammars),
+            Ok((2, Command::EndAvail(None, 12, Some(_))))
+        ));
+        assert_eq!(
+            parse_command("!rmavail @bob:matrix.org 12", &grammars).ok(),
+            Some((3, Command::RemoveAvail(Some("@bob:matrix.org".into()), 12)))
+        );
+        assert!(parse_command("!rmavail twelve", &grammars).is_err());
+    }
+
     #[test]
     fn parse_complete_without_date() {
-        match parse_command("!complete foo/bar") {
+        match parse_command("!complete foo/bar", &[complete]) {
             Err(r) => panic!("err: {}", r),
             Ok(_) => (),
         }
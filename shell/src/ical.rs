@@ -0,0 +1,292 @@
+//! Just enough of iCalendar (RFC 5545) to read holiday calendars and to
+//! write schedules.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::time;
+
+use crate::util::st_from_ts;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub uid: Option<String>,
+    pub summary: String,
+    pub start: time::SystemTime,
+    pub end: time::SystemTime,
+    /// Written as dates, the days of `start` and `end` in UTC, the end
+    /// being the day after the last.
+    pub all_day: bool,
+}
+
+/// Joins folded lines, those starting with a space or a tab continuing the
+/// one before.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        match (
+            line.strip_prefix(|c| c == ' ' || c == '\t'),
+            lines.last_mut(),
+        ) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(String::from(line)),
+        }
+    }
+    lines
+}
+
+/// `NAME;PARAM=X:value` as its name and value; the value type is told
+/// apart by its shape rather than by parameters.
+fn property(line: &str) -> Option<(String, String)> {
+    let (head, value) = line.split_once(':')?;
+    let name = head.split(';').next()?.to_uppercase();
+    Some((name, String::from(value)))
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => (),
+            },
+            (c, false) => out.push(c),
+        }
+    }
+    out
+}
+
+/// A `DATE` or `DATE-TIME` value. Days are taken at one past midnight UTC
+/// like the dates of commands; times without a zone are read as UTC.
+fn date_value(value: &str) -> Option<(time::SystemTime, bool)> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        let dt = Utc.from_utc_datetime(&date.and_hms(0, 1, 1));
+        return Some((st_from_ts(dt.timestamp_millis()), true));
+    }
+    let naive = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()?;
+    Some((
+        st_from_ts(Utc.from_utc_datetime(&naive).timestamp_millis()),
+        false,
+    ))
+}
+
+/// An event being read, between its `BEGIN` and `END` lines.
+#[derive(Default)]
+struct Partial {
+    uid: Option<String>,
+    summary: String,
+    start: Option<(time::SystemTime, bool)>,
+    end: Option<time::SystemTime>,
+}
+
+impl Partial {
+    /// All-day events without an end last one day, others no time at all.
+    fn event(self) -> Option<Event> {
+        let (start, is_date) = self.start?;
+        let day = time::Duration::from_secs(24 * 60 * 60);
+        Some(Event {
+            uid: self.uid,
+            summary: self.summary,
+            start,
+            end: self
+                .end
+                .unwrap_or(if is_date { start + day } else { start }),
+            all_day: is_date,
+        })
+    }
+}
+
+/// The `VEVENT`s of a calendar, skipping those without a start.
+pub fn events(text: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<Partial> = None;
+    for line in unfold(text) {
+        let (name, value) = match property(&line) {
+            Some(p) => p,
+            None => continue,
+        };
+        match (name.as_str(), value.trim(), current.as_mut()) {
+            ("BEGIN", "VEVENT", _) => current = Some(Partial::default()),
+            ("END", "VEVENT", Some(_)) => events.extend(current.take().and_then(Partial::event)),
+            ("UID", _, Some(event)) => event.uid = Some(unescape(&value)),
+            ("SUMMARY", _, Some(event)) => event.summary = unescape(&value),
+            ("DTSTART", _, Some(event)) => event.start = date_value(&value),
+            ("DTEND", _, Some(event)) => event.end = date_value(&value).map(|(end, _)| end),
+            _ => (),
+        }
+    }
+    events
+}
+
+fn escape(value: &str) -> String {
+    value.chars().fold(String::new(), |mut out, c| {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c)
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        };
+        out
+    })
+}
+
+/// Splits lines longer than 75 octets, without cutting a character.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn date_time(t: &time::SystemTime) -> String {
+    let dt: DateTime<Utc> = (*t).into();
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn date(t: &time::SystemTime) -> String {
+    let dt: DateTime<Utc> = (*t).into();
+    dt.format("%Y%m%d").to_string()
+}
+
+/// A calendar of events, those without a uid getting one from their start
+/// and summary.
+pub fn calendar(name: &str, events: &[Event]) -> String {
+    let stamp = date_time(&time::SystemTime::now());
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//pearls//schedule//EN"),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    for event in events {
+        let uid = event
+            .uid
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}@pearls", date_time(&event.start), event.summary));
+        lines.extend(vec![
+            String::from("BEGIN:VEVENT"),
+            format!("UID:{}", escape(&uid)),
+            format!("DTSTAMP:{}", stamp),
+        ]);
+        lines.extend(match event.all_day {
+            true => vec![
+                format!("DTSTART;VALUE=DATE:{}", date(&event.start)),
+                format!("DTEND;VALUE=DATE:{}", date(&event.end)),
+            ],
+            false => vec![
+                format!("DTSTART:{}", date_time(&event.start)),
+                format!("DTEND:{}", date_time(&event.end)),
+            ],
+        });
+        lines.extend(vec![
+            format!("SUMMARY:{}", escape(&event.summary)),
+            String::from("END:VEVENT"),
+        ]);
+    }
+    lines.push(String::from("END:VCALENDAR"));
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_all_day_events() {
+        let text = "BEGIN:VCALENDAR\r\n\
+                    VERSION:2.0\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:xmas-2026@example.org\r\n\
+                    DTSTART;VALUE=DATE:20261225\r\n\
+                    SUMMARY:Christmas\\, at last\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    DTSTART;VALUE=DATE:20261231\r\n\
+                    DTEND;VALUE=DATE:20270102\r\n\
+                    SUMMARY:New \r\n\
+                    \x20year\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    SUMMARY:No start\r\n\
+                    END:VEVENT\r\n\
+                    END:VCALENDAR\r\n";
+        let events = events(text);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid.as_deref(), Some("xmas-2026@example.org"));
+        assert_eq!(events[0].summary, "Christmas, at last");
+        assert_eq!(
+            events[0]
+                .end
+                .duration_since(events[0].start)
+                .unwrap()
+                .as_secs(),
+            24 * 60 * 60
+        );
+        assert_eq!(events[1].summary, "New year");
+        assert_eq!(
+            events[1]
+                .end
+                .duration_since(events[1].start)
+                .unwrap()
+                .as_secs(),
+            2 * 24 * 60 * 60
+        );
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let start = st_from_ts(Utc.ymd(2026, 10, 19).and_hms(8, 0, 0).timestamp_millis());
+        let written = calendar(
+            "Schedule",
+            &[Event {
+                uid: Some(String::from("alice-ac/bot-20261019@pearls")),
+                summary: format!("ac/bot; {}", "review ".repeat(12)),
+                start,
+                end: start + time::Duration::from_secs(6 * 60 * 60),
+                all_day: false,
+            }],
+        );
+
+        assert!(written.contains("DTSTART:20261019T080000Z\r\n"));
+        assert!(written.lines().all(|line| line.len() <= 76));
+        let events = events(&written);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].summary,
+            format!("ac/bot; {}", "review ".repeat(12))
+        );
+        assert_eq!(events[0].start, start);
+    }
+
+    #[test]
+    fn writes_all_day_events_as_dates() {
+        let text = "BEGIN:VCALENDAR\r\n\
+                    BEGIN:VEVENT\r\n\
+                    DTSTART;VALUE=DATE:20261225\r\n\
+                    SUMMARY:Christmas\r\n\
+                    END:VEVENT\r\n\
+                    END:VCALENDAR\r\n";
+        let read = events(text);
+        let written = calendar("Holidays", &read);
+
+        assert!(read[0].all_day);
+        assert!(written.contains("DTSTART;VALUE=DATE:20261225\r\n"));
+        assert!(written.contains("DTEND;VALUE=DATE:20261226\r\n"));
+        let again = events(&written);
+        assert_eq!((again[0].start, again[0].end), (read[0].start, read[0].end));
+    }
+}
@@ -2,6 +2,8 @@ extern crate chrono;
 
 pub mod cal;
 pub mod expr;
+pub mod ical;
+pub mod metrics;
 pub mod parser_ext;
 pub mod plan;
 pub mod store;
@@ -0,0 +1,136 @@
+//! Counters and timings of the running program, rendered in the Prometheus
+//! text format for the `/metrics` route.
+//!
+//! The bot and the HTTP server share one process, hence one registry.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time;
+
+pub const COMMANDS: &str = "pearls_commands_total";
+pub const PARSE_FAILURES: &str = "pearls_parse_failures_total";
+pub const NOTIFICATIONS: &str = "pearls_notifications_total";
+pub const STORE_ERRORS: &str = "pearls_store_errors_total";
+pub const HTTP_REQUESTS: &str = "pearls_http_request_duration_seconds";
+
+enum Kind {
+    Counter,
+    Summary,
+}
+
+const METRICS: &[(&str, Kind, &str)] = &[
+    (COMMANDS, Kind::Counter, "Commands run, by command."),
+    (
+        PARSE_FAILURES,
+        Kind::Counter,
+        "Messages that did not parse, by the command they started.",
+    ),
+    (NOTIFICATIONS, Kind::Counter, "Notifications sent, by kind."),
+    (STORE_ERRORS, Kind::Counter, "Failed SQLite statements."),
+    (
+        HTTP_REQUESTS,
+        Kind::Summary,
+        "Time spent answering HTTP requests, by route.",
+    ),
+];
+
+#[derive(Default)]
+struct Sample {
+    count: u64,
+    sum: f64,
+}
+
+/// Samples by metric name and rendered labels.
+static SAMPLES: Mutex<BTreeMap<(&str, String), Sample>> = Mutex::new(BTreeMap::new());
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn record(name: &'static str, pairs: &[(&str, &str)], value: f64) {
+    let mut samples = SAMPLES.lock().unwrap_or_else(|err| err.into_inner());
+    let sample = samples.entry((name, labels(pairs))).or_default();
+    sample.count += 1;
+    sample.sum += value;
+}
+
+/// Counts one more of `name`.
+pub fn incr(name: &'static str, pairs: &[(&str, &str)]) {
+    record(name, pairs, 1.0);
+}
+
+/// Adds a timing to the summary `name`.
+pub fn observe(name: &'static str, pairs: &[(&str, &str)], elapsed: time::Duration) {
+    record(name, pairs, elapsed.as_secs_f64());
+}
+
+fn series(name: &str, labels: &str) -> String {
+    match labels.is_empty() {
+        true => String::from(name),
+        false => format!("{}{{{}}}", name, labels),
+    }
+}
+
+pub fn render() -> String {
+    let samples = SAMPLES.lock().unwrap_or_else(|err| err.into_inner());
+    let mut out = String::new();
+    for (name, kind, help) in METRICS {
+        let kind_name = match kind {
+            Kind::Counter => "counter",
+            Kind::Summary => "summary",
+        };
+        writeln!(out, "# HELP {} {}", name, help).unwrap_or(());
+        writeln!(out, "# TYPE {} {}", name, kind_name).unwrap_or(());
+        for ((_, labels), sample) in samples.iter().filter(|((n, _), _)| n == name) {
+            match kind {
+                Kind::Counter => {
+                    writeln!(out, "{} {}", series(name, labels), sample.count).unwrap_or(())
+                }
+                Kind::Summary => {
+                    let sum = format!("{}_sum", name);
+                    let count = format!("{}_count", name);
+                    writeln!(out, "{} {}", series(&sum, labels), sample.sum).unwrap_or(());
+                    writeln!(out, "{} {}", series(&count, labels), sample.count).unwrap_or(());
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_and_summaries() {
+        incr(COMMANDS, &[("command", "render-test")]);
+        incr(COMMANDS, &[("command", "render-test")]);
+        observe(
+            HTTP_REQUESTS,
+            &[("route", "render-\"test\"")],
+            time::Duration::from_millis(250),
+        );
+        let text = render();
+
+        assert!(text.contains("# TYPE pearls_commands_total counter\n"));
+        assert!(text.contains("pearls_commands_total{command=\"render-test\"} 2\n"));
+        assert!(text.contains(
+            "pearls_http_request_duration_seconds_sum{route=\"render-\\\"test\\\"\"} 0.25\n"
+        ));
+        assert!(text.contains(
+            "pearls_http_request_duration_seconds_count{route=\"render-\\\"test\\\"\"} 1\n"
+        ));
+    }
+}
@@ -13,6 +13,10 @@ pub enum ParseCommandError {
     DurationFormat,
     IdentFormat,
     ProjectIdentFormat,
+    QuoteFormat,
+    EmptyQuote,
+    UserFormat,
+    RoleFormat,
 }
 
 impl fmt::Display for ParseCommandError {
@@ -20,20 +24,69 @@ impl fmt::Display for ParseCommandError {
         match self {
             Self::NotFound => write!(f, "Command does not exists"),
             Self::Mysterious => write!(f, "Something bad happened..."),
-            Self::Unknown(name) => write!(f, "Something bad happened in `{}`...", name),
+            Self::Unknown(name) => {
+                write!(f, "I could not make sense of the arguments to !{}", name)
+            }
             Self::DateFormat => write!(f, "A date was not well encoded"),
             Self::DurationFormat => write!(f, "A duration was not well encoded"),
             Self::IdentFormat => write!(f, "An identifier was not working for me"),
             Self::ProjectIdentFormat => {
                 write!(f, "A project identifier was not working for me")
             }
+            Self::QuoteFormat => write!(f, "A quoted name was not closed"),
+            Self::EmptyQuote => write!(f, "A quoted name was empty"),
+            Self::UserFormat => write!(f, "A user was not well written"),
+            Self::RoleFormat => write!(f, "A role was not one I know"),
+        }
+    }
+}
+
+impl ParseCommandError {
+    /// What we were hoping to find where parsing failed.
+    pub fn expected(&self) -> Option<&'static str> {
+        match self {
+            Self::DateFormat => Some("a date like 2024-06-30 or 30/06"),
+            Self::DurationFormat => Some("a duration like 2h or 1h 30m"),
+            Self::IdentFormat => Some("a name made of letters, digits, '_', '-' or '.'"),
+            Self::ProjectIdentFormat => Some("a project name like client/project"),
+            Self::QuoteFormat => Some("a closing \""),
+            Self::EmptyQuote => Some("a name between the quotes"),
+            Self::UserFormat => Some("a user like @alice:matrix.org"),
+            Self::RoleFormat => Some("one of admin, member or viewer"),
+            _ => None,
+        }
+    }
+}
+
+/// A failed parse, with the command we were in when it failed, if any,
+/// and the byte offset in the input where things went wrong.
+#[derive(Debug, Clone)]
+pub struct CommandError {
+    pub command: Option<String>,
+    pub error: ParseCommandError,
+    pub position: Option<usize>,
+}
+
+impl CommandError {
+    pub fn expected(&self) -> Option<&'static str> {
+        self.error.expected()
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(expected) = self.expected() {
+            write!(f, ", expected {}", expected)?;
         }
+        Ok(())
     }
 }
 
 pub struct Context {
     pub command: Option<String>,
     pub error: Option<ParseCommandError>,
+    pub position: Option<usize>,
 }
 
 impl Context {
@@ -41,6 +94,7 @@ impl Context {
         Context {
             command: None,
             error: None,
+            position: None,
         }
     }
 
@@ -48,8 +102,9 @@ impl Context {
         self.command = Some(String::from(name));
     }
 
-    pub fn set_error(&mut self, err: ParseCommandError) {
+    pub fn set_error(&mut self, err: ParseCommandError, position: usize) {
         self.error = Some(err);
+        self.position = Some(position);
     }
 }
 
@@ -65,24 +120,44 @@ pub fn new_context() -> SharedContext {
     SharedContext::new(RefCell::new(Context::new()))
 }
 
-pub fn err_date_format(ctx: SharedContext) {
+pub fn err_date_format(ctx: SharedContext, position: usize) {
+    let mut ctx = ctx.borrow_mut();
+    ctx.set_error(ParseCommandError::DateFormat, position);
+}
+
+pub fn err_duration_format(ctx: SharedContext, position: usize) {
+    let mut ctx = ctx.borrow_mut();
+    ctx.set_error(ParseCommandError::DurationFormat, position);
+}
+
+pub fn err_ident(ctx: SharedContext, position: usize) {
+    let mut ctx = ctx.borrow_mut();
+    ctx.set_error(ParseCommandError::IdentFormat, position);
+}
+
+pub fn err_project_ident(ctx: SharedContext, position: usize) {
+    let mut ctx = ctx.borrow_mut();
+    ctx.set_error(ParseCommandError::ProjectIdentFormat, position);
+}
+
+pub fn err_quote(ctx: SharedContext, position: usize) {
     let mut ctx = ctx.borrow_mut();
-    ctx.set_error(ParseCommandError::DateFormat);
+    ctx.set_error(ParseCommandError::QuoteFormat, position);
 }
 
-pub fn err_duration_format(ctx: SharedContext) {
+pub fn err_empty_quote(ctx: SharedContext, position: usize) {
     let mut ctx = ctx.borrow_mut();
-    ctx.set_error(ParseCommandError::DurationFormat)
+    ctx.set_error(ParseCommandError::EmptyQuote, position);
 }
 
-pub fn err_ident(ctx: SharedContext) {
+pub fn err_user(ctx: SharedContext, position: usize) {
     let mut ctx = ctx.borrow_mut();
-    ctx.set_error(ParseCommandError::IdentFormat)
+    ctx.set_error(ParseCommandError::UserFormat, position);
 }
 
-pub fn err_project_ident(ctx: SharedContext) {
+pub fn err_role(ctx: SharedContext, position: usize) {
     let mut ctx = ctx.borrow_mut();
-    ctx.set_error(ParseCommandError::ProjectIdentFormat)
+    ctx.set_error(ParseCommandError::RoleFormat, position);
 }
 
 pub fn ctx_command(name: &str, ctx: SharedContext) {
@@ -104,15 +179,16 @@ where
     })
 }
 
+/// Runs `on_error` with the position the parser started at when it fails.
 pub fn with_error<'a, I, O, E>(parser: Parser<'a, I, O>, on_error: E) -> Parser<'a, I, O>
 where
     I: 'a + PartialEq + Debug,
     O: 'a,
-    E: 'a + Fn(),
+    E: 'a + Fn(usize),
 {
     Parser::new(move |input: &'a [I], start: usize| {
         (parser.method)(input, start).map_err(|err| {
-            on_error();
+            on_error(start);
             err
         })
     })
@@ -4,10 +4,22 @@ use std::{cmp::Ordering, collections::HashSet, fmt::Display, time::SystemTime};
 use chrono::{DateTime, Datelike, Duration, TimeZone, Weekday};
 
 use crate::{
-    store::{Avail, Intent, ProjectRecord, TaskRecord},
+    store::{Absence, Avail, DoneRecord, Intent, ProjectRecord},
     util::{date_time_from_st, st_from_date_time},
 };
 
+mod capacity;
+mod daily;
+mod strategy;
+mod whatif;
+
+pub use capacity::{capacity, client, iso_week, Balance, Capacity, UNASSIGNED};
+pub use daily::{daily_plan, DayLoad};
+pub use strategy::{
+    room_strategy, strategy, DeadlineFirst, PriorityFirst, Proportional, Strategy, STRATEGIES,
+};
+pub use whatif::{what_if, Baseline, Change, Finish, Hypothesis};
+
 // fn intents_for_user(project_name: &str, intents: &Vec<Intent>) {
 //     let ret = intents
 //         .iter()
@@ -61,41 +73,110 @@ fn work_day(dt: &chrono::DateTime<chrono::Local>) -> (SystemTime, SystemTime) {
     (st_from_date_time(&start), st_from_date_time(&end))
 }
 
-fn daily_avail(dt: &chrono::DateTime<chrono::Local>, avails: &Vec<&Avail>) -> u64 {
+/// The availability holding on the day of `dt`.
+///
+/// Periods may overlap, for instance a part-time contract starting in the
+/// middle of a year-long `!avail`: the one registered last wins for the days
+/// they share.
+fn avail_on<'a>(dt: &chrono::DateTime<chrono::Local>, avails: &[&'a Avail]) -> Option<&'a Avail> {
     let (start, end) = work_day(dt);
     avails
         .iter()
-        .filter_map(|a| {
-            if a.start_time < end && a.end_time > start {
-                Some(a.weekly.as_secs() / 5)
-            } else {
-                None
-            }
-        })
-        .min()
-        .unwrap_or(0)
+        .filter(|a| a.start_time < end && a.end_time > start)
+        .max_by_key(|a| a.id)
+        .copied()
 }
 
-fn working_days(dt: &DateTime<chrono::Local>) -> u64 {
-    match dt.weekday() {
-        Weekday::Mon => 5,
-        Weekday::Tue => 4,
-        Weekday::Wed => 3,
-        Weekday::Thu => 2,
-        Weekday::Fri => 1,
-        Weekday::Sat => 0,
-        Weekday::Sun => 0,
-    }
+/// The availability of `username` on the day of `at`.
+pub fn avail_of<'a>(username: &str, at: &SystemTime, avails: &'a [Avail]) -> Option<&'a Avail> {
+    let user_avails = avails
+        .iter()
+        .filter(|a| a.username == username)
+        .collect::<Vec<_>>();
+    avail_on(&date_time_from_st(at), &user_avails)
 }
 
-fn weekly_avail(start_time: &SystemTime, avails: &Vec<&Avail>) -> u64 {
+/// Share of the day of `dt` taken off, from nothing to all of it.
+fn day_off(dt: &chrono::DateTime<chrono::Local>, absences: &[&Absence]) -> f64 {
+    let (start, end) = work_day(dt);
+    absences
+        .iter()
+        .filter(|a| a.start_time < end && a.end_time > start)
+        .map(|a| a.portion)
+        .sum::<f64>()
+        .min(1.0)
+}
+
+fn daily_avail(
+    dt: &chrono::DateTime<chrono::Local>,
+    avails: &[&Avail],
+    absences: &[&Absence],
+) -> u64 {
+    let planned = avail_on(dt, avails).map_or(0, |a| a.pattern.day(dt.weekday()).as_secs());
+    (planned as f64 * (1.0 - day_off(dt, absences))).round() as u64
+}
+
+/// Days from `dt` to the end of its week, that day included.
+fn days_left(dt: &DateTime<chrono::Local>) -> u64 {
+    7 - u64::from(dt.weekday().num_days_from_monday())
+}
+
+fn weekly_avail(start_time: &SystemTime, avails: &[&Avail], absences: &[&Absence]) -> u64 {
     let start = date_time_from_st(start_time);
 
-    (0u64..working_days(&start))
-        .map(|i| daily_avail(&(start + (Duration::days(i as i64))), avails))
+    (0u64..days_left(&start))
+        .map(|i| daily_avail(&(start + (Duration::days(i as i64))), avails, absences))
         .sum()
 }
 
+/// Whole days from `dt` spent by `amount` seconds of work.
+fn days_spent(
+    dt: &DateTime<chrono::Local>,
+    amount: u64,
+    avails: &[&Avail],
+    absences: &[&Absence],
+) -> u64 {
+    let mut left = amount;
+    let mut days = 0;
+    while days < days_left(dt) {
+        let daily = daily_avail(&(*dt + Duration::days(days as i64)), avails, absences);
+        if left == 0 || left < daily {
+            break;
+        }
+        left -= daily;
+        days += 1;
+    }
+    days
+}
+
+/// The time `username` is expected to work from `start` to `end`, following
+/// their availability less their absences and the room holidays, a day at a
+/// time.
+pub fn expected(
+    username: &str,
+    start: &SystemTime,
+    end: &SystemTime,
+    avails: &[Avail],
+    absences: &[Absence],
+) -> time::Duration {
+    let user_avails = avails
+        .iter()
+        .filter(|a| a.username == username)
+        .collect::<Vec<_>>();
+    let user_absences = absences
+        .iter()
+        .filter(|a| a.applies_to(username))
+        .collect::<Vec<_>>();
+    let end = date_time_from_st(end);
+    let mut day = date_time_from_st(start);
+    let mut secs = 0;
+    while day < end {
+        secs += daily_avail(&day, &user_avails, &user_absences);
+        day = day + Duration::days(1);
+    }
+    time::Duration::from_secs(secs)
+}
+
 pub fn next_monday(dt: &DateTime<chrono::Local>) -> DateTime<chrono::Local> {
     match dt.weekday() {
         Weekday::Mon => *dt + Duration::days(7),
@@ -108,23 +189,31 @@ pub fn next_monday(dt: &DateTime<chrono::Local>) -> DateTime<chrono::Local> {
     }
 }
 
-fn sum_done(username: &str, project_name: &str, dones: &Vec<TaskRecord>) -> u64 {
+fn sum_done(username: &str, project_name: &str, dones: &[DoneRecord]) -> u64 {
     dones
         .iter()
-        .filter(|d| &d.project == project_name && &d.username == username)
-        .fold(0, |acc, rec| {
-            acc + rec
-                .end_time
-                .duration_since(rec.start_time)
-                .unwrap_or_else(|_| time::Duration::from_secs(0))
-                .as_secs()
-        })
+        .filter(|d| d.project == project_name && d.username == username)
+        .map(|d| d.duration.as_secs())
+        .sum()
 }
 
-fn find_intent<'a>(username: &str, project: &str, intents: &'a Vec<Intent>) -> Option<&'a Intent> {
+/// Whether `intent` has not ended by `at`.
+fn ongoing(intent: &Intent, at: &SystemTime) -> bool {
+    match intent.end_time {
+        Some(end) => end > *at,
+        None => true,
+    }
+}
+
+fn find_intent<'a>(
+    username: &str,
+    project: &str,
+    intents: &'a [Intent],
+    at: &SystemTime,
+) -> Option<&'a Intent> {
     intents
         .iter()
-        .find(|i| &i.username == username && &i.project == project)
+        .find(|i| i.username == username && i.project == project && ongoing(i, at))
 }
 
 #[derive(Debug)]
@@ -191,7 +280,88 @@ impl Display for WorkLoad {
     }
 }
 
-pub type WorkPlan = Vec<(String, Vec<(String, Vec<WorkLoad>)>)>;
+/// How far the planner looks ahead; what does not fit by then never ends.
+const HORIZON_WEEKS: i64 = 104;
+
+/// The weekly loads of a user on a project, and whether they end in time.
+#[derive(Debug)]
+pub struct Projection {
+    pub project: String,
+    pub loads: Vec<WorkLoad>,
+    /// The day the intent is spent and the next project can start, `None`
+    /// when it is not within the planning horizon or the intent ends first.
+    pub finish: Option<chrono::DateTime<chrono::Local>>,
+    pub deadline: Option<chrono::DateTime<chrono::Local>>,
+    /// When the intent ends before it is spent: the day it ends and the
+    /// work left then.
+    pub cut_short: Option<(chrono::DateTime<chrono::Local>, Duration)>,
+}
+
+impl Projection {
+    /// Days between the projected finish and the deadline, negative when
+    /// late, `None` without a deadline or a finish.
+    pub fn slack(&self) -> Option<Duration> {
+        match (self.finish, self.deadline) {
+            (Some(finish), Some(deadline)) => Some(deadline.date() - finish.date()),
+            _ => None,
+        }
+    }
+
+    /// Whether the intent can't fit before the deadline.
+    pub fn late(&self) -> bool {
+        match (self.finish, self.deadline) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(finish), Some(deadline)) => finish.date() > deadline.date(),
+        }
+    }
+
+    /// How the finish stands against the deadline, in a few words.
+    pub fn outlook(&self) -> String {
+        let days = |n: i64| match n.abs() {
+            1 => String::from("1 day"),
+            n => format!("{} days", n),
+        };
+        match (self.finish, self.slack()) {
+            (None, _) => match self.cut_short {
+                Some((end, left)) => format!(
+                    "intent ends {} with {}h left",
+                    end.format("%F"),
+                    left.num_hours()
+                ),
+                None => format!("does not fit within {} weeks", HORIZON_WEEKS),
+            },
+            (Some(_), None) => String::from("no deadline"),
+            (Some(_), Some(slack)) if self.late() => format!("{} late", days(slack.num_days())),
+            (Some(_), Some(slack)) => format!("{} of slack", days(slack.num_days())),
+        }
+    }
+}
+
+pub type WorkPlan = Vec<(String, Vec<Projection>)>;
+
+/// The projections of a plan, late ones first, then by deadline.
+pub fn projections(plan: &WorkPlan) -> Vec<(&str, &Projection)> {
+    let mut all = plan
+        .iter()
+        .flat_map(|(username, projections)| {
+            projections
+                .iter()
+                .map(move |projection| (username.as_str(), projection))
+        })
+        .collect::<Vec<_>>();
+    all.sort_by(|(_, a), (_, b)| {
+        b.late()
+            .cmp(&a.late())
+            .then_with(|| match (a.deadline, b.deadline) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.project.cmp(&b.project),
+            })
+    });
+    all
+}
 
 pub fn find_loads<'a>(
     plan: &'a WorkPlan,
@@ -201,8 +371,8 @@ pub fn find_loads<'a>(
     // println!("find_loads [{}; {}]", start.format("%F"), end.format("%F"));
     plan.iter()
         .flat_map(|(_, user_loads)| {
-            user_loads.iter().flat_map(|(_, loads)| {
-                loads.iter().filter(|&load| {
+            user_loads.iter().flat_map(|projection| {
+                projection.loads.iter().filter(|&load| {
                     // println!(
                     //     "{} > {} = {} && {} < {} = {}",
                     //     load.start.format("%F"),
@@ -219,87 +389,634 @@ pub fn find_loads<'a>(
         .collect()
 }
 
+/// What the planner knows of a user: when they are available, and from
+/// when to how far to plan.
+pub struct Schedule<'a> {
+    avails: Vec<&'a Avail>,
+    absences: Vec<&'a Absence>,
+    pub start: DateTime<chrono::Local>,
+    pub horizon: DateTime<chrono::Local>,
+}
+
+impl<'a> Schedule<'a> {
+    fn new(
+        username: &str,
+        avails: &'a [Avail],
+        absences: &'a [Absence],
+        start_time: SystemTime,
+    ) -> Schedule<'a> {
+        let start = date_time_from_st(&start_time);
+        Schedule {
+            avails: avails.iter().filter(|a| a.username == username).collect(),
+            absences: absences.iter().filter(|a| a.applies_to(username)).collect(),
+            start,
+            horizon: start + Duration::weeks(HORIZON_WEEKS),
+        }
+    }
+
+    /// Seconds available from `dt` to the end of its week.
+    pub fn week(&self, dt: &DateTime<chrono::Local>) -> u64 {
+        weekly_avail(&st_from_date_time(dt), &self.avails, &self.absences)
+    }
+
+    /// Seconds available from `dt` to the end of its week, or to the day of
+    /// `end` when it comes first.
+    pub fn week_until(
+        &self,
+        dt: &DateTime<chrono::Local>,
+        end: Option<&DateTime<chrono::Local>>,
+    ) -> u64 {
+        match end {
+            Some(end) if *end < next_monday(dt) => {
+                let days = (end.date() - dt.date()).num_days().max(0);
+                (0..days)
+                    .map(|i| daily_avail(&(*dt + Duration::days(i)), &self.avails, &self.absences))
+                    .sum()
+            }
+            _ => self.week(dt),
+        }
+    }
+
+    /// Whole days from `dt` spent by `amount` seconds of work.
+    pub fn days(&self, dt: &DateTime<chrono::Local>, amount: u64) -> Duration {
+        Duration::days(days_spent(dt, amount, &self.avails, &self.absences) as i64)
+    }
+}
+
+/// The effort left on a project for a user.
+pub struct Demand<'a> {
+    pub project: &'a ProjectRecord,
+    pub username: &'a str,
+    /// In seconds.
+    pub remaining: u64,
+    /// When the intent starts, no load is planned before then.
+    pub start: DateTime<chrono::Local>,
+    /// When the intent ends, no load is planned from then on.
+    pub end: Option<DateTime<chrono::Local>>,
+}
+
+impl<'a> Demand<'a> {
+    /// A demand is met when less than an hour is left.
+    pub fn met(&self) -> bool {
+        self.remaining <= 3600
+    }
+
+    /// Whether the intent still holds at `dt`.
+    pub fn open(&self, dt: &DateTime<chrono::Local>) -> bool {
+        match self.end {
+            Some(end) => *dt < end,
+            None => true,
+        }
+    }
+
+    /// Whether the intent holds by the end of the week of `dt`.
+    pub fn started(&self, dt: &DateTime<chrono::Local>) -> bool {
+        self.start < next_monday(dt)
+    }
+
+    /// Whether there is work left to plan in the week of `dt`.
+    pub fn pending(&self, dt: &DateTime<chrono::Local>) -> bool {
+        !self.met() && self.started(dt) && self.open(dt)
+    }
+
+    /// The projection of the demand once planned into `loads`, with what is
+    /// left of it then.
+    pub fn projection(
+        &self,
+        loads: Vec<WorkLoad>,
+        finish: Option<DateTime<chrono::Local>>,
+    ) -> Projection {
+        let cut_short = match (finish, self.end) {
+            (None, Some(end)) if !self.met() => {
+                Some((end, Duration::seconds(self.remaining as i64)))
+            }
+            _ => None,
+        };
+        Projection {
+            project: self.project.name.clone(),
+            loads,
+            finish,
+            deadline: self.project.end_time.map(|t| date_time_from_st(&t)),
+            cut_short,
+        }
+    }
+}
+
 pub fn plan_all(
-    projects: &Vec<ProjectRecord>,
-    intents: &Vec<Intent>,
-    avails: &Vec<Avail>,
-    dones: &Vec<TaskRecord>,
+    projects: &[ProjectRecord],
+    intents: &[Intent],
+    avails: &[Avail],
+    absences: &[Absence],
+    dones: &[DoneRecord],
     start_time: SystemTime,
+    strategy: &dyn Strategy,
 ) -> WorkPlan {
-    let mut open_projects = projects
+    let open_projects = projects
         .iter()
         .filter(|p| p.completed.is_none())
-        .map(|p| p.clone())
         .collect::<Vec<_>>();
-    open_projects.sort_by(cmp_by_deadline);
 
-    let user_loads = intents
+    intents
         .iter()
-        .filter(|i| i.end_time.map_or(true, |t| t > start_time))
+        .filter(|i| ongoing(i, &start_time))
         .fold(HashSet::new(), |mut acc, i| {
             acc.insert(i.username.clone());
             acc
         })
         .iter()
         .map(|username| {
-            let mut dt = date_time_from_st(&start_time);
-            let user_avails = avails
+            let schedule = Schedule::new(username, avails, absences, start_time);
+            let demands = open_projects
                 .iter()
-                .filter(|a| &a.username == username)
-                .collect::<Vec<_>>();
-            let loads =
-                open_projects
-                    .iter()
-                    .filter_map(|p| {
-                        find_intent(&username, &p.name, intents).map(|intent| {
-                            let partial = WorkLoad::partial(&username, &p.name);
-                            let mut loads: Vec<WorkLoad> = Vec::new();
-                            let done = sum_done(&username, &p.name, dones);
-                            if done < intent.amount.as_secs() {
-                                let mut remaining = intent.amount.as_secs() - done;
-                                // sparing an hour
-                                while remaining > 3600 {
-                                    let week_avail =
-                                        weekly_avail(&st_from_date_time(&dt), &user_avails);
-                                    if week_avail > remaining {
-                                        loads.push(partial.and_load(
-                                            dt.clone(),
-                                            Duration::seconds(remaining as i64),
-                                        ));
-
-                                        let consumed = {
-                                            let days = remaining * working_days(&dt) / week_avail;
-                                            Duration::days(days as i64)
-                                        };
-
-                                        dt = dt + consumed;
-                                        break;
-                                    } else {
-                                        loads.push(partial.and_load(
-                                            dt.clone(),
-                                            Duration::seconds(week_avail as i64),
-                                        ));
-                                        dt = next_monday(&dt);
-                                        remaining = remaining - week_avail;
-                                    };
-                                }
-                            }
-                            (p.name.clone(), loads)
-                        })
+                .filter_map(|p| {
+                    find_intent(username, &p.name, intents, &start_time).map(|intent| Demand {
+                        project: p,
+                        username,
+                        remaining: intent
+                            .amount
+                            .as_secs()
+                            .saturating_sub(sum_done(username, &p.name, dones)),
+                        start: date_time_from_st(&intent.start_time),
+                        end: intent.end_time.map(|t| date_time_from_st(&t)),
                     })
-                    .collect::<Vec<_>>();
+                })
+                .collect::<Vec<_>>();
 
-            (username.clone(), loads)
+            (username.clone(), strategy.plan(&schedule, demands))
         })
-        .collect::<Vec<_>>();
-
-    user_loads
+        .collect::<Vec<_>>()
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{plan::*, store::Store};
+    use crate::{
+        plan::*,
+        store::{Store, WeekPattern},
+    };
+
+    fn midnight(date: &str) -> SystemTime {
+        st_from_date_time(
+            &chrono::Local
+                .datetime_from_str(&format!("{} 00:00", date), "%F %R")
+                .unwrap(),
+        )
+    }
+
+    fn avail(id: i64, from: &str, to: &str, pattern: WeekPattern) -> Avail {
+        Avail {
+            id,
+            username: "@alice:localhost".into(),
+            start_time: midnight(from),
+            end_time: midnight(to),
+            weekly: pattern.weekly(),
+            pattern,
+        }
+    }
+
+    fn project(id: i64, name: &str, deadline: &str, priority: Option<i64>) -> ProjectRecord {
+        ProjectRecord {
+            id,
+            name: name.into(),
+            username: "@alice:localhost".into(),
+            start_time: midnight("2026-01-01"),
+            end_time: Some(midnight(deadline)),
+            provision: None,
+            completed: None,
+            is_meta: false,
+            parent: None,
+            priority,
+        }
+    }
+
+    fn intent(username: &str, project: &str, amount: time::Duration) -> Intent {
+        Intent {
+            id: 0,
+            username: username.into(),
+            project: project.into(),
+            amount,
+            start_time: midnight("2026-01-01"),
+            end_time: None,
+        }
+    }
+
+    #[test]
+    fn follows_the_week_pattern_of_the_latest_avail() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let full = avail(
+            1,
+            "2024-01-01",
+            "2024-12-31",
+            WeekPattern::spread(hours(40)),
+        );
+        let mut days = [hours(0); 7];
+        days[0] = hours(8);
+        days[1] = hours(8);
+        days[2] = hours(4);
+        let part_time = avail(2, "2024-03-01", "2024-06-30", WeekPattern(days));
+        let avails = vec![&part_time, &full];
+
+        assert_eq!(
+            weekly_avail(&midnight("2024-01-08"), &avails, &[]),
+            40 * 3600
+        );
+        assert_eq!(
+            weekly_avail(&midnight("2024-03-04"), &avails, &[]),
+            20 * 3600
+        );
+        // from the Wednesday on, only that day is left in the week
+        assert_eq!(
+            weekly_avail(&midnight("2024-03-06"), &avails, &[]),
+            4 * 3600
+        );
+        assert_eq!(
+            days_spent(
+                &date_time_from_st(&midnight("2024-03-04")),
+                16 * 3600,
+                &avails,
+                &[]
+            ),
+            2
+        );
+        assert_eq!(
+            weekly_avail(&midnight("2024-07-01"), &avails, &[]),
+            40 * 3600
+        );
+    }
+
+    #[test]
+    fn subtracts_absences_and_holidays() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let alice = avail(
+            1,
+            "2026-01-01",
+            "2027-12-31",
+            WeekPattern::spread(hours(40)),
+        );
+        let bob = Avail {
+            id: 2,
+            username: "@bob:localhost".into(),
+            ..alice.clone()
+        };
+        let avails = vec![alice, bob];
+        let off = |username: Option<&str>, from: &str, to: &str, portion: f64| Absence {
+            id: 0,
+            username: username.map(String::from),
+            start_time: midnight(from),
+            end_time: midnight(to),
+            portion,
+            label: String::new(),
+        };
+        let absences = vec![
+            off(None, "2026-12-25", "2026-12-26", 1.0),
+            off(Some("@alice:localhost"), "2026-12-21", "2026-12-22", 0.5),
+            off(Some("@bob:localhost"), "2026-12-22", "2026-12-24", 1.0),
+        ];
+        let week = |username| {
+            expected(
+                username,
+                &midnight("2026-12-21"),
+                &midnight("2026-12-28"),
+                &avails,
+                &absences,
+            )
+        };
+
+        assert_eq!(week("@alice:localhost"), hours(28));
+        assert_eq!(week("@bob:localhost"), hours(16));
+    }
+
+    #[test]
+    fn projects_finish_dates_against_deadlines() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let projects = vec![
+            project(1, "ac/early", "2026-01-09", None),
+            project(2, "ac/late", "2026-01-12", None),
+        ];
+        let intents = vec![
+            intent("@alice:localhost", "ac/early", hours(30)),
+            intent("@alice:localhost", "ac/late", hours(30)),
+            intent("@bob:localhost", "ac/early", hours(10)),
+        ];
+        let avails = vec![avail(
+            1,
+            "2026-01-01",
+            "2026-12-31",
+            WeekPattern::spread(hours(40)),
+        )];
+        let plan = plan_all(
+            &projects,
+            &intents,
+            &avails,
+            &[],
+            &[],
+            midnight("2026-01-05"),
+            &DeadlineFirst,
+        );
+        let find = |username: &str, project: &str| {
+            projections(&plan)
+                .into_iter()
+                .find(|(u, p)| *u == username && p.project == project)
+                .map(|(_, p)| {
+                    (
+                        p.finish.map(|f| f.format("%F").to_string()),
+                        p.slack(),
+                        p.late(),
+                    )
+                })
+        };
+
+        assert_eq!(
+            find("@alice:localhost", "ac/early"),
+            Some((Some("2026-01-08".into()), Some(Duration::days(1)), false))
+        );
+        assert_eq!(
+            find("@alice:localhost", "ac/late"),
+            Some((Some("2026-01-13".into()), Some(Duration::days(-1)), true))
+        );
+        assert_eq!(find("@bob:localhost", "ac/early"), Some((None, None, true)));
+        assert!(projections(&plan)[0].1.late());
+    }
+
+    #[test]
+    fn stops_the_loads_of_an_intent_when_it_ends() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let projects = vec![
+            project(1, "ac/ended", "2026-06-01", None),
+            project(2, "ac/short", "2026-06-01", None),
+        ];
+        let ending = |project: &str, end: &str| Intent {
+            end_time: Some(midnight(end)),
+            ..intent("@alice:localhost", project, hours(100))
+        };
+        let intents = vec![
+            ending("ac/ended", "2026-01-02"),
+            ending("ac/short", "2026-01-14"),
+        ];
+        let avails = vec![avail(
+            1,
+            "2026-01-01",
+            "2026-12-31",
+            WeekPattern::spread(hours(40)),
+        )];
+        let plan = plan_all(
+            &projects,
+            &intents,
+            &avails,
+            &[],
+            &[],
+            midnight("2026-01-05"),
+            &DeadlineFirst,
+        );
+        let all = projections(&plan);
+        let loads = all[0]
+            .1
+            .loads
+            .iter()
+            .map(|l| (l.start().format("%F").to_string(), l.load().num_hours()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].1.project, "ac/short");
+        assert_eq!(all[0].1.finish, None);
+        assert_eq!(all[0].1.outlook(), "intent ends 2026-01-14 with 44h left");
+        assert_eq!(
+            loads,
+            vec![("2026-01-05".into(), 40), ("2026-01-12".into(), 16)]
+        );
+    }
+
+    #[test]
+    fn waits_for_an_intent_to_start() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let projects = vec![
+            project(1, "ac/now", "2026-01-30", None),
+            project(2, "ac/later", "2026-02-27", None),
+        ];
+        let intents = vec![
+            intent("@alice:localhost", "ac/now", hours(20)),
+            Intent {
+                start_time: midnight("2026-01-14"),
+                ..intent("@alice:localhost", "ac/later", hours(60))
+            },
+        ];
+        let avails = vec![avail(
+            1,
+            "2026-01-01",
+            "2026-12-31",
+            WeekPattern::spread(hours(40)),
+        )];
+        let later = |strategy: &dyn Strategy| {
+            let plan = plan_all(
+                &projects,
+                &intents,
+                &avails,
+                &[],
+                &[],
+                midnight("2026-01-05"),
+                strategy,
+            );
+            plan[0]
+                .1
+                .iter()
+                .filter(|p| p.project == "ac/later")
+                .flat_map(|p| p.loads.iter())
+                .map(|l| (l.start().format("%F").to_string(), l.load().num_hours()))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![("2026-01-14".into(), 24), ("2026-01-19".into(), 36)];
+
+        assert_eq!(later(&DeadlineFirst), expected);
+        assert_eq!(later(&Proportional), expected);
+    }
+
+    #[test]
+    fn strategies_share_the_week_differently() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let projects = vec![
+            project(1, "ac/soon", "2026-01-30", Some(1)),
+            project(2, "ac/later", "2026-02-27", Some(3)),
+        ];
+        let intents = vec![
+            intent("@alice:localhost", "ac/soon", hours(20)),
+            intent("@alice:localhost", "ac/later", hours(20)),
+        ];
+        let avails = vec![avail(
+            1,
+            "2026-01-01",
+            "2026-12-31",
+            WeekPattern::spread(hours(40)),
+        )];
+        let finishes = |strategy: &dyn Strategy| {
+            let plan = plan_all(
+                &projects,
+                &intents,
+                &avails,
+                &[],
+                &[],
+                midnight("2026-01-05"),
+                strategy,
+            );
+            plan[0]
+                .1
+                .iter()
+                .map(|p| format!("{} {}", p.project, p.finish.unwrap().format("%F")))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            finishes(&DeadlineFirst),
+            vec!["ac/soon 2026-01-07", "ac/later 2026-01-09"]
+        );
+        assert_eq!(
+            finishes(&PriorityFirst),
+            vec!["ac/later 2026-01-07", "ac/soon 2026-01-09"]
+        );
+        assert_eq!(
+            finishes(strategy("proportional").unwrap().as_ref()),
+            vec!["ac/soon 2026-01-13", "ac/later 2026-01-08"]
+        );
+    }
+
+    #[test]
+    fn what_if_reports_what_slips() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let baseline = Baseline {
+            projects: vec![project(1, "ac/soon", "2026-01-08", None)],
+            intents: vec![intent("@alice:localhost", "ac/soon", hours(20))],
+            avails: vec![avail(
+                1,
+                "2026-01-01",
+                "2026-12-31",
+                WeekPattern::spread(hours(40)),
+            )],
+            absences: Vec::new(),
+            dones: Vec::new(),
+        };
+        let start = midnight("2026-01-05");
+        let new_contract = Hypothesis::Intent {
+            username: "@alice:localhost".into(),
+            project: "ac/new".into(),
+            amount: hours(20),
+            deadline: Some(midnight("2026-01-07")),
+        };
+
+        let changes = what_if(
+            &baseline,
+            std::slice::from_ref(&new_contract),
+            start,
+            &DeadlineFirst,
+        );
+        assert_eq!(
+            changes
+                .iter()
+                .map(|c| format!("{} {}", c.project, c.outlook()))
+                .collect::<Vec<_>>(),
+            vec!["ac/soon slips 2 days, late", "ac/new new"]
+        );
+
+        let overtime = Hypothesis::Avail {
+            username: "@alice:localhost".into(),
+            start: midnight("2026-01-01"),
+            end: midnight("2026-01-31"),
+            pattern: WeekPattern::spread(hours(80)),
+        };
+        let changes = what_if(&baseline, &[new_contract, overtime], start, &DeadlineFirst);
+        assert_eq!(changes[0].project, "ac/new");
+        assert!(changes.iter().all(|c| !c.late()));
+        assert!(what_if(&baseline, &[], start, &DeadlineFirst).is_empty());
+    }
+
+    #[test]
+    fn lays_the_plan_out_day_by_day() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let projects = vec![
+            project(1, "ac/soon", "2026-01-30", None),
+            project(2, "ac/later", "2026-02-27", None),
+        ];
+        let intents = vec![
+            intent("@alice:localhost", "ac/soon", hours(20)),
+            intent("@alice:localhost", "ac/later", hours(20)),
+        ];
+        let avails = vec![avail(
+            1,
+            "2026-01-01",
+            "2026-12-31",
+            WeekPattern::spread(hours(40)),
+        )];
+        let absences = vec![Absence {
+            id: 1,
+            username: Some("@alice:localhost".into()),
+            start_time: midnight("2026-01-08"),
+            end_time: midnight("2026-01-09"),
+            portion: 0.5,
+            label: String::new(),
+        }];
+        let plan = plan_all(
+            &projects,
+            &intents,
+            &avails,
+            &absences,
+            &[],
+            midnight("2026-01-05"),
+            &DeadlineFirst,
+        );
+        let days = daily_plan(&plan, &avails, &absences)
+            .iter()
+            .map(|d| {
+                format!(
+                    "{} {} {}h",
+                    d.start.format("%a %R"),
+                    d.project,
+                    d.load.num_hours()
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            days,
+            vec![
+                "Mon 10:00 ac/soon 8h",
+                "Tue 10:00 ac/soon 8h",
+                "Wed 10:00 ac/soon 4h",
+                "Wed 14:00 ac/later 4h",
+                "Thu 10:00 ac/later 4h",
+                "Fri 10:00 ac/later 8h",
+                "Mon 10:00 ac/later 4h",
+            ]
+        );
+    }
+
+    #[test]
+    fn weighs_capacity_against_demand() {
+        let hours = |h: u64| time::Duration::from_secs(h * 60 * 60);
+        let mut provisioned = project(2, "bc/web", "2026-01-09", None);
+        provisioned.provision = Some(hours(40));
+        let baseline = Baseline {
+            projects: vec![project(1, "ac/soon", "2026-01-16", None), provisioned],
+            intents: vec![intent("@alice:localhost", "ac/soon", hours(60))],
+            avails: vec![avail(
+                1,
+                "2026-01-01",
+                "2026-12-31",
+                WeekPattern::spread(hours(40)),
+            )],
+            absences: Vec::new(),
+            dones: Vec::new(),
+        };
+        let report = capacity(&baseline, midnight("2026-01-05"), 3);
+        let demand = |row: &[Balance]| row.iter().map(|b| b.demand / 3600).collect::<Vec<_>>();
+
+        assert_eq!(iso_week(&report.weeks[0]), "2026-W02");
+        assert_eq!(report.users[0].0, "@alice:localhost");
+        assert_eq!(demand(&report.users[0].1), vec![30, 30, 0]);
+        assert_eq!(report.users[1].0, UNASSIGNED);
+        assert_eq!(report.clients[0].0, "ac");
+        assert_eq!(demand(&report.clients[1].1), vec![40, 0, 0]);
+        assert!(report.total[0].overbooked());
+        assert_eq!(report.total[1].idle(), hours(10).as_secs());
+        assert_eq!(report.total[2].idle(), hours(40).as_secs());
+    }
 
     #[test]
     fn all_of_a_plan_is_possible() {
@@ -308,13 +1025,21 @@ mod tests {
         let projects = con.select_all_project_info().unwrap();
         let intents = con.select_intent_all().unwrap();
         let avails = con.select_avail_all().unwrap();
-        let dones = con.select_current_task().unwrap();
-        let plan = plan_all(&projects, &intents, &avails, &dones, SystemTime::now());
+        let dones = con.select_done_for_intents().unwrap();
+        let plan = plan_all(
+            &projects,
+            &intents,
+            &avails,
+            &[],
+            &dones,
+            SystemTime::now(),
+            &DeadlineFirst,
+        );
         for (name, user_loads) in plan {
             println!("{}", name);
-            for (project, loads) in user_loads {
-                println!("  {}", project);
-                for load in loads {
+            for projection in user_loads {
+                println!("  {}", projection.project);
+                for load in &projection.loads {
                     println!("    {}", load);
                 }
             }
@@ -327,13 +1052,21 @@ mod tests {
         let projects = con.select_all_project_info().unwrap();
         let intents = con.select_intent_all().unwrap();
         let avails = con.select_avail_all().unwrap();
-        let dones = con.select_current_task().unwrap();
-        let plan = plan_all(&projects, &intents, &avails, &dones, SystemTime::now());
+        let dones = con.select_done_for_intents().unwrap();
+        let plan = plan_all(
+            &projects,
+            &intents,
+            &avails,
+            &[],
+            &dones,
+            SystemTime::now(),
+            &DeadlineFirst,
+        );
         for (name, user_loads) in plan.iter() {
             println!("{}", name);
-            for (project, loads) in user_loads {
-                println!("  {}", project);
-                for load in loads {
+            for projection in user_loads {
+                println!("  {}", projection.project);
+                for load in &projection.loads {
                     println!("    {}", load);
                 }
             }
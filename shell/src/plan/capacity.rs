@@ -0,0 +1,196 @@
+//! Capacity against demand, week by week: what users are available for
+//! next to what open intents and provisions ask of them.
+
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone};
+
+use crate::util::{date_time_from_st, st_from_date_time};
+
+use super::{expected, ongoing, sum_done, Baseline};
+
+/// Name under which the part of a provision no intent covers is shown.
+pub const UNASSIGNED: &str = "unassigned";
+
+/// Seconds available and asked for in a week.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Balance {
+    pub capacity: u64,
+    pub demand: u64,
+}
+
+impl Balance {
+    pub fn overbooked(&self) -> bool {
+        self.demand > self.capacity
+    }
+
+    /// Capacity no demand takes.
+    pub fn idle(&self) -> u64 {
+        self.capacity.saturating_sub(self.demand)
+    }
+
+    fn add(&mut self, other: Balance) {
+        self.capacity += other.capacity;
+        self.demand += other.demand;
+    }
+}
+
+/// The balance of each week for the users, the clients and the room.
+/// Clients, the part of a project name before its `/`, have no capacity
+/// of their own.
+#[derive(Debug)]
+pub struct Capacity {
+    /// The Monday of each week.
+    pub weeks: Vec<NaiveDate>,
+    pub users: Vec<(String, Vec<Balance>)>,
+    pub clients: Vec<(String, Vec<Balance>)>,
+    pub total: Vec<Balance>,
+}
+
+/// `2026-W43`
+pub fn iso_week(monday: &NaiveDate) -> String {
+    let week = monday.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+fn monday(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+pub fn client(project: &str) -> &str {
+    project.split('/').next().unwrap_or(project)
+}
+
+/// Spreads `amount` seconds evenly over the weeks from `from` to the
+/// deadline, both included; without a deadline over all the weeks of the
+/// report, and into the week of `from` when the deadline is already past.
+fn spread(
+    row: &mut [Balance],
+    weeks: &[NaiveDate],
+    from: NaiveDate,
+    deadline: Option<NaiveDate>,
+    amount: u64,
+) {
+    let first = match weeks.iter().position(|w| *w >= monday(from)) {
+        Some(first) => first,
+        None => return,
+    };
+    let last = match deadline {
+        Some(deadline) => {
+            let span = (monday(deadline) - weeks[first]).num_weeks().max(0) as usize;
+            first + span
+        }
+        None => weeks.len() - 1,
+    };
+    let count = (last - first + 1) as u64;
+    for week in row.iter_mut().skip(first).take(last - first + 1) {
+        week.demand += amount / count;
+    }
+}
+
+/// The first moment of `date`, later than midnight on the days a clock
+/// change skips it.
+fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    (0..24)
+        .find_map(|hour| {
+            Local
+                .from_local_datetime(&date.and_hms(hour, 0, 0))
+                .earliest()
+        })
+        .unwrap_or_else(|| Local.from_utc_datetime(&date.and_hms(0, 0, 0)))
+}
+
+/// Capacity and demand over `count` weeks from the week of `start_time`.
+///
+/// Capacity is the availability of each user less their absences. Demand
+/// is what is left of each open intent, and the part of each provision no
+/// intent covers, spread evenly from now to the deadline of the project.
+pub fn capacity(baseline: &Baseline, start_time: SystemTime, count: usize) -> Capacity {
+    let today = date_time_from_st(&start_time).date().naive_local();
+    let weeks = (0..count.max(1))
+        .map(|i| monday(today) + Duration::weeks(i as i64))
+        .collect::<Vec<_>>();
+    let empty = vec![Balance::default(); weeks.len()];
+    let mut users: BTreeMap<String, Vec<Balance>> = BTreeMap::new();
+    let mut clients: BTreeMap<String, Vec<Balance>> = BTreeMap::new();
+
+    for avail in &baseline.avails {
+        users
+            .entry(avail.username.clone())
+            .or_insert_with(|| empty.clone());
+    }
+    for (username, row) in users.iter_mut() {
+        for (week, balance) in weeks.iter().zip(row.iter_mut()) {
+            let start = start_of_day(*week);
+            let end = start + Duration::weeks(1);
+            balance.capacity = expected(
+                username,
+                &st_from_date_time(&start.max(date_time_from_st(&start_time))),
+                &st_from_date_time(&end),
+                &baseline.avails,
+                &baseline.absences,
+            )
+            .as_secs();
+        }
+    }
+
+    let open = baseline
+        .projects
+        .iter()
+        .filter(|p| p.completed.is_none())
+        .collect::<Vec<_>>();
+    for project in open {
+        let deadline = project
+            .end_time
+            .map(|t| date_time_from_st(&t).date().naive_local());
+        let client_row = clients
+            .entry(String::from(client(&project.name)))
+            .or_insert_with(|| empty.clone());
+        let intents = baseline
+            .intents
+            .iter()
+            .filter(|i| i.project == project.name)
+            .filter(|i| ongoing(i, &start_time))
+            .collect::<Vec<_>>();
+        for intent in intents.iter() {
+            let remaining = intent.amount.as_secs().saturating_sub(sum_done(
+                &intent.username,
+                &project.name,
+                &baseline.dones,
+            ));
+            let from = date_time_from_st(&intent.start_time.max(start_time))
+                .date()
+                .naive_local();
+            let user_row = users
+                .entry(intent.username.clone())
+                .or_insert_with(|| empty.clone());
+            spread(user_row, &weeks, from, deadline, remaining);
+            spread(client_row, &weeks, from, deadline, remaining);
+        }
+        let covered: u64 = intents.iter().map(|i| i.amount.as_secs()).sum();
+        let uncovered = project
+            .provision
+            .map_or(0, |p| p.as_secs().saturating_sub(covered));
+        if uncovered > 0 {
+            let unassigned = users
+                .entry(String::from(UNASSIGNED))
+                .or_insert_with(|| empty.clone());
+            spread(unassigned, &weeks, today, deadline, uncovered);
+            spread(client_row, &weeks, today, deadline, uncovered);
+        }
+    }
+
+    let mut total = empty;
+    for row in users.values() {
+        for (sum, balance) in total.iter_mut().zip(row) {
+            sum.add(*balance);
+        }
+    }
+    Capacity {
+        weeks,
+        users: users.into_iter().collect(),
+        clients: clients.into_iter().collect(),
+        total,
+    }
+}
@@ -0,0 +1,81 @@
+//! The weekly loads of a plan laid out day by day, as they would go in a
+//! calendar.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+
+use crate::store::{Absence, Avail};
+
+use super::{daily_avail, WorkPlan, HORIZON_WEEKS};
+
+/// Hour at which the first load of a day starts.
+const DAY_START: u32 = 10;
+
+/// Time a user spends on a project on one day.
+#[derive(Debug, Clone)]
+pub struct DayLoad {
+    pub user: String,
+    pub project: String,
+    pub start: DateTime<Local>,
+    pub load: Duration,
+}
+
+impl DayLoad {
+    pub fn end(&self) -> DateTime<Local> {
+        self.start + self.load
+    }
+
+    pub fn day(&self) -> NaiveDate {
+        self.start.date().naive_local()
+    }
+}
+
+/// Splits the loads of each user into days, in the order they start, each
+/// day taking no more than the availability of the user less their
+/// absences; what a week can't take goes on to the next days.
+pub fn daily_plan(plan: &WorkPlan, avails: &[Avail], absences: &[Absence]) -> Vec<DayLoad> {
+    let mut days = Vec::new();
+    for (username, projections) in plan {
+        let user_avails = avails
+            .iter()
+            .filter(|a| &a.username == username)
+            .collect::<Vec<_>>();
+        let user_absences = absences
+            .iter()
+            .filter(|a| a.applies_to(username))
+            .collect::<Vec<_>>();
+        let mut loads = projections
+            .iter()
+            .flat_map(|projection| projection.loads.iter())
+            .collect::<Vec<_>>();
+        loads.sort_by_key(|load| *load.start());
+        // seconds already given on each day
+        let mut used: HashMap<NaiveDate, u64> = HashMap::new();
+
+        for load in loads {
+            let mut date = load.start().date();
+            let horizon = date + Duration::weeks(HORIZON_WEEKS);
+            let mut remaining = load.load().num_seconds().max(0) as u64;
+            while remaining > 0 && date < horizon {
+                let day = date.and_hms(DAY_START, 0, 0);
+                let taken = used.entry(date.naive_local()).or_insert(0);
+                let free = daily_avail(&day, &user_avails, &user_absences).saturating_sub(*taken);
+                let share = free.min(remaining);
+                if share > 0 {
+                    days.push(DayLoad {
+                        user: username.clone(),
+                        project: String::from(load.project()),
+                        start: day + Duration::seconds(*taken as i64),
+                        load: Duration::seconds(share as i64),
+                    });
+                    *taken += share;
+                    remaining -= share;
+                }
+                date = date.succ();
+            }
+        }
+    }
+    days.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.user.cmp(&b.user)));
+    days
+}
@@ -0,0 +1,180 @@
+//! How the time of a user is shared between the projects they intend to
+//! work on. A room picks one with `!strategy`.
+
+use std::cmp::Ordering;
+
+use chrono::Duration;
+
+use crate::store::{ConnectedStore, StoreResult};
+
+use super::{cmp_by_deadline, next_monday, Demand, Projection, Schedule, WorkLoad};
+
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// The projections of the demands of one user, in the order to show
+    /// them.
+    fn plan(&self, schedule: &Schedule, demands: Vec<Demand>) -> Vec<Projection>;
+}
+
+/// Names of the known strategies, the first being the default.
+pub const STRATEGIES: &[&str] = &["deadline", "priority", "proportional"];
+
+pub fn strategy(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "deadline" => Some(Box::new(DeadlineFirst)),
+        "priority" => Some(Box::new(PriorityFirst)),
+        "proportional" => Some(Box::new(Proportional)),
+        _ => None,
+    }
+}
+
+/// The strategy set for the room with `!strategy`, deadline first unless
+/// set.
+pub fn room_strategy(store: &ConnectedStore) -> StoreResult<Box<dyn Strategy>> {
+    store.select_setting("strategy").map(|name| {
+        name.and_then(|name| strategy(&name))
+            .unwrap_or_else(|| Box::new(DeadlineFirst))
+    })
+}
+
+/// Fills weeks with one project after the other, until each is done or its
+/// intent ends; a project whose intent starts later waits for it.
+fn in_order(schedule: &Schedule, demands: Vec<Demand>) -> Vec<Projection> {
+    let mut dt = schedule.start;
+    demands
+        .into_iter()
+        .map(|mut demand| {
+            let partial = WorkLoad::partial(demand.username, &demand.project.name);
+            let mut loads: Vec<WorkLoad> = Vec::new();
+            let mut remaining = demand.remaining;
+            if !demand.met() {
+                dt = dt.max(demand.start);
+            }
+            // sparing an hour
+            while remaining > 3600 && dt < schedule.horizon && demand.open(&dt) {
+                let week_avail = schedule.week_until(&dt, demand.end.as_ref());
+                if week_avail > remaining {
+                    loads.push(partial.and_load(dt, Duration::seconds(remaining as i64)));
+                    dt = dt + schedule.days(&dt, remaining);
+                    remaining = 0;
+                } else {
+                    loads.push(partial.and_load(dt, Duration::seconds(week_avail as i64)));
+                    dt = next_monday(&dt);
+                    remaining -= week_avail;
+                };
+            }
+            let finish = match remaining > 3600 {
+                true => None,
+                false => Some(dt),
+            };
+            demand.remaining = remaining;
+            demand.projection(loads, finish)
+        })
+        .collect()
+}
+
+/// Earliest deadline first, then earliest start.
+pub struct DeadlineFirst;
+
+impl Strategy for DeadlineFirst {
+    fn name(&self) -> &'static str {
+        "deadline"
+    }
+
+    fn plan(&self, schedule: &Schedule, mut demands: Vec<Demand>) -> Vec<Projection> {
+        demands.sort_by(|a, b| cmp_by_deadline(a.project, b.project));
+        in_order(schedule, demands)
+    }
+}
+
+/// Highest `!priority` first, projects without one last, ties broken by
+/// deadline.
+pub struct PriorityFirst;
+
+impl Strategy for PriorityFirst {
+    fn name(&self) -> &'static str {
+        "priority"
+    }
+
+    fn plan(&self, schedule: &Schedule, mut demands: Vec<Demand>) -> Vec<Projection> {
+        demands.sort_by(|a, b| match (a.project.priority, b.project.priority) {
+            (Some(pa), Some(pb)) if pa != pb => pb.cmp(&pa),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            _ => cmp_by_deadline(a.project, b.project),
+        });
+        in_order(schedule, demands)
+    }
+}
+
+/// Every week is shared between the projects not yet done whose intent
+/// holds that week, in proportion to their `!priority`, 1 when unset; what a project
+/// can't use is lost for that week.
+pub struct Proportional;
+
+impl Proportional {
+    fn weight(demand: &Demand) -> u64 {
+        demand.project.priority.unwrap_or(1).max(1) as u64
+    }
+}
+
+impl Strategy for Proportional {
+    fn name(&self) -> &'static str {
+        "proportional"
+    }
+
+    fn plan(&self, schedule: &Schedule, mut demands: Vec<Demand>) -> Vec<Projection> {
+        demands.sort_by(|a, b| cmp_by_deadline(a.project, b.project));
+        let mut loads: Vec<Vec<WorkLoad>> = demands.iter().map(|_| Vec::new()).collect();
+        let mut finish = demands
+            .iter()
+            .map(|demand| match demand.met() {
+                true => Some(schedule.start),
+                false => None,
+            })
+            .collect::<Vec<_>>();
+        let mut dt = schedule.start;
+
+        while dt < schedule.horizon
+            && demands
+                .iter()
+                .any(|demand| !demand.met() && demand.open(&dt))
+        {
+            let week_avail = schedule.week(&dt);
+            let total: u64 = demands
+                .iter()
+                .filter(|demand| demand.pending(&dt))
+                .map(Proportional::weight)
+                .sum();
+            for (index, demand) in demands.iter_mut().enumerate() {
+                if !demand.pending(&dt) {
+                    continue;
+                }
+                let from = dt.max(demand.start);
+                let share = week_avail * Proportional::weight(demand) / total;
+                let load = share
+                    .min(demand.remaining)
+                    .min(schedule.week_until(&from, demand.end.as_ref()));
+                loads[index].push(
+                    WorkLoad::partial(demand.username, &demand.project.name)
+                        .and_load(from, Duration::seconds(load as i64)),
+                );
+                demand.remaining -= load;
+                if demand.met() {
+                    // the share runs alongside the others, at their pace
+                    let spent = load * total / Proportional::weight(demand);
+                    finish[index] = Some(from + schedule.days(&from, spent));
+                }
+            }
+            dt = next_monday(&dt);
+        }
+
+        demands
+            .iter()
+            .zip(loads)
+            .zip(finish)
+            .map(|((demand, loads), finish)| demand.projection(loads, finish))
+            .collect()
+    }
+}
@@ -0,0 +1,252 @@
+//! What-if simulations: hypothetical intents and availability laid over
+//! the stored ones, and how the projected finish dates move.
+
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::store::{
+    Absence, Avail, ConnectedStore, DoneRecord, Intent, ProjectRecord, StoreResult, WeekPattern,
+};
+
+use super::{daily_plan, plan_all, DayLoad, Strategy, WorkPlan};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub enum Hypothesis {
+    /// `username` spends `amount` on `project` from the start of the
+    /// simulation, in place of a stored intent; the project is made up
+    /// when it does not exist, and gets `deadline` when given.
+    Intent {
+        username: String,
+        project: String,
+        amount: Duration,
+        deadline: Option<SystemTime>,
+    },
+    /// `username` is available with `pattern` from `start` to `end`, over
+    /// the stored availability.
+    Avail {
+        username: String,
+        start: SystemTime,
+        end: SystemTime,
+        pattern: WeekPattern,
+    },
+}
+
+/// Everything the planner reads from a room.
+#[derive(Clone)]
+pub struct Baseline {
+    pub projects: Vec<ProjectRecord>,
+    pub intents: Vec<Intent>,
+    pub avails: Vec<Avail>,
+    pub absences: Vec<Absence>,
+    pub dones: Vec<DoneRecord>,
+}
+
+impl Baseline {
+    pub fn load(store: &ConnectedStore) -> StoreResult<Baseline> {
+        Ok(Baseline {
+            projects: store.select_all_project_info()?,
+            intents: store.select_intent_all()?,
+            avails: store.select_avail_all()?,
+            absences: store.select_absence_all()?,
+            dones: store.select_done_for_intents()?,
+        })
+    }
+
+    pub fn plan(&self, start_time: SystemTime, strategy: &dyn Strategy) -> WorkPlan {
+        plan_all(
+            &self.projects,
+            &self.intents,
+            &self.avails,
+            &self.absences,
+            &self.dones,
+            start_time,
+            strategy,
+        )
+    }
+
+    /// The plan laid out day by day.
+    pub fn schedule(&self, start_time: SystemTime, strategy: &dyn Strategy) -> Vec<DayLoad> {
+        daily_plan(
+            &self.plan(start_time, strategy),
+            &self.avails,
+            &self.absences,
+        )
+    }
+
+    /// A copy with `hypotheses` applied in order.
+    pub fn overlay(&self, hypotheses: &[Hypothesis], start_time: SystemTime) -> Baseline {
+        let mut scenario = self.clone();
+        for hypothesis in hypotheses {
+            match hypothesis {
+                Hypothesis::Intent {
+                    username,
+                    project,
+                    amount,
+                    deadline,
+                } => {
+                    match scenario.projects.iter_mut().find(|p| &p.name == project) {
+                        Some(record) => {
+                            record.end_time = deadline.or(record.end_time);
+                        }
+                        None => {
+                            let id = scenario.projects.iter().map(|p| p.id).max().unwrap_or(0);
+                            scenario.projects.push(ProjectRecord {
+                                id: id + 1,
+                                name: project.clone(),
+                                username: username.clone(),
+                                start_time,
+                                end_time: *deadline,
+                                provision: None,
+                                completed: None,
+                                is_meta: false,
+                                parent: None,
+                                priority: None,
+                            });
+                        }
+                    }
+                    let same = |u: &str, p: &str| u == username && p == project;
+                    scenario.intents.retain(|i| !same(&i.username, &i.project));
+                    scenario.dones.retain(|d| !same(&d.username, &d.project));
+                    let id = scenario.intents.iter().map(|i| i.id).max().unwrap_or(0);
+                    scenario.intents.push(Intent {
+                        id: id + 1,
+                        username: username.clone(),
+                        project: project.clone(),
+                        amount: *amount,
+                        start_time,
+                        end_time: None,
+                    });
+                }
+                Hypothesis::Avail {
+                    username,
+                    start,
+                    end,
+                    pattern,
+                } => {
+                    // the latest avail wins where they overlap
+                    let id = scenario.avails.iter().map(|a| a.id).max().unwrap_or(0);
+                    scenario.avails.push(Avail {
+                        id: id + 1,
+                        username: username.clone(),
+                        start_time: *start,
+                        end_time: *end,
+                        weekly: pattern.weekly(),
+                        pattern: pattern.clone(),
+                    });
+                }
+            }
+        }
+        scenario
+    }
+}
+
+/// A projected finish, `None` when it does not fit within the horizon.
+pub type Finish = Option<DateTime<Local>>;
+
+/// How the projection of a user on a project moves in a simulation.
+#[derive(Debug)]
+pub struct Change {
+    pub username: String,
+    pub project: String,
+    /// The finish in the stored plan, `None` for an intent only the
+    /// simulation has.
+    pub before: Option<Finish>,
+    pub after: Finish,
+    pub deadline: Option<DateTime<Local>>,
+}
+
+impl Change {
+    /// Whether the simulated finish misses the deadline.
+    pub fn late(&self) -> bool {
+        match (self.after, self.deadline) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(after), Some(deadline)) => after.date() > deadline.date(),
+        }
+    }
+
+    /// How the finish moved, in a few words.
+    pub fn outlook(&self) -> String {
+        let days = |n: i64| match n.abs() {
+            1 => String::from("1 day"),
+            n => format!("{} days", n),
+        };
+        let moved = match (self.before, self.after) {
+            (None, _) => String::from("new"),
+            (Some(None), None) => String::from("still does not fit"),
+            (Some(None), Some(_)) => String::from("now fits"),
+            (Some(Some(_)), None) => String::from("no longer fits"),
+            (Some(Some(before)), Some(after)) => {
+                let delta = (after.date() - before.date()).num_days();
+                match delta > 0 {
+                    true => format!("slips {}", days(delta)),
+                    false => format!("gains {}", days(delta)),
+                }
+            }
+        };
+        match self.late() {
+            true => format!("{}, late", moved),
+            false => moved,
+        }
+    }
+}
+
+fn finishes(plan: &WorkPlan) -> Vec<(&str, &str, Finish, Option<DateTime<Local>>)> {
+    plan.iter()
+        .flat_map(|(username, projections)| {
+            projections.iter().map(move |projection| {
+                (
+                    username.as_str(),
+                    projection.project.as_str(),
+                    projection.finish,
+                    projection.deadline,
+                )
+            })
+        })
+        .collect()
+}
+
+/// The projections whose finish date differs once `hypotheses` are laid
+/// over `baseline`, late ones first.
+pub fn what_if(
+    baseline: &Baseline,
+    hypotheses: &[Hypothesis],
+    start_time: SystemTime,
+    strategy: &dyn Strategy,
+) -> Vec<Change> {
+    let before = baseline.plan(start_time, strategy);
+    let after = baseline
+        .overlay(hypotheses, start_time)
+        .plan(start_time, strategy);
+    let before = finishes(&before);
+    let same_day = |a: Finish, b: Finish| a.map(|a| a.date()) == b.map(|b| b.date());
+
+    let mut changes = finishes(&after)
+        .into_iter()
+        .filter_map(|(username, project, after, deadline)| {
+            let before = before
+                .iter()
+                .find(|(u, p, ..)| *u == username && *p == project)
+                .map(|(_, _, finish, _)| *finish);
+            match before {
+                Some(before) if same_day(before, after) => None,
+                _ => Some(Change {
+                    username: String::from(username),
+                    project: String::from(project),
+                    before,
+                    after,
+                    deadline,
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+    changes.sort_by(|a, b| {
+        b.late()
+            .cmp(&a.late())
+            .then_with(|| a.project.cmp(&b.project))
+            .then_with(|| a.username.cmp(&b.username))
+    });
+    changes
+}
@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS sync(
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    since TEXT
+);
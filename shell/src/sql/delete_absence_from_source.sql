@@ -0,0 +1,4 @@
+DELETE FROM
+    absence
+WHERE
+    source = :source;
\ No newline at end of file
@@ -0,0 +1,5 @@
+DELETE FROM
+    avail
+WHERE
+    id = :id
+    AND username = :username;
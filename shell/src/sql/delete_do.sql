@@ -0,0 +1,2 @@
+DELETE FROM command_do
+WHERE id = :id ;
@@ -0,0 +1,5 @@
+DELETE FROM
+    intent
+WHERE
+    username = :username
+    AND project = :project;
@@ -0,0 +1,2 @@
+DELETE FROM notif
+WHERE task_id = :id ;
@@ -0,0 +1,2 @@
+DELETE FROM pending
+WHERE username = :username ;
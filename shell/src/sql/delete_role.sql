@@ -0,0 +1,4 @@
+DELETE FROM
+    role
+WHERE
+    username = :username;
@@ -0,0 +1,4 @@
+INSERT INTO
+    absence (username, start_time, end_time, portion, label, source)
+VALUES
+    (:username, :start, :end, :portion, :label, :source);
\ No newline at end of file
@@ -1,4 +1,4 @@
 INSERT INTO
-    avail (username, start_time, end_time, weekly)
+    avail (username, start_time, end_time, weekly, pattern)
 VALUES
-    (:username, :start, :end, :weekly);
\ No newline at end of file
+    (:username, :start, :end, :weekly, :pattern);
\ No newline at end of file
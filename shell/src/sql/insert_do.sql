@@ -1,4 +1,4 @@
 INSERT INTO command_do
-    (username, start_time, end_time, project, task)
+    (username, start_time, end_time, project, task, entered_by, group_id, event_id)
 VALUES 
-    (:username, :start, :end, :project, :task);
\ No newline at end of file
+    (:username, :start, :end, :project, :task, :entered_by, :group_id, :event_id);
\ No newline at end of file
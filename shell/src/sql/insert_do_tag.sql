@@ -0,0 +1,9 @@
+INSERT
+    OR IGNORE INTO command_do_tag (do_id, tag_id)
+SELECT
+    :do_id,
+    id
+FROM
+    tag
+WHERE
+    name = :name;
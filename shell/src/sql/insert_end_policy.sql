@@ -0,0 +1,7 @@
+INSERT INTO
+    end_policy (username, policy, amount)
+VALUES
+    (:username, :policy, :amount) 
+ON CONFLICT(username) 
+DO UPDATE
+    SET policy = :policy, amount = :amount;
\ No newline at end of file
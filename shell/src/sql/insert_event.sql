@@ -0,0 +1,4 @@
+INSERT
+    OR IGNORE INTO event (id, received)
+VALUES
+    (:id, :received);
@@ -0,0 +1,8 @@
+INSERT INTO
+    feed (token, username, created_at)
+VALUES
+    (:token, :username, :created_at)
+ON CONFLICT(username)
+DO UPDATE
+    SET token = :token,
+    created_at = :created_at;
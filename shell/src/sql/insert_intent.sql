@@ -1,7 +1,9 @@
 INSERT INTO
-    intent (username, project, amount)
+    intent (username, project, amount, start_time)
 VALUES
-    (:username, :project, :amount) 
+    (:username, :project, :amount, :start) 
 ON CONFLICT(username, project) 
 DO UPDATE
-    SET amount = :amount;
\ No newline at end of file
+    SET amount = :amount,
+    start_time = COALESCE(:start, start_time),
+    end_time = NULL;
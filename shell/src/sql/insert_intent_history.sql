@@ -0,0 +1,25 @@
+INSERT INTO
+    intent_history (
+        username,
+        project,
+        amount,
+        start_time,
+        end_time,
+        change,
+        changed_by,
+        changed_at
+    )
+SELECT
+    username,
+    project,
+    amount,
+    start_time,
+    end_time,
+    :change,
+    :by,
+    :at
+FROM
+    intent
+WHERE
+    username = :username
+    AND project = :project;
@@ -0,0 +1,7 @@
+INSERT INTO
+    pending (username, body, created)
+VALUES
+    (:username, :body, :created)
+ON CONFLICT(username)
+DO UPDATE
+    SET body = :body, created = :created;
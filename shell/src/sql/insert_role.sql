@@ -0,0 +1,7 @@
+INSERT INTO
+    role (username, role)
+VALUES
+    (:username, :role)
+ON CONFLICT(username)
+DO UPDATE
+    SET role = :role;
@@ -0,0 +1,7 @@
+INSERT INTO
+    setting (name, value)
+VALUES
+    (:name, :value) 
+ON CONFLICT(name) 
+DO UPDATE
+    SET value = :value;
\ No newline at end of file
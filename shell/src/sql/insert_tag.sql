@@ -0,0 +1,4 @@
+INSERT
+    OR IGNORE INTO tag (name)
+VALUES
+    (:name);
@@ -0,0 +1,8 @@
+PRAGMA user_version = 7;
+
+CREATE TABLE IF NOT EXISTS end_policy(
+    id INTEGER PRIMARY KEY ASC,
+    username TEXT UNIQUE,
+    policy TEXT,
+    amount INTEGER
+);
\ No newline at end of file
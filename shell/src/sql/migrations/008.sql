@@ -0,0 +1,12 @@
+PRAGMA user_version = 8;
+
+CREATE TABLE IF NOT EXISTS tag(
+    id INTEGER PRIMARY KEY ASC,
+    name TEXT UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS command_do_tag(
+    do_id INTEGER REFERENCES command_do(id) ON DELETE CASCADE,
+    tag_id INTEGER REFERENCES tag(id) ON DELETE CASCADE,
+    PRIMARY KEY (do_id, tag_id)
+);
@@ -0,0 +1,7 @@
+PRAGMA user_version = 9;
+
+CREATE TABLE IF NOT EXISTS role(
+    id INTEGER PRIMARY KEY ASC,
+    username TEXT UNIQUE,
+    role TEXT
+);
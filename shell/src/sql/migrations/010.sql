@@ -0,0 +1,6 @@
+PRAGMA user_version = 10;
+
+CREATE TABLE IF NOT EXISTS event(
+    id TEXT PRIMARY KEY,
+    received INTEGER
+);
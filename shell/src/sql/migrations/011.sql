@@ -0,0 +1,4 @@
+PRAGMA user_version = 11;
+
+-- who typed the command when it was not the owner of the record
+ALTER TABLE command_do ADD COLUMN entered_by TEXT;
@@ -0,0 +1,4 @@
+PRAGMA user_version = 12;
+
+-- records of a task shared by several participants carry the same group
+ALTER TABLE command_do ADD COLUMN group_id TEXT;
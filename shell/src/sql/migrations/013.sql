@@ -0,0 +1,9 @@
+PRAGMA user_version = 13;
+
+-- a command waiting for its sender to reply !yes, one per user
+CREATE TABLE IF NOT EXISTS pending(
+    id INTEGER PRIMARY KEY ASC,
+    username TEXT UNIQUE,
+    body TEXT,
+    created INTEGER
+);
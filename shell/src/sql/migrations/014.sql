@@ -0,0 +1,4 @@
+PRAGMA user_version = 14;
+
+-- the chat event a record comes from, so that editing it can correct it
+ALTER TABLE command_do ADD COLUMN event_id TEXT;
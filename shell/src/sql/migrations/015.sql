@@ -0,0 +1,5 @@
+PRAGMA user_version = 15;
+
+-- milliseconds available on each day, Monday first, comma separated;
+-- NULL spreads the weekly amount over Monday to Friday
+ALTER TABLE avail ADD COLUMN pattern TEXT;
@@ -0,0 +1,13 @@
+PRAGMA user_version = 16;
+
+-- days off, for one user or, when username is NULL, the whole room;
+-- portion is the share of the day taken, source the holiday file it comes from
+CREATE TABLE IF NOT EXISTS absence(
+    id INTEGER PRIMARY KEY ASC,
+    username TEXT,
+    start_time INTEGER,
+    end_time INTEGER,
+    portion REAL NOT NULL DEFAULT 1.0,
+    label TEXT,
+    source TEXT
+);
@@ -0,0 +1,4 @@
+PRAGMA user_version = 17;
+
+-- when work starts counting towards an intent; NULL for the project start
+ALTER TABLE intent ADD COLUMN start_time INTEGER;
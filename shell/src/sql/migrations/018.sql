@@ -0,0 +1,10 @@
+PRAGMA user_version = 18;
+
+-- higher comes first with the priority strategy, weighs more when proportional
+ALTER TABLE project ADD COLUMN priority INTEGER;
+
+-- settings of the room, such as its planning strategy
+CREATE TABLE IF NOT EXISTS setting(
+    name TEXT PRIMARY KEY,
+    value TEXT
+);
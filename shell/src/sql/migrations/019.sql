@@ -0,0 +1,41 @@
+PRAGMA user_version = 19;
+
+-- when an intent was ended with !endintent, NULL while it runs
+ALTER TABLE intent ADD COLUMN end_time INTEGER;
+
+-- every change made to an intent, as the intent stood after it
+CREATE TABLE IF NOT EXISTS intent_history(
+    id INTEGER PRIMARY KEY ASC,
+    username TEXT,
+    project TEXT,
+    amount INTEGER,
+    start_time INTEGER,
+    end_time INTEGER,
+    -- set, end or remove
+    change TEXT,
+    changed_by TEXT,
+    changed_at INTEGER
+);
+
+INSERT INTO
+    intent_history (
+        username,
+        project,
+        amount,
+        start_time,
+        end_time,
+        change,
+        changed_by,
+        changed_at
+    )
+SELECT
+    username,
+    project,
+    amount,
+    start_time,
+    NULL,
+    'set',
+    username,
+    CAST(strftime('%s', 'now') AS INTEGER) * 1000
+FROM
+    intent;
@@ -0,0 +1,8 @@
+PRAGMA user_version = 20;
+
+-- the secret each user reads the calendar feeds of the room with
+CREATE TABLE IF NOT EXISTS feed(
+    token TEXT PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    created_at INTEGER NOT NULL
+);
@@ -0,0 +1,11 @@
+SELECT
+    id,
+    username,
+    start_time,
+    end_time,
+    portion,
+    label
+FROM
+    absence
+ORDER BY
+    start_time;
\ No newline at end of file
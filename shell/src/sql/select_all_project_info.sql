@@ -6,5 +6,6 @@ SELECT id,
     provision,
     completed,
     is_meta,
-    parent
+    parent,
+    priority
 FROM project;
\ No newline at end of file
@@ -3,7 +3,8 @@ SELECT
     username,
     start_time,
     end_time,
-    weekly
+    weekly,
+    pattern
 FROM
     avail
 WHERE
@@ -1,5 +1,5 @@
 SELECT 
-    id, username, start_time, end_time, project, task
+    id, username, start_time, end_time, project, task, entered_by, group_id
 FROM 
     command_do 
 WHERE
@@ -0,0 +1,6 @@
+SELECT 
+    id, username, start_time, end_time, project, task, entered_by, group_id
+FROM 
+    command_do 
+WHERE
+    event_id = :event_id;
@@ -0,0 +1,15 @@
+SELECT
+    i.username,
+    i.project,
+    SUM(MIN(d.end_time, :now) - d.start_time)
+FROM
+    intent AS i
+    LEFT JOIN project AS p ON i.project = p.name
+    JOIN command_do AS d ON d.username = i.username
+    AND d.project = i.project
+WHERE
+    d.start_time >= COALESCE(i.start_time, p.start_time, 0)
+    AND d.start_time < :now
+GROUP BY
+    i.username,
+    i.project;
\ No newline at end of file
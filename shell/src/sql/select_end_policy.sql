@@ -0,0 +1,6 @@
+SELECT
+    username,
+    policy,
+    amount
+FROM
+    end_policy;
\ No newline at end of file
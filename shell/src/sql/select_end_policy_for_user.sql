@@ -0,0 +1,8 @@
+SELECT
+    username,
+    policy,
+    amount
+FROM
+    end_policy
+WHERE
+    username = :user;
\ No newline at end of file
@@ -4,9 +4,11 @@ SELECT
   d.start_time,
   d.end_time,
   d.project,
-  d.task
+  d.task,
+  d.entered_by,
+  d.group_id
 FROM command_do as d
-LEFT JOIN notif as n ON d.id = n.task_id
+LEFT JOIN notif as n ON d.id = n.task_id AND d.end_time = n.end_time
 WHERE
   n.id IS NULL
   AND (
@@ -0,0 +1,8 @@
+SELECT
+    token,
+    username,
+    created_at
+FROM
+    feed
+WHERE
+    token = :token;
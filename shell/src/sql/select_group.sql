@@ -0,0 +1,7 @@
+SELECT 
+    id, username, start_time, end_time, project, task, entered_by, group_id
+FROM 
+    command_do 
+WHERE
+    id = :id
+    OR (group_id IS NOT NULL AND group_id = (SELECT group_id FROM command_do WHERE id = :id));
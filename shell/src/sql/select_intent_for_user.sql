@@ -3,8 +3,8 @@ SELECT
     i.username,
     i.project,
     i.amount,
-    p.start_time,
-    p.completed
+    COALESCE(i.start_time, p.start_time),
+    COALESCE(i.end_time, p.completed)
 FROM
     intent AS i
     LEFT JOIN project AS p ON i.project = p.name
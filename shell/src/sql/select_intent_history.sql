@@ -0,0 +1,24 @@
+SELECT
+    id,
+    username,
+    project,
+    amount,
+    start_time,
+    end_time,
+    change,
+    changed_by,
+    changed_at
+FROM
+    intent_history
+WHERE
+    (
+        :username IS NULL
+        OR username = :username
+    )
+    AND (
+        :project IS NULL
+        OR project = :project
+    )
+ORDER BY
+    changed_at,
+    id;
@@ -1,5 +1,5 @@
 SELECT 
-    id, username, start_time, end_time, project, task, max(end_time)
+    id, username, start_time, end_time, project, task, entered_by, group_id, max(end_time)
 FROM 
     command_do 
 WHERE
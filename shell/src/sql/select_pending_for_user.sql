@@ -0,0 +1,6 @@
+SELECT
+    username, body, created
+FROM
+    pending
+WHERE
+    username = :username;
@@ -4,8 +4,23 @@ SELECT
   start_time,
   end_time,
   project,
-  task
+  task,
+  entered_by,
+  group_id
 FROM
   command_do
 WHERE
-  project = :project;
\ No newline at end of file
+  project = :project
+  AND (
+    :tag IS NULL
+    OR EXISTS (
+      SELECT
+        1
+      FROM
+        command_do_tag dt
+        JOIN tag t ON t.id = dt.tag_id
+      WHERE
+        dt.do_id = command_do.id
+        AND t.name = :tag
+    )
+  );
@@ -6,7 +6,8 @@ SELECT id,
     provision,
     completed,
     is_meta,
-    parent
+    parent,
+    priority
 FROM project
 WHERE name = :project
 LIMIT 1;
\ No newline at end of file
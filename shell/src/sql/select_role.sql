@@ -0,0 +1,7 @@
+SELECT
+    username,
+    role
+FROM
+    role
+ORDER BY
+    username;
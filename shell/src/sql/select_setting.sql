@@ -0,0 +1,7 @@
+SELECT
+    name,
+    value
+FROM
+    setting
+WHERE
+    name = :name;
\ No newline at end of file
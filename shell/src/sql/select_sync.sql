@@ -0,0 +1,6 @@
+SELECT
+    since
+FROM
+    sync
+WHERE
+    id = 0;
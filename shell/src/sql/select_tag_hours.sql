@@ -0,0 +1,14 @@
+SELECT
+    t.name,
+    SUM(d.end_time - d.start_time)
+FROM
+    command_do d
+    JOIN command_do_tag dt ON dt.do_id = d.id
+    JOIN tag t ON t.id = dt.tag_id
+WHERE
+    d.start_time > :since
+    AND d.start_time < :until
+GROUP BY
+    t.name
+ORDER BY
+    SUM(d.end_time - d.start_time) DESC;
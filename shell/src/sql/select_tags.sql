@@ -0,0 +1,6 @@
+SELECT
+    name
+FROM
+    tag
+ORDER BY
+    name;
@@ -0,0 +1,9 @@
+SELECT
+    t.name
+FROM
+    tag t
+    JOIN command_do_tag dt ON dt.tag_id = t.id
+WHERE
+    dt.do_id = :do_id
+ORDER BY
+    t.name;
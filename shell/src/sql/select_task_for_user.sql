@@ -0,0 +1,16 @@
+SELECT
+    id,
+    username,
+    start_time,
+    end_time,
+    project,
+    task,
+    entered_by,
+    group_id
+FROM
+    command_do
+WHERE
+    username = :user
+    AND start_time > :since
+ORDER BY
+    start_time;
@@ -1,10 +1,30 @@
 SELECT 
-    project, username, task, start_time, end_time , SUM(end_time - start_time)
+    project, username, task, start_time, end_time , SUM(end_time - start_time),
+    GROUP_CONCAT(DISTINCT entered_by),
+    GROUP_CONCAT((
+        SELECT GROUP_CONCAT(o.username)
+        FROM command_do o
+        WHERE o.group_id = command_do.group_id
+            AND o.id != command_do.id
+    ))
 
 FROM 
     command_do 
 WHERE
     username = :user
     AND start_time > :since
+    AND (
+        :tag IS NULL
+        OR EXISTS (
+            SELECT
+                1
+            FROM
+                command_do_tag dt
+                JOIN tag t ON t.id = dt.tag_id
+            WHERE
+                dt.do_id = command_do.id
+                AND t.name = :tag
+        )
+    )
 GROUP BY 
-    project, task;
\ No newline at end of file
+    project, task;
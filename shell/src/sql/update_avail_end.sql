@@ -0,0 +1,8 @@
+UPDATE
+    avail
+SET
+    end_time = :end
+WHERE
+    id = :id
+    AND username = :username
+    AND end_time > :end;
@@ -0,0 +1,11 @@
+UPDATE
+    intent
+SET
+    end_time = :end
+WHERE
+    username = :username
+    AND project = :project
+    AND (
+        end_time IS NULL
+        OR end_time > :end
+    );
@@ -0,0 +1,6 @@
+UPDATE
+    project
+SET
+    priority = :priority
+WHERE
+    name = :name;
\ No newline at end of file
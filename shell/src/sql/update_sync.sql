@@ -0,0 +1,7 @@
+INSERT INTO
+    sync (id, since)
+VALUES
+    (0, :since)
+ON CONFLICT(id)
+DO UPDATE
+    SET since = :since;
@@ -1,10 +1,14 @@
-use crate::util::{dur, dur_from_ts, st_from_ts, ts};
+use crate::ical::Event;
+use crate::metrics;
+use crate::util::{dur, dur_from_ts, human_duration, st_from_ts, ts};
 use rusqlite::{named_params, Connection, Result as SqlResult, Row, ToSql};
 use serde::{Deserialize, Serialize};
 use std;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time;
+use tracing::{error, info};
+use uuid::Uuid;
 
 // struct SqlVec<T>(Vec<T>);
 
@@ -22,6 +26,10 @@ pub struct TaskRecord {
     pub end_time: time::SystemTime,
     pub project: String,
     pub task: String,
+    /// Who recorded it, when not the user themselves.
+    pub entered_by: Option<String>,
+    /// Shared by the records of a task done together.
+    pub group_id: Option<String>,
 }
 
 impl TaskRecord {
@@ -33,6 +41,8 @@ impl TaskRecord {
             end_time: st_from_ts(row.get(3)?),
             project: row.get(4)?,
             task: row.get(5)?,
+            entered_by: row.get(6)?,
+            group_id: row.get(7)?,
         })
     }
 }
@@ -45,6 +55,10 @@ pub struct AggregatedTaskRecord {
     pub start_time: time::SystemTime,
     pub end_time: time::SystemTime,
     pub duration: time::Duration,
+    /// Those who recorded some of it for the user.
+    pub entered_by: Vec<String>,
+    /// Those who shared some of it with the user.
+    pub with: Vec<String>,
 }
 
 impl AggregatedTaskRecord {
@@ -56,6 +70,35 @@ impl AggregatedTaskRecord {
             start_time: st_from_ts(row.get(3)?),
             end_time: st_from_ts(row.get(4)?),
             duration: dur_from_ts(row.get(5)?),
+            entered_by: row
+                .get::<usize, Option<String>>(6)?
+                .map(|names| names.split(',').map(String::from).collect())
+                .unwrap_or_default(),
+            with: row
+                .get::<usize, Option<String>>(7)?
+                .map(|names| {
+                    let mut names: Vec<String> = names.split(',').map(String::from).collect();
+                    names.sort();
+                    names.dedup();
+                    names
+                })
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Time spent on tasks carrying a tag.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TagRecord {
+    pub name: String,
+    pub duration: time::Duration,
+}
+
+impl TagRecord {
+    fn from_row(row: &Row) -> SqlResult<TagRecord> {
+        Ok(TagRecord {
+            name: row.get(0)?,
+            duration: dur_from_ts(row.get(1)?),
         })
     }
 }
@@ -72,6 +115,7 @@ pub struct ProjectRecord {
     pub completed: Option<time::SystemTime>,
     pub is_meta: bool,
     pub parent: Option<i64>,
+    pub priority: Option<i64>,
 }
 
 impl ProjectRecord {
@@ -86,6 +130,7 @@ impl ProjectRecord {
             completed: row.get(6).map(st_from_ts).ok(),
             is_meta: row.get(7).unwrap_or(false),
             parent: row.get(8).ok(),
+            priority: row.get(9).ok().flatten(),
         })
     }
 }
@@ -134,6 +179,74 @@ impl Intent {
     }
 }
 
+/// An intent as it stood after a change: `set` with `!intent`, `end` with
+/// `!endintent` or `remove` with `!rmintent`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntentChange {
+    pub id: i64,
+    pub username: String,
+    pub project: String,
+    pub amount: time::Duration,
+    /// `None` when it counts from the project start.
+    pub start_time: Option<time::SystemTime>,
+    pub end_time: Option<time::SystemTime>,
+    pub change: String,
+    pub changed_by: String,
+    pub changed_at: time::SystemTime,
+}
+
+impl IntentChange {
+    fn from_row(row: &Row) -> SqlResult<IntentChange> {
+        Ok(IntentChange {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            project: row.get(2)?,
+            amount: dur_from_ts(row.get(3)?),
+            start_time: row.get::<_, Option<i64>>(4)?.map(st_from_ts),
+            end_time: row.get::<_, Option<i64>>(5)?.map(st_from_ts),
+            change: row.get(6)?,
+            changed_by: row.get(7)?,
+            changed_at: st_from_ts(row.get(8)?),
+        })
+    }
+}
+
+/// Time a user has logged on a project since their intent started.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DoneRecord {
+    pub username: String,
+    pub project: String,
+    pub duration: time::Duration,
+}
+
+impl DoneRecord {
+    fn from_row(row: &Row) -> SqlResult<DoneRecord> {
+        Ok(DoneRecord {
+            username: row.get(0)?,
+            project: row.get(1)?,
+            duration: dur_from_ts(row.get::<_, i64>(2)?.max(0)),
+        })
+    }
+}
+
+/// The secret a user reads the calendar feeds of a room with.
+#[derive(Clone, Debug)]
+pub struct Feed {
+    pub token: String,
+    pub username: String,
+    pub created_at: time::SystemTime,
+}
+
+impl Feed {
+    fn from_row(row: &Row) -> SqlResult<Feed> {
+        Ok(Feed {
+            token: row.get(0)?,
+            username: row.get(1)?,
+            created_at: st_from_ts(row.get(2)?),
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Avail {
     pub id: i64,
@@ -141,30 +254,273 @@ pub struct Avail {
     pub start_time: time::SystemTime,
     pub end_time: time::SystemTime,
     pub weekly: time::Duration,
+    pub pattern: WeekPattern,
 }
 
 impl Avail {
     fn from_row(row: &Row) -> SqlResult<Avail> {
+        let weekly = dur_from_ts(row.get(4)?);
+        let pattern = match row.get::<_, Option<String>>(5)? {
+            Some(column) => WeekPattern::from_column(&column),
+            None => WeekPattern::spread(weekly),
+        };
         Ok(Avail {
             id: row.get(0)?,
             username: row.get(1)?,
             start_time: st_from_ts(row.get(2)?),
             end_time: st_from_ts(row.get(3)?),
-            weekly: dur_from_ts(row.get(4)?),
+            weekly,
+            pattern,
+        })
+    }
+}
+
+/// Time off, for a user or the whole room.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Absence {
+    pub id: i64,
+    /// `None` for a holiday of the whole room.
+    pub username: Option<String>,
+    pub start_time: time::SystemTime,
+    pub end_time: time::SystemTime,
+    /// Share of each day taken off, 1.0 for whole days.
+    pub portion: f64,
+    pub label: String,
+}
+
+impl Absence {
+    fn from_row(row: &Row) -> SqlResult<Absence> {
+        Ok(Absence {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            start_time: st_from_ts(row.get(2)?),
+            end_time: st_from_ts(row.get(3)?),
+            portion: row.get(4)?,
+            label: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn applies_to(&self, username: &str) -> bool {
+        match &self.username {
+            Some(user) => user == username,
+            None => true,
+        }
+    }
+}
+
+pub const WEEKDAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Time available on each day of the week, Monday first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct WeekPattern(pub [time::Duration; 7]);
+
+impl WeekPattern {
+    /// The weekly amount shared evenly between Monday and Friday, which is
+    /// what a plain `!avail` amount means.
+    pub fn spread(weekly: time::Duration) -> WeekPattern {
+        let day = weekly / 5;
+        WeekPattern([day, day, day, day, day, ZERO, ZERO])
+    }
+
+    pub fn day(&self, weekday: chrono::Weekday) -> time::Duration {
+        self.0[weekday.num_days_from_monday() as usize]
+    }
+
+    pub fn weekly(&self) -> time::Duration {
+        self.0.iter().sum()
+    }
+
+    fn from_column(column: &str) -> WeekPattern {
+        let mut days = [ZERO; 7];
+        for (day, millis) in days.iter_mut().zip(column.split(',')) {
+            *day = dur_from_ts(millis.trim().parse().unwrap_or(0));
+        }
+        WeekPattern(days)
+    }
+
+    fn to_column(&self) -> String {
+        self.0
+            .iter()
+            .map(|day| dur(day).to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl fmt::Display for WeekPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = WEEKDAYS
+            .iter()
+            .zip(self.0.iter())
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(name, amount)| {
+                let (hours, minutes) = (amount.as_secs() / 3600, amount.as_secs() / 60 % 60);
+                match minutes {
+                    0 => format!("{}={}h", name, hours),
+                    _ => format!("{}={}h{}m", name, hours, minutes),
+                }
+            })
+            .collect::<Vec<_>>();
+        match days.is_empty() {
+            true => write!(f, "none"),
+            false => write!(f, "{}", days.join(" ")),
+        }
+    }
+}
+
+const ZERO: time::Duration = time::Duration::from_secs(0);
+
+/// What happens when a `!do` task reaches its end time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub enum EndPolicy {
+    /// Push the end time by the given amount, again and again until `!stop`.
+    Extend(time::Duration),
+    /// Remind the user and let them `!more`; this is the default.
+    #[default]
+    Ask,
+    /// Let the task end without a word.
+    Silent,
+}
+
+impl EndPolicy {
+    fn from_row(row: &Row) -> SqlResult<EndPolicy> {
+        let policy: String = row.get(1)?;
+        match policy.as_str() {
+            "extend" => Ok(EndPolicy::Extend(dur_from_ts(row.get(2)?))),
+            "silent" => Ok(EndPolicy::Silent),
+            _ => Ok(EndPolicy::Ask),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            EndPolicy::Extend(_) => "extend",
+            EndPolicy::Ask => "ask",
+            EndPolicy::Silent => "silent",
+        }
+    }
+
+    fn amount(&self) -> time::Duration {
+        match self {
+            EndPolicy::Extend(d) => *d,
+            _ => time::Duration::from_secs(0),
+        }
+    }
+}
+
+impl fmt::Display for EndPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndPolicy::Extend(d) => write!(f, "extend by {}", human_duration(*d)),
+            EndPolicy::Ask => write!(f, "ask"),
+            EndPolicy::Silent => write!(f, "end silently"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EndPolicyRecord {
+    pub username: String,
+    pub policy: EndPolicy,
+}
+
+impl EndPolicyRecord {
+    fn from_row(row: &Row) -> SqlResult<EndPolicyRecord> {
+        Ok(EndPolicyRecord {
+            username: row.get(0)?,
+            policy: EndPolicy::from_row(row)?,
+        })
+    }
+}
+
+/// What a user may do in a room, each role allowing what the ones
+/// before it do.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    /// Reads reports.
+    Viewer,
+    /// Logs work and manages their own settings.
+    Member,
+    /// Changes projects and hands out roles.
+    Admin,
+}
+
+impl Role {
+    pub fn from_name(name: &str) -> Option<Role> {
+        match name {
+            "viewer" => Some(Role::Viewer),
+            "member" => Some(Role::Member),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Member => "member",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoleRecord {
+    pub username: String,
+    pub role: Role,
+}
+
+impl RoleRecord {
+    fn from_row(row: &Row) -> SqlResult<RoleRecord> {
+        let name: String = row.get(1)?;
+        Ok(RoleRecord {
+            username: row.get(0)?,
+            role: Role::from_name(&name).unwrap_or(Role::Member),
+        })
+    }
+}
+
+/// A command waiting for its sender to confirm it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PendingRecord {
+    pub username: String,
+    pub body: String,
+    pub created: time::SystemTime,
+}
+
+impl PendingRecord {
+    fn from_row(row: &Row) -> SqlResult<PendingRecord> {
+        Ok(PendingRecord {
+            username: row.get(0)?,
+            body: row.get(1)?,
+            created: st_from_ts(row.get(2)?),
         })
     }
 }
 
 pub struct ConnectedStore {
     room_id: String,
+    root_dir: String,
     conn: Connection,
+    /// The chat event being handled, kept with the records it creates.
+    event_id: Option<String>,
 }
 
 pub struct Store {
     root_dir: String,
     connections: Vec<ConnectedStore>,
+    sync: Option<Connection>,
 }
 
+/// Where the state shared by all rooms is kept, hidden next to room databases.
+const SYNC_DB: &str = ".sync.db";
+
 #[derive(Debug, Clone)]
 pub enum StoreError {
     Open(String),
@@ -199,12 +555,33 @@ pub enum Name {
     InsertNotification,
     InsertProject,
     InsertAvail,
+    InsertAbsence,
+    DeleteAbsenceFromSource,
     InsertIntent,
+    InsertIntentHistory,
+    DeleteIntent,
+    DeleteAvail,
+    InsertEndPolicy,
+    InsertTag,
+    InsertDoTag,
+    InsertRole,
+    DeleteRole,
+    InsertEvent,
+    DeleteDo,
+    DeletePending,
+    InsertPending,
+    InsertSetting,
+    InsertFeed,
+    DeleteNotificationForDo,
     SelectAllProjectInfo,
     SelectCurrentTask,
     SelectCurrentTaskFor,
     SelectEndingTask,
     SelectLatestTaskFor,
+    SelectTaskForUser,
+    SelectGroup,
+    SelectDoForEvent,
+    SelectPendingForUser,
     SelectNotes,
     SelectProject,
     SelectProjectDetail,
@@ -212,15 +589,32 @@ pub enum Name {
     SelectUser,
     SelectAvail,
     SelectAvailForUser,
+    SelectAbsence,
     SelectIntentAll,
     SelectIntentForProject,
     SelectIntentForUser,
+    SelectIntentHistory,
+    SelectDoneForIntents,
+    SelectEndPolicy,
+    SelectEndPolicyForUser,
+    SelectTags,
+    SelectTagsForDo,
+    SelectTagHours,
+    SelectRole,
+    SelectSync,
+    SelectSetting,
+    SelectFeed,
+    SelectFeedForUser,
     UpdateCompleted,
     UpdateDeadline,
     UpdateProvision,
+    UpdatePriority,
     UpdateTaskEnd,
+    UpdateIntentEnd,
+    UpdateAvailEnd,
     UpdateMeta,
     UpdateParent,
+    UpdateSync,
 }
 
 fn sql(name: Name) -> &'static str {
@@ -230,12 +624,33 @@ fn sql(name: Name) -> &'static str {
         Name::InsertNotification => include_str!("sql/insert_notification.sql"),
         Name::InsertProject => include_str!("sql/insert_project.sql"),
         Name::InsertAvail => include_str!("sql/insert_avail.sql"),
+        Name::InsertAbsence => include_str!("sql/insert_absence.sql"),
+        Name::DeleteAbsenceFromSource => include_str!("sql/delete_absence_from_source.sql"),
         Name::InsertIntent => include_str!("sql/insert_intent.sql"),
+        Name::InsertIntentHistory => include_str!("sql/insert_intent_history.sql"),
+        Name::DeleteIntent => include_str!("sql/delete_intent.sql"),
+        Name::DeleteAvail => include_str!("sql/delete_avail.sql"),
+        Name::InsertEndPolicy => include_str!("sql/insert_end_policy.sql"),
+        Name::InsertTag => include_str!("sql/insert_tag.sql"),
+        Name::InsertDoTag => include_str!("sql/insert_do_tag.sql"),
+        Name::InsertRole => include_str!("sql/insert_role.sql"),
+        Name::DeleteRole => include_str!("sql/delete_role.sql"),
+        Name::InsertEvent => include_str!("sql/insert_event.sql"),
+        Name::DeleteDo => include_str!("sql/delete_do.sql"),
+        Name::DeletePending => include_str!("sql/delete_pending.sql"),
+        Name::InsertPending => include_str!("sql/insert_pending.sql"),
+        Name::InsertSetting => include_str!("sql/insert_setting.sql"),
+        Name::InsertFeed => include_str!("sql/insert_feed.sql"),
+        Name::DeleteNotificationForDo => include_str!("sql/delete_notification_for_do.sql"),
         Name::SelectAllProjectInfo => include_str!("sql/select_all_project_info.sql"),
         Name::SelectCurrentTask => include_str!("sql/select_current_task.sql"),
         Name::SelectCurrentTaskFor => include_str!("sql/select_current_task_for.sql"),
         Name::SelectEndingTask => include_str!("sql/select_ending_task.sql"),
         Name::SelectLatestTaskFor => include_str!("sql/select_latest_task_for.sql"),
+        Name::SelectTaskForUser => include_str!("sql/select_task_for_user.sql"),
+        Name::SelectGroup => include_str!("sql/select_group.sql"),
+        Name::SelectDoForEvent => include_str!("sql/select_do_for_event.sql"),
+        Name::SelectPendingForUser => include_str!("sql/select_pending_for_user.sql"),
         Name::SelectNotes => include_str!("sql/select_notes.sql"),
         Name::SelectProject => include_str!("sql/select_project.sql"),
         Name::SelectProjectDetail => include_str!("sql/select_project_detail.sql"),
@@ -243,15 +658,32 @@ fn sql(name: Name) -> &'static str {
         Name::SelectUser => include_str!("sql/select_user.sql"),
         Name::SelectAvail => include_str!("sql/select_avail.sql"),
         Name::SelectAvailForUser => include_str!("sql/select_avail_for_user.sql"),
+        Name::SelectAbsence => include_str!("sql/select_absence.sql"),
         Name::SelectIntentAll => include_str!("sql/select_intent_all.sql"),
         Name::SelectIntentForProject => include_str!("sql/select_intent_for_project.sql"),
         Name::SelectIntentForUser => include_str!("sql/select_intent_for_user.sql"),
+        Name::SelectIntentHistory => include_str!("sql/select_intent_history.sql"),
+        Name::SelectDoneForIntents => include_str!("sql/select_done_for_intents.sql"),
+        Name::SelectEndPolicy => include_str!("sql/select_end_policy.sql"),
+        Name::SelectEndPolicyForUser => include_str!("sql/select_end_policy_for_user.sql"),
+        Name::SelectTags => include_str!("sql/select_tags.sql"),
+        Name::SelectTagsForDo => include_str!("sql/select_tags_for_do.sql"),
+        Name::SelectTagHours => include_str!("sql/select_tag_hours.sql"),
+        Name::SelectRole => include_str!("sql/select_role.sql"),
+        Name::SelectSync => include_str!("sql/select_sync.sql"),
+        Name::SelectSetting => include_str!("sql/select_setting.sql"),
+        Name::SelectFeed => include_str!("sql/select_feed.sql"),
+        Name::SelectFeedForUser => include_str!("sql/select_feed_for_user.sql"),
         Name::UpdateCompleted => include_str!("sql/update_completed.sql"),
         Name::UpdateDeadline => include_str!("sql/update_deadline.sql"),
         Name::UpdateProvision => include_str!("sql/update_provision.sql"),
+        Name::UpdatePriority => include_str!("sql/update_priority.sql"),
         Name::UpdateTaskEnd => include_str!("sql/update_task_end.sql"),
+        Name::UpdateIntentEnd => include_str!("sql/update_intent_end.sql"),
+        Name::UpdateAvailEnd => include_str!("sql/update_avail_end.sql"),
         Name::UpdateMeta => include_str!("sql/update_meta.sql"),
         Name::UpdateParent => include_str!("sql/update_parent.sql"),
+        Name::UpdateSync => include_str!("sql/update_sync.sql"),
     }
 }
 
@@ -266,53 +698,178 @@ fn migrate(conn: &Connection) {
         0 => {
             conn.execute_batch(include_str!("sql/migrations/001.sql"))
                 .expect("Failed migration: 001.sql");
-            println!("Applied sql/migrations/001.sql");
+            info!("Applied sql/migrations/001.sql");
             migrate(conn);
         }
         1 => {
             conn.execute_batch(include_str!("sql/migrations/002.sql"))
                 .expect("Failed migration: 002.sql");
-            println!("Applied sql/migrations/002.sql");
+            info!("Applied sql/migrations/002.sql");
             migrate(conn);
         }
         2 => {
             conn.execute_batch(include_str!("sql/migrations/003.sql"))
                 .expect("Failed migration: 003.sql");
-            println!("Applied sql/migrations/003.sql");
+            info!("Applied sql/migrations/003.sql");
             migrate(conn);
         }
         3 => {
             conn.execute_batch(include_str!("sql/migrations/004.sql"))
                 .expect("Failed migration: 004.sql");
-            println!("Applied sql/migrations/004.sql");
+            info!("Applied sql/migrations/004.sql");
             migrate(conn);
         }
         4 => {
             conn.execute_batch(include_str!("sql/migrations/005.sql"))
                 .expect("Failed migration: 005.sql");
-            println!("Applied sql/migrations/005.sql");
+            info!("Applied sql/migrations/005.sql");
             migrate(conn);
         }
         5 => {
             conn.execute_batch(include_str!("sql/migrations/006.sql"))
                 .expect("Failed migration: 006.sql");
-            println!("Applied sql/migrations/006.sql");
+            info!("Applied sql/migrations/006.sql");
+            migrate(conn);
+        }
+        6 => {
+            conn.execute_batch(include_str!("sql/migrations/007.sql"))
+                .expect("Failed migration: 007.sql");
+            info!("Applied sql/migrations/007.sql");
+            migrate(conn);
+        }
+        7 => {
+            conn.execute_batch(include_str!("sql/migrations/008.sql"))
+                .expect("Failed migration: 008.sql");
+            info!("Applied sql/migrations/008.sql");
+            migrate(conn);
+        }
+        8 => {
+            conn.execute_batch(include_str!("sql/migrations/009.sql"))
+                .expect("Failed migration: 009.sql");
+            info!("Applied sql/migrations/009.sql");
             migrate(conn);
         }
-        _ => println!("Migrate completed, we're at version {}", user_version),
+        9 => {
+            conn.execute_batch(include_str!("sql/migrations/010.sql"))
+                .expect("Failed migration: 010.sql");
+            info!("Applied sql/migrations/010.sql");
+            migrate(conn);
+        }
+        10 => {
+            conn.execute_batch(include_str!("sql/migrations/011.sql"))
+                .expect("Failed migration: 011.sql");
+            info!("Applied sql/migrations/011.sql");
+            migrate(conn);
+        }
+        11 => {
+            conn.execute_batch(include_str!("sql/migrations/012.sql"))
+                .expect("Failed migration: 012.sql");
+            info!("Applied sql/migrations/012.sql");
+            migrate(conn);
+        }
+        12 => {
+            conn.execute_batch(include_str!("sql/migrations/013.sql"))
+                .expect("Failed migration: 013.sql");
+            info!("Applied sql/migrations/013.sql");
+            migrate(conn);
+        }
+        13 => {
+            conn.execute_batch(include_str!("sql/migrations/014.sql"))
+                .expect("Failed migration: 014.sql");
+            info!("Applied sql/migrations/014.sql");
+            migrate(conn);
+        }
+        14 => {
+            conn.execute_batch(include_str!("sql/migrations/015.sql"))
+                .expect("Failed migration: 015.sql");
+            info!("Applied sql/migrations/015.sql");
+            migrate(conn);
+        }
+        15 => {
+            conn.execute_batch(include_str!("sql/migrations/016.sql"))
+                .expect("Failed migration: 016.sql");
+            info!("Applied sql/migrations/016.sql");
+            migrate(conn);
+        }
+        16 => {
+            conn.execute_batch(include_str!("sql/migrations/017.sql"))
+                .expect("Failed migration: 017.sql");
+            info!("Applied sql/migrations/017.sql");
+            migrate(conn);
+        }
+        17 => {
+            conn.execute_batch(include_str!("sql/migrations/018.sql"))
+                .expect("Failed migration: 018.sql");
+            info!("Applied sql/migrations/018.sql");
+            migrate(conn);
+        }
+        18 => {
+            conn.execute_batch(include_str!("sql/migrations/019.sql"))
+                .expect("Failed migration: 019.sql");
+            info!("Applied sql/migrations/019.sql");
+            migrate(conn);
+        }
+        19 => {
+            conn.execute_batch(include_str!("sql/migrations/020.sql"))
+                .expect("Failed migration: 020.sql");
+            info!("Applied sql/migrations/020.sql");
+            migrate(conn);
+        }
+        _ => info!("Migrate completed, we're at version {}", user_version),
     };
 }
 
 pub type StoreResult<T> = Result<T, StoreError>;
 
+fn sql_error(err: &rusqlite::Error) {
+    error!("SQLite error: {}", err);
+    metrics::incr(metrics::STORE_ERRORS, &[]);
+}
+
 impl Store {
     pub fn new(root_dir: String) -> Store {
         Store {
             root_dir,
             connections: vec![],
+            sync: None,
+        }
+    }
+
+    fn sync_connection(&mut self) -> StoreResult<&Connection> {
+        if self.sync.is_none() {
+            let path = Path::new(&self.root_dir).join(SYNC_DB);
+            let conn = Connection::open(path).map_err(|_| StoreError::Open(SYNC_DB.into()))?;
+            conn.execute(include_str!("sql/create_sync.sql"), [])
+                .map_err(|_| StoreError::Open(SYNC_DB.into()))?;
+            self.sync = Some(conn);
+        }
+        self.sync
+            .as_ref()
+            .ok_or_else(|| StoreError::Connected(SYNC_DB.into()))
+    }
+
+    /// The token the last sync with the homeserver ended on, if any.
+    pub fn select_sync_token(&mut self) -> StoreResult<Option<String>> {
+        let conn = self.sync_connection()?;
+        match conn.query_row(sql(Name::SelectSync), [], |row| row.get(0)) {
+            Ok(since) => Ok(Some(since)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => {
+                sql_error(&err);
+                Err(StoreError::Get)
+            }
         }
     }
 
+    pub fn update_sync_token(&mut self, since: &str) -> StoreResult<usize> {
+        let conn = self.sync_connection()?;
+        conn.execute(sql(Name::UpdateSync), named_params! {":since": since})
+            .map_err(|err| {
+                sql_error(&err);
+                StoreError::LogRecord
+            })
+    }
+
     pub fn connect_or_create(&mut self, db_name: &str) -> StoreResult<&mut ConnectedStore> {
         let exists = self
             .connections
@@ -341,6 +898,8 @@ impl Store {
         self.connections.push(ConnectedStore {
             conn,
             room_id: db_name.into(),
+            root_dir: self.root_dir.clone(),
+            event_id: None,
         });
         self.connections
             .last_mut()
@@ -363,6 +922,8 @@ impl Store {
             self.connections.push(ConnectedStore {
                 conn,
                 room_id: db_name.into(),
+                root_dir: self.root_dir.clone(),
+                event_id: None,
             });
             self.connections
                 .last_mut()
@@ -379,6 +940,32 @@ impl Store {
             .ok_or_else(|| StoreError::Connected(db_name.into()))
     }
 
+    /// The room `token` opens the feeds of, along with the feed, looking
+    /// through every room database of the store.
+    pub fn find_feed(&mut self, token: &str) -> StoreResult<Option<(String, Feed)>> {
+        let rooms: Vec<String> = std::fs::read_dir(&self.root_dir)
+            .map_err(|_| StoreError::Open(self.root_dir.clone()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| {
+                !name.starts_with('.')
+                    && !["-journal", "-wal", "-shm"]
+                        .iter()
+                        .any(|suffix| name.ends_with(suffix))
+            })
+            .collect();
+        for room in rooms {
+            let found = self
+                .connect_existing(&room)
+                .and_then(|conn| conn.select_feed(token));
+            if let Ok(Some(feed)) = found {
+                return Ok(Some((room, feed)));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ConnectedStore> {
         self.connections.iter_mut()
     }
@@ -393,7 +980,7 @@ impl ConnectedStore {
         match self.conn.execute(sql(name), params) {
             Ok(s) => Ok(s),
             Err(err) => {
-                println!("SQLite error: {}", err);
+                sql_error(&err);
                 Err(StoreError::LogRecord)
             }
         }
@@ -404,18 +991,18 @@ impl ConnectedStore {
     {
         match self.conn.prepare(sql(name)) {
             Err(err) => {
-                println!("SQLite error: {}", err);
+                sql_error(&err);
                 Err(StoreError::Iter)
             }
             Ok(mut stmt) => match stmt.query_map(params, f) {
                 Err(err) => {
-                    println!("SQLite error: {}", err);
+                    sql_error(&err);
                     Err(StoreError::Iter)
                 }
                 Ok(rows) => Ok(rows
                     .filter_map(|row| match row {
                         Err(err) => {
-                            println!("Row Error: {}", err);
+                            sql_error(&err);
                             None
                         }
                         Ok(_) => row.ok(),
@@ -425,6 +1012,9 @@ impl ConnectedStore {
         }
     }
 
+    /// Records work of `user`, `entered_by` being whoever typed the command
+    /// if someone else.
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_do(
         &mut self,
         user: String,
@@ -432,17 +1022,113 @@ impl ConnectedStore {
         end: time::SystemTime,
         project: String,
         task: String,
+        tags: &[String],
+        entered_by: Option<String>,
     ) -> StoreResult<usize> {
-        self.exec(
-            Name::InsertDo,
-            named_params! {
-                ":username": user,
-                ":start": ts(&start),
-                ":end": ts(&end),
-                ":project": project,
-                ":task": task,
-            },
-        )
+        self.insert_do_in(user, start, end, &project, &task, tags, entered_by, None)
+    }
+
+    /// Records the same work for each of `users`, given as owner and who
+    /// entered it, linked by a new group id.
+    pub fn insert_do_group(
+        &mut self,
+        users: &[(String, Option<String>)],
+        start: time::SystemTime,
+        end: time::SystemTime,
+        project: String,
+        task: String,
+        tags: &[String],
+    ) -> StoreResult<usize> {
+        let group_id = Uuid::new_v4().to_string();
+        self.atomic(|store| {
+            users.iter().try_fold(0, |inserted, (user, entered_by)| {
+                store
+                    .insert_do_in(
+                        user.clone(),
+                        start,
+                        end,
+                        &project,
+                        &task,
+                        tags,
+                        entered_by.clone(),
+                        Some(&group_id),
+                    )
+                    .map(|n| inserted + n)
+            })
+        })
+    }
+
+    /// Runs `f` within a savepoint, so that what it writes is kept whole or
+    /// not at all, whether or not a transaction is already open.
+    pub fn atomic<T, F>(&mut self, f: F) -> StoreResult<T>
+    where
+        F: FnOnce(&mut ConnectedStore) -> StoreResult<T>,
+    {
+        self.tentative(|store| f(store).map(|value| (value, true)))
+    }
+
+    /// Like `atomic`, but `f` also tells whether to keep what it wrote, all
+    /// of it being rolled back otherwise.
+    pub fn tentative<T, F>(&mut self, f: F) -> StoreResult<T>
+    where
+        F: FnOnce(&mut ConnectedStore) -> StoreResult<(T, bool)>,
+    {
+        self.conn.execute_batch("SAVEPOINT atomic").map_err(|err| {
+            sql_error(&err);
+            StoreError::LogRecord
+        })?;
+        let result = f(self);
+        let end = match result {
+            Ok((_, true)) => "RELEASE atomic",
+            _ => "ROLLBACK TO atomic; RELEASE atomic",
+        };
+        self.conn.execute_batch(end).map_err(|err| {
+            sql_error(&err);
+            StoreError::LogRecord
+        })?;
+        result.map(|(value, _)| value)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert_do_in(
+        &mut self,
+        user: String,
+        start: time::SystemTime,
+        end: time::SystemTime,
+        project: &str,
+        task: &str,
+        tags: &[String],
+        entered_by: Option<String>,
+        group_id: Option<&str>,
+    ) -> StoreResult<usize> {
+        let event_id = self.event_id.clone();
+        self.atomic(|store| {
+            let inserted = store.exec(
+                Name::InsertDo,
+                named_params! {
+                    ":username": user,
+                    ":start": ts(&start),
+                    ":end": ts(&end),
+                    ":project": project,
+                    ":task": task,
+                    ":entered_by": entered_by,
+                    ":group_id": group_id,
+                    ":event_id": event_id,
+                },
+            )?;
+            let do_id = store.conn.last_insert_rowid();
+            for tag in tags {
+                store.exec(Name::InsertTag, named_params! { ":name": tag })?;
+                store.exec(
+                    Name::InsertDoTag,
+                    named_params! {
+                        ":do_id": do_id,
+                        ":name": tag,
+                    },
+                )?;
+            }
+            Ok(inserted)
+        })
     }
 
     pub fn insert_project(
@@ -483,7 +1169,7 @@ impl ConnectedStore {
         username: String,
         start: time::SystemTime,
         end: time::SystemTime,
-        weekly: time::Duration,
+        pattern: &WeekPattern,
     ) -> StoreResult<usize> {
         self.exec(
             Name::InsertAvail,
@@ -491,16 +1177,75 @@ impl ConnectedStore {
                 ":username": username,
                 ":start": ts(&start),
                 ":end": ts(&end),
-                ":weekly": dur(&weekly),
+                ":weekly": dur(&pattern.weekly()),
+                ":pattern": pattern.to_column(),
             },
         )
     }
 
+    pub fn insert_absence(
+        &mut self,
+        username: Option<String>,
+        start: time::SystemTime,
+        end: time::SystemTime,
+        portion: f64,
+        label: &str,
+    ) -> StoreResult<usize> {
+        self.exec(
+            Name::InsertAbsence,
+            named_params! {
+                ":username": username,
+                ":start": ts(&start),
+                ":end": ts(&end),
+                ":portion": portion,
+                ":label": label,
+                ":source": None::<String>,
+            },
+        )
+    }
+
+    /// Sets the room holidays read from `source` to `events`, dropping those
+    /// of an earlier import of the same file.
+    pub fn replace_holidays(&mut self, source: &str, events: &[Event]) -> StoreResult<usize> {
+        self.exec(
+            Name::DeleteAbsenceFromSource,
+            named_params! {
+                ":source": source,
+            },
+        )?;
+        events.iter().try_fold(0, |inserted, event| {
+            self.exec(
+                Name::InsertAbsence,
+                named_params! {
+                    ":username": None::<String>,
+                    ":start": ts(&event.start),
+                    ":end": ts(&event.end),
+                    ":portion": 1.0,
+                    ":label": event.summary,
+                    ":source": source,
+                },
+            )
+            .map(|n| inserted + n)
+        })
+    }
+
+    /// Where the holiday calendars of `!holidays` are looked up: a
+    /// `holidays` directory next to room databases. Only plain file names
+    /// are accepted, so that no other file can be read.
+    pub fn holidays_path(&self, file: &str) -> Option<PathBuf> {
+        let plain = !file.is_empty() && !file.starts_with('.') && !file.contains(['/', '\\']);
+        match plain {
+            true => Some(Path::new(&self.root_dir).join("holidays").join(file)),
+            false => None,
+        }
+    }
+
     pub fn insert_intent(
         &mut self,
         username: String,
         project: String,
         amount: time::Duration,
+        start: Option<time::SystemTime>,
     ) -> StoreResult<usize> {
         self.exec(
             Name::InsertIntent,
@@ -508,6 +1253,102 @@ impl ConnectedStore {
                 ":project": project,
                 ":username": username,
                 ":amount": dur(&amount),
+                ":start": start.map(|t| ts(&t)),
+            },
+        )?;
+        self.log_intent(&username, &project, "set", &username)
+    }
+
+    /// Keeps the intent of `username` on `project` as it stands in its
+    /// history.
+    fn log_intent(
+        &mut self,
+        username: &str,
+        project: &str,
+        change: &str,
+        by: &str,
+    ) -> StoreResult<usize> {
+        self.exec(
+            Name::InsertIntentHistory,
+            named_params! {
+                ":username": username,
+                ":project": project,
+                ":change": change,
+                ":by": by,
+                ":at": ts(&time::SystemTime::now()),
+            },
+        )
+    }
+
+    /// Ends an intent at `end`, the number of intents ended being 0 when
+    /// there was none.
+    pub fn end_intent(
+        &mut self,
+        username: &str,
+        project: &str,
+        end: time::SystemTime,
+        by: &str,
+    ) -> StoreResult<usize> {
+        let ended = self.exec(
+            Name::UpdateIntentEnd,
+            named_params! {
+                ":username": username,
+                ":project": project,
+                ":end": ts(&end),
+            },
+        )?;
+        if ended > 0 {
+            self.log_intent(username, project, "end", by)?;
+        }
+        Ok(ended)
+    }
+
+    pub fn delete_intent(&mut self, username: &str, project: &str, by: &str) -> StoreResult<usize> {
+        self.log_intent(username, project, "remove", by)?;
+        self.exec(
+            Name::DeleteIntent,
+            named_params! {
+                ":username": username,
+                ":project": project,
+            },
+        )
+    }
+
+    /// Brings the end of an availability period of `username` forward to
+    /// `end`; periods ending earlier are left as they are.
+    pub fn end_avail(
+        &mut self,
+        username: &str,
+        id: i64,
+        end: time::SystemTime,
+    ) -> StoreResult<usize> {
+        self.exec(
+            Name::UpdateAvailEnd,
+            named_params! {
+                ":id": id,
+                ":username": username,
+                ":end": ts(&end),
+            },
+        )
+    }
+
+    pub fn delete_avail(&mut self, username: &str, id: i64) -> StoreResult<usize> {
+        self.exec(
+            Name::DeleteAvail,
+            named_params! {
+                ":id": id,
+                ":username": username,
+            },
+        )
+    }
+
+    pub fn insert_end_policy(&mut self, username: String, policy: EndPolicy) -> StoreResult<usize> {
+        self.exec(
+            Name::InsertEndPolicy,
+            named_params! {
+                ":username": username,
+                ":policy": policy.name(),
+                ":amount": dur(&policy.amount()),
             },
         )
     }
@@ -550,6 +1391,78 @@ impl ConnectedStore {
         )
     }
 
+    pub fn update_priority(&mut self, name: String, priority: i64) -> StoreResult<usize> {
+        self.exec(
+            Name::UpdatePriority,
+            named_params! {
+                ":name": name,
+                ":priority": priority,
+            },
+        )
+    }
+
+    pub fn insert_setting(&mut self, name: &str, value: &str) -> StoreResult<usize> {
+        self.exec(
+            Name::InsertSetting,
+            named_params! {
+                ":name": name,
+                ":value": value,
+            },
+        )
+    }
+
+    pub fn select_setting(&self, name: &str) -> StoreResult<Option<String>> {
+        self.map_rows(
+            Name::SelectSetting,
+            named_params! {
+                ":name": name,
+            },
+            |row| row.get::<_, String>(1),
+        )
+        .map(|values| values.into_iter().next())
+    }
+
+    /// The feed token of `username`, made up the first time it is asked
+    /// for, or made anew when `renew` is set so the former stops working.
+    pub fn feed_token(&mut self, username: &str, renew: bool) -> StoreResult<String> {
+        let current = self
+            .map_rows(
+                Name::SelectFeedForUser,
+                named_params! {
+                    ":username": username,
+                },
+                Feed::from_row,
+            )?
+            .into_iter()
+            .next();
+        match current {
+            Some(feed) if !renew => Ok(feed.token),
+            _ => {
+                let token = Uuid::new_v4().to_simple().to_string();
+                self.exec(
+                    Name::InsertFeed,
+                    named_params! {
+                        ":token": token,
+                        ":username": username,
+                        ":created_at": ts(&time::SystemTime::now()),
+                    },
+                )?;
+                Ok(token)
+            }
+        }
+    }
+
+    pub fn select_feed(&self, token: &str) -> StoreResult<Option<Feed>> {
+        self.map_rows(
+            Name::SelectFeed,
+            named_params! {
+                ":token": token,
+            },
+            Feed::from_row,
+        )
+        .map(|feeds| feeds.into_iter().next())
+    }
+
     pub fn update_meta(&mut self, name: String, is_meta: bool) -> StoreResult<usize> {
         self.exec(
             Name::UpdateMeta,
@@ -613,6 +1526,21 @@ impl ConnectedStore {
         )
     }
 
+    pub fn select_task_for_user(
+        &self,
+        user: String,
+        since: time::SystemTime,
+    ) -> StoreResult<Vec<TaskRecord>> {
+        self.map_rows(
+            Name::SelectTaskForUser,
+            named_params! {
+                ":user": user,
+                ":since": ts(&since),
+            },
+            TaskRecord::from_row,
+        )
+    }
+
     pub fn select_all_project_info(&self) -> StoreResult<Vec<ProjectRecord>> {
         self.map_rows(
             Name::SelectAllProjectInfo,
@@ -635,7 +1563,11 @@ impl ConnectedStore {
         })
     }
 
-    pub fn select_project(&self, project_name: String) -> StoreResult<Vec<AggregatedTaskRecord>> {
+    pub fn select_project(
+        &self,
+        project_name: String,
+        tag: Option<String>,
+    ) -> StoreResult<Vec<AggregatedTaskRecord>> {
         self.select_project_info(project_name.clone())
             .and_then(|project| {
                 if project.is_meta {
@@ -647,7 +1579,9 @@ impl ConnectedStore {
                                     .map(|parent_id| parent_id == project.id)
                                     .unwrap_or(false)
                             })
-                            .filter_map(|project| self.select_project(project.name.clone()).ok())
+                            .filter_map(|project| {
+                                self.select_project(project.name.clone(), tag.clone()).ok()
+                            })
                             .flatten()
                             .collect()
                     })
@@ -656,6 +1590,7 @@ impl ConnectedStore {
                         Name::SelectProject,
                         named_params! {
                             ":project": project_name.clone(),
+                            ":tag": tag.clone(),
                         },
                         AggregatedTaskRecord::from_row,
                     )
@@ -663,7 +1598,11 @@ impl ConnectedStore {
             })
     }
 
-    pub fn select_project_detail(&self, project_name: String) -> StoreResult<Vec<TaskRecord>> {
+    pub fn select_project_detail(
+        &self,
+        project_name: String,
+        tag: Option<String>,
+    ) -> StoreResult<Vec<TaskRecord>> {
         self.select_project_info(project_name.clone())
             .and_then(|project| {
                 if project.is_meta {
@@ -676,7 +1615,8 @@ impl ConnectedStore {
                                     .unwrap_or(false)
                             })
                             .filter_map(|project| {
-                                self.select_project_detail(project.name.clone()).ok()
+                                self.select_project_detail(project.name.clone(), tag.clone())
+                                    .ok()
                             })
                             .flatten()
                             .collect()
@@ -686,6 +1626,7 @@ impl ConnectedStore {
                         Name::SelectProjectDetail,
                         named_params! {
                             ":project": project_name.clone(),
+                            ":tag": tag.clone(),
                         },
                         TaskRecord::from_row,
                     )
@@ -707,12 +1648,14 @@ impl ConnectedStore {
         &self,
         user: String,
         since: time::SystemTime,
+        tag: Option<String>,
     ) -> StoreResult<Vec<AggregatedTaskRecord>> {
         self.map_rows(
             Name::SelectUser,
             named_params! {
                 ":user": user,
                 ":since": ts(&since),
+                ":tag": tag,
             },
             AggregatedTaskRecord::from_row,
         )
@@ -739,6 +1682,22 @@ impl ConnectedStore {
         )
     }
 
+    pub fn select_absence_all(&self) -> StoreResult<Vec<Absence>> {
+        self.map_rows(Name::SelectAbsence, named_params! {}, Absence::from_row)
+    }
+
+    /// Time logged per user and project, counted from the start of their
+    /// intent, a running task up to now.
+    pub fn select_done_for_intents(&self) -> StoreResult<Vec<DoneRecord>> {
+        self.map_rows(
+            Name::SelectDoneForIntents,
+            named_params! {
+                ":now": ts(&time::SystemTime::now()),
+            },
+            DoneRecord::from_row,
+        )
+    }
+
     pub fn select_intent_for_project(&self, project: String) -> StoreResult<Vec<Intent>> {
         self.map_rows(
             Name::SelectIntentForProject,
@@ -770,6 +1729,251 @@ impl ConnectedStore {
         )
     }
 
+    pub fn select_intent_history(
+        &self,
+        username: Option<&str>,
+        project: Option<&str>,
+    ) -> StoreResult<Vec<IntentChange>> {
+        self.map_rows(
+            Name::SelectIntentHistory,
+            named_params! {
+                ":username": username,
+                ":project": project,
+            },
+            IntentChange::from_row,
+        )
+    }
+
+    pub fn select_end_policy_all(&self) -> StoreResult<Vec<EndPolicyRecord>> {
+        self.map_rows(
+            Name::SelectEndPolicy,
+            named_params! {},
+            EndPolicyRecord::from_row,
+        )
+    }
+
+    pub fn select_end_policy_for_user(&self, user: String) -> StoreResult<EndPolicy> {
+        self.map_rows(
+            Name::SelectEndPolicyForUser,
+            named_params! {
+                ":user": user,
+            },
+            EndPolicyRecord::from_row,
+        )
+        .map(|records| {
+            records
+                .into_iter()
+                .next()
+                .map(|r| r.policy)
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn select_tags(&self) -> StoreResult<Vec<String>> {
+        self.map_rows(Name::SelectTags, named_params! {}, |row| row.get(0))
+    }
+
+    pub fn select_tags_for_do(&self, do_id: i64) -> StoreResult<Vec<String>> {
+        self.map_rows(
+            Name::SelectTagsForDo,
+            named_params! {
+                ":do_id": do_id,
+            },
+            |row| row.get(0),
+        )
+    }
+
+    pub fn select_tag_hours(
+        &self,
+        since: time::SystemTime,
+        until: time::SystemTime,
+    ) -> StoreResult<Vec<TagRecord>> {
+        self.map_rows(
+            Name::SelectTagHours,
+            named_params! {
+                ":since": ts(&since),
+                ":until": ts(&until),
+            },
+            TagRecord::from_row,
+        )
+    }
+
+    pub fn insert_role(&mut self, username: String, role: Role) -> StoreResult<usize> {
+        self.exec(
+            Name::InsertRole,
+            named_params! {
+                ":username": username,
+                ":role": role.name(),
+            },
+        )
+    }
+
+    pub fn delete_role(&mut self, username: String) -> StoreResult<usize> {
+        self.exec(
+            Name::DeleteRole,
+            named_params! {
+                ":username": username,
+            },
+        )
+    }
+
+    /// Keeps `body` until `user` confirms it, in place of whatever they left
+    /// pending before.
+    pub fn insert_pending(
+        &mut self,
+        username: String,
+        body: String,
+        created: time::SystemTime,
+    ) -> StoreResult<usize> {
+        self.exec(
+            Name::InsertPending,
+            named_params! {
+                ":username": username,
+                ":body": body,
+                ":created": ts(&created),
+            },
+        )
+    }
+
+    pub fn select_pending_for_user(&self, username: &str) -> StoreResult<Option<PendingRecord>> {
+        self.map_rows(
+            Name::SelectPendingForUser,
+            named_params! {
+                ":username": username,
+            },
+            PendingRecord::from_row,
+        )
+        .map(|records| records.into_iter().next())
+    }
+
+    pub fn delete_pending(&mut self, username: String) -> StoreResult<usize> {
+        self.exec(
+            Name::DeletePending,
+            named_params! {
+                ":username": username,
+            },
+        )
+    }
+
+    /// Runs `f` unless the event `event_id` was already handled in this
+    /// room, recording it in the same transaction as whatever `f` writes so
+    /// that a replayed event never runs twice.
+    pub fn once<T, F>(&mut self, event_id: &str, f: F) -> StoreResult<Option<T>>
+    where
+        F: FnOnce(&mut ConnectedStore) -> T,
+    {
+        self.conn.execute_batch("BEGIN").map_err(|err| {
+            sql_error(&err);
+            StoreError::LogRecord
+        })?;
+        let inserted = self.exec(
+            Name::InsertEvent,
+            named_params! {
+                ":id": event_id,
+                ":received": ts(&time::SystemTime::now()),
+            },
+        );
+        self.event_id = Some(String::from(event_id));
+        let result = match inserted {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(f(self))),
+            Err(err) => Err(err),
+        };
+        self.event_id = None;
+        let end = match result {
+            Ok(Some(_)) => "COMMIT",
+            _ => "ROLLBACK",
+        };
+        self.conn.execute_batch(end).map_err(|err| {
+            sql_error(&err);
+            StoreError::LogRecord
+        })?;
+        result
+    }
+
+    /// Removes the records created by the event `original`, and has those
+    /// created next carry its id instead of the one being handled, so that
+    /// an edit of an edit still finds them. Meant to be called within `once`,
+    /// and `tentative` to keep them when the edit records nothing.
+    /// Records are left alone, and `None` returned, unless `editor` sent
+    /// `original`: the one who entered each of them.
+    pub fn replace_event(&mut self, original: &str, editor: &str) -> StoreResult<Option<usize>> {
+        let records = self.select_do_for_event(original)?;
+        let sent_by_editor = records
+            .iter()
+            .all(|rec| rec.entered_by.as_deref().unwrap_or(&rec.username) == editor);
+        if !sent_by_editor {
+            return Ok(None);
+        }
+        for rec in records.iter() {
+            self.delete_do(rec.id)?;
+        }
+        self.event_id = Some(String::from(original));
+        Ok(Some(records.len()))
+    }
+
+    /// The records created by the event `event_id`, edits of it included.
+    pub fn select_do_for_event(&self, event_id: &str) -> StoreResult<Vec<TaskRecord>> {
+        self.map_rows(
+            Name::SelectDoForEvent,
+            named_params! {
+                ":event_id": event_id,
+            },
+            TaskRecord::from_row,
+        )
+    }
+
+    /// Stops the records created next from carrying the id of the event
+    /// being handled, for commands whose effect an edit could not replace.
+    pub fn detach_event(&mut self) {
+        self.event_id = None;
+    }
+
+    pub fn select_role_all(&self) -> StoreResult<Vec<RoleRecord>> {
+        self.map_rows(Name::SelectRole, named_params! {}, RoleRecord::from_row)
+    }
+
+    /// The role of `user` in this room: the one granted, or member. Until
+    /// someone is made admin, everyone is, so that a room can get started.
+    pub fn select_role_for_user(&self, user: &str) -> StoreResult<Role> {
+        self.select_role_all().map(|records| {
+            match (
+                records.iter().find(|r| r.username == user),
+                records.iter().any(|r| r.role == Role::Admin),
+            ) {
+                (Some(record), true) => record.role,
+                (_, false) => Role::Admin,
+                (None, true) => Role::Member,
+            }
+        })
+    }
+
+    /// The record `id` along with the others of its group.
+    pub fn select_group(&self, id: i64) -> StoreResult<Vec<TaskRecord>> {
+        self.map_rows(
+            Name::SelectGroup,
+            named_params! {
+                ":id": id,
+            },
+            TaskRecord::from_row,
+        )
+    }
+
+    pub fn delete_do(&self, id: i64) -> StoreResult<usize> {
+        self.exec(
+            Name::DeleteNotificationForDo,
+            named_params! {
+                ":id": id,
+            },
+        )?;
+        self.exec(
+            Name::DeleteDo,
+            named_params! {
+                ":id": id,
+            },
+        )
+    }
+
     pub fn update_task_end(&self, id: i64, end: time::SystemTime) -> StoreResult<usize> {
         self.exec(
             Name::UpdateTaskEnd,
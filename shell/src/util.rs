@@ -1,7 +1,7 @@
 use chrono;
 use chrono::TimeZone;
 use chrono_humanize;
-use html::{td, tr, Element};
+use html::{escape, td, tr, Element};
 use std::convert::{TryFrom, TryInto};
 use std::time;
 
@@ -71,7 +71,7 @@ pub fn st_from_date_time(t: &chrono::DateTime<chrono::Local>) -> time::SystemTim
 }
 
 pub fn make_table_row(cells: Vec<String>) -> Element {
-    let inner: Vec<Element> = cells.iter().map(|s| td(s.clone())).collect();
+    let inner: Vec<Element> = cells.iter().map(|s| td(escape(s))).collect();
     tr(inner)
 }
 
@@ -131,3 +131,70 @@ where
 {
     s.into()
 }
+
+/// Percent-encodes a project name for use in a URL path, keeping the
+/// `/` between client and project.
+pub fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                String::from(b as char)
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Reverses `url_encode`, leaving malformed sequences as they are.
+pub fn url_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                decoded.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A stable id for `room` that tells nothing of its name, for the uids of
+/// calendar events: the name of a room opens all its pages.
+pub fn room_uid(room: &str) -> String {
+    // FNV-1a, which unlike the hasher of std stays the same across releases
+    let hash = room.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn room_uids_are_stable_and_opaque() {
+        assert_eq!(room_uid(""), "cbf29ce484222325");
+        assert_eq!(room_uid("a"), "af63dc4c8601ec8c");
+        assert_eq!(room_uid("secret-room"), room_uid("secret-room"));
+        assert!(!room_uid("secret-room").contains("secret"));
+    }
+
+    #[test]
+    fn url_encoding_round_trips() {
+        let name = "atelier/réunion client";
+        assert_eq!(url_encode(name), "atelier/r%C3%A9union%20client");
+        assert_eq!(url_decode(&url_encode(name)), name);
+    }
+}